@@ -1,3 +1,9 @@
+mod partition;
+mod query;
+
+use partition::{chunk_collection, chunks, divide_in_two, split_even, split_exact};
+use query::{Pattern, Query, Value};
+
 fn main() {
     //16.0 Pattern Matching
     let input = 'x';
@@ -18,25 +24,27 @@ fn main() {
 
 
     // 16.1 Destructing Enums
-    enum Result {
-        Ok(i32),
-        Err(String),
+    let n = 100;
+    match divide_in_two(n) {
+        Ok(half) => println!("{n} divided in two is {half}"),
+        Err(e) => println!("sorry, an error happened: {e}"),
     }
 
-    fn divide_in_two(n: i32) -> Result {
-        if n % 2 == 0 {
-            Result::Ok(n / 2)
-        } else {
-            Result::Err(format!("cannot divide {n} into two equal parts"))
-        }
+    match split_even(10, 3) {
+        Ok(parts) => println!("10 split into 3 near-equal parts is {parts:?}"),
+        Err(e) => println!("sorry, an error happened: {e}"),
     }
 
-    let n = 100;
-    match divide_in_two(n) {
-        Result::Ok(half) => println!("{n} divided in two is {half}"),
-        Result::Err(msg) => println!("sorry, an error happened: {msg}"),
+    let books = ["a", "b", "c", "d", "e"];
+    if let Ok(chunks) = chunks(&books, 2) {
+        println!("{books:?} in two chunks is {chunks:?}");
+    }
+
+    if let Err(e) = split_exact(10, 3) {
+        println!("split_exact failed: {e}");
     }
 
+    println!("{:?}", chunk_collection(vec![1, 2, 3], 2));
 
 
 
@@ -54,6 +62,7 @@ fn main() {
         y: u32,
     }
 
+    #[allow(clippy::disallowed_names)]
     let foo = Foo { x: (1, 2), y: 3};
 
     match foo {
@@ -100,4 +109,32 @@ fn main() {
         (x, _) if x % 2 == 1 => println!("The first one is odd"),
         _                    => println!("No correlation..."),
     }
+
+
+
+
+
+
+
+
+
+
+    // The same patterns at runtime, matched against JSON-like values.
+    let events = [
+        r#"{"kind": "move", "at": [0, 0]}"#,
+        r#"{"kind": "move", "at": [3, -3]}"#,
+        r#"{"kind": "quit"}"#,
+    ];
+    let events: Vec<Value> = events.iter().map(|e| Value::parse(e).unwrap()).collect();
+    let query = Query::parse(r#"{ kind: "move", at: [x, y] } if x + y == 0"#).unwrap();
+    for (event, bindings) in query.filter(&events) {
+        println!("{event} matched with {bindings:?}");
+    }
+
+    let quit = Pattern::parse(r#"{ kind: "quit" }"#).unwrap();
+    for event in &events {
+        if quit.matches(event).is_some() {
+            println!("{:?} is a quit event", event.get("kind"));
+        }
+    }
 }
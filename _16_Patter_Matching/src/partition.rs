@@ -0,0 +1,264 @@
+// Partitioning integers and collections into N parts.
+// Generalises the `divide_in_two` demo: exact splits return a typed error
+// explaining why the division failed, near-equal splits hand the remainder
+// out one by one to the first parts.
+
+use std::fmt;
+use std::ops::Add;
+
+/// Integer types that can be partitioned.
+pub trait Integer: Copy + PartialEq + PartialOrd + Add<Output = Self> + fmt::Debug + fmt::Display {
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// `None` if `n` does not fit in `Self`.
+    fn from_usize(n: usize) -> Option<Self>;
+    fn div_euclid(self, rhs: Self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn from_usize(n: usize) -> Option<Self> {
+                    <$t>::try_from(n).ok()
+                }
+
+                fn div_euclid(self, rhs: Self) -> Self {
+                    <$t>::div_euclid(self, rhs)
+                }
+
+                fn rem_euclid(self, rhs: Self) -> Self {
+                    <$t>::rem_euclid(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// The most parts any split returns. Every part is a `Vec` element, so an
+/// unbounded count would let `split_exact(0u64, usize::MAX)` abort on allocation.
+pub const MAX_PARTS: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionError<T> {
+    /// Asked for zero parts.
+    ZeroParts,
+    /// `parts` is above `MAX_PARTS`, or cannot be represented in the value's
+    /// type (e.g. 300 parts of an `i8`).
+    TooManyParts { parts: usize },
+    /// `value` is not a multiple of `parts`; `remainder` is what is left over.
+    Indivisible { value: T, parts: usize, remainder: T },
+}
+
+impl<T: fmt::Display> fmt::Display for PartitionError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PartitionError::ZeroParts => write!(f, "cannot divide into zero parts"),
+            PartitionError::TooManyParts { parts } => {
+                write!(f, "cannot divide into {parts} parts, too many for the value's type or above {MAX_PARTS}")
+            }
+            PartitionError::Indivisible { value, parts, remainder } => write!(
+                f,
+                "cannot divide {value} into {parts} equal parts ({remainder} left over)"
+            ),
+        }
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> std::error::Error for PartitionError<T> {}
+
+fn parts_as<T: Integer>(parts: usize) -> Result<T, PartitionError<T>> {
+    if parts == 0 {
+        return Err(PartitionError::ZeroParts);
+    }
+    if parts > MAX_PARTS {
+        return Err(PartitionError::TooManyParts { parts });
+    }
+    T::from_usize(parts).ok_or(PartitionError::TooManyParts { parts })
+}
+
+/// Splits `value` into `parts` equal parts, or explains why it can't.
+pub fn split_exact<T: Integer>(value: T, parts: usize) -> Result<Vec<T>, PartitionError<T>> {
+    let p = parts_as::<T>(parts)?;
+    let remainder = value.rem_euclid(p);
+    if remainder != T::ZERO {
+        return Err(PartitionError::Indivisible { value, parts, remainder });
+    }
+    Ok(vec![value.div_euclid(p); parts])
+}
+
+/// Splits `value` into `parts` parts that differ by at most one.
+/// The parts sum to `value`; the larger ones come first.
+pub fn split_even<T: Integer>(value: T, parts: usize) -> Result<Vec<T>, PartitionError<T>> {
+    let p = parts_as::<T>(parts)?;
+    let quotient = value.div_euclid(p);
+    let remainder = value.rem_euclid(p);
+
+    // rem_euclid is never negative and is below `parts`, so the first
+    // `remainder` parts each take one extra.
+    let mut handed_out = T::ZERO;
+    let mut result = Vec::with_capacity(parts);
+    for _ in 0..parts {
+        if handed_out < remainder {
+            result.push(quotient + T::ONE);
+            handed_out = handed_out + T::ONE;
+        } else {
+            result.push(quotient);
+        }
+    }
+    Ok(result)
+}
+
+/// Like `split_exact(n, 2)`, kept for the 16.1 demo.
+pub fn divide_in_two(n: i32) -> Result<i32, PartitionError<i32>> {
+    split_exact(n, 2).map(|halves| halves[0])
+}
+
+/// Splits a slice into `parts` contiguous chunks whose lengths differ by at most one.
+/// If there are more parts than elements, the trailing chunks are empty.
+pub fn chunks<T>(items: &[T], parts: usize) -> Result<Vec<&[T]>, PartitionError<usize>> {
+    let sizes = split_even(items.len(), parts)?;
+    let mut rest = items;
+    let mut result = Vec::with_capacity(parts);
+    for size in sizes {
+        let (chunk, tail) = rest.split_at(size);
+        result.push(chunk);
+        rest = tail;
+    }
+    Ok(result)
+}
+
+/// Same as `chunks`, but takes ownership of any collection.
+pub fn chunk_collection<I: IntoIterator>(
+    items: I,
+    parts: usize,
+) -> Result<Vec<Vec<I::Item>>, PartitionError<usize>> {
+    let items: Vec<I::Item> = items.into_iter().collect();
+    let sizes = split_even(items.len(), parts)?;
+    let mut iter = items.into_iter();
+    Ok(sizes
+        .into_iter()
+        .map(|size| iter.by_ref().take(size).collect())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Values around the multiples and remainders the property tests care about.
+    const VALUES: [i64; 16] = [-10_000, -9_999, -101, -100, -49, -1, 0, 1, 2, 48, 49, 50, 99, 100, 9_999, 10_000];
+
+    #[test]
+    fn test_divide_in_two() {
+        assert_eq!(divide_in_two(100), Ok(50));
+        assert_eq!(
+            divide_in_two(7),
+            Err(PartitionError::Indivisible { value: 7, parts: 2, remainder: 1 })
+        );
+    }
+
+    #[test]
+    fn test_split_exact() {
+        assert_eq!(split_exact(12u32, 3), Ok(vec![4, 4, 4]));
+        assert_eq!(split_exact(-12i64, 4), Ok(vec![-3, -3, -3, -3]));
+        assert_eq!(split_exact(5i32, 0), Err(PartitionError::ZeroParts));
+        assert_eq!(
+            split_exact(10i8, 300),
+            Err(PartitionError::TooManyParts { parts: 300 })
+        );
+        assert_eq!(
+            split_exact(0u64, usize::MAX),
+            Err(PartitionError::TooManyParts { parts: usize::MAX })
+        );
+        assert_eq!(split_exact(0u64, MAX_PARTS).map(|parts| parts.len()), Ok(MAX_PARTS));
+        assert_eq!(
+            chunks(&[1, 2], MAX_PARTS + 1),
+            Err(PartitionError::TooManyParts { parts: MAX_PARTS + 1 })
+        );
+    }
+
+    #[test]
+    fn test_split_even() {
+        assert_eq!(split_even(10u8, 3), Ok(vec![4, 3, 3]));
+        assert_eq!(split_even(-7i32, 2), Ok(vec![-3, -4]));
+        assert_eq!(split_even(2usize, 4), Ok(vec![1, 1, 0, 0]));
+        assert_eq!(split_even(i8::MAX, 2), Ok(vec![64, 63]));
+    }
+
+    #[test]
+    fn test_error_message() {
+        let err = split_exact(10, 3).unwrap_err();
+        assert_eq!(err.to_string(), "cannot divide 10 into 3 equal parts (1 left over)");
+    }
+
+    #[test]
+    fn test_chunks() {
+        let v = [1, 2, 3, 4, 5, 6, 7];
+        let chunks = chunks(&v, 3).unwrap();
+        assert_eq!(chunks, vec![&[1, 2, 3][..], &[4, 5][..], &[6, 7][..]]);
+        assert_eq!(super::chunks(&v, 0), Err(PartitionError::ZeroParts));
+    }
+
+    #[test]
+    fn test_chunk_collection() {
+        let words = vec!["a", "b", "c", "d", "e"];
+        let chunks = chunk_collection(words, 2).unwrap();
+        assert_eq!(chunks, vec![vec!["a", "b", "c"], vec!["d", "e"]]);
+
+        let chunks = chunk_collection(0..2, 3).unwrap();
+        assert_eq!(chunks, vec![vec![0], vec![1], vec![]]);
+    }
+
+    #[test]
+    fn prop_split_even_sums_and_is_balanced() {
+        for (value, parts) in VALUES.into_iter().flat_map(|value| (1..50).map(move |parts| (value, parts))) {
+            let split = split_even(value, parts).unwrap();
+
+            assert_eq!(split.len(), parts);
+            assert_eq!(split.iter().sum::<i64>(), value);
+            let max = *split.iter().max().unwrap();
+            let min = *split.iter().min().unwrap();
+            assert!(max - min <= 1, "{value} / {parts} -> {split:?}");
+            assert!(split.windows(2).all(|w| w[0] >= w[1]));
+        }
+    }
+
+    #[test]
+    fn prop_split_exact_agrees_with_remainder() {
+        for (value, parts) in VALUES.into_iter().flat_map(|value| (1..50).map(move |parts| (value as i32, parts))) {
+            match split_exact(value, parts) {
+                Ok(split) => {
+                    assert_eq!(value % parts as i32, 0);
+                    assert_eq!(split, split_even(value, parts).unwrap());
+                }
+                Err(PartitionError::Indivisible { remainder, .. }) => {
+                    assert_ne!(remainder, 0);
+                    assert_eq!(remainder, value.rem_euclid(parts as i32));
+                }
+                Err(e) => panic!("unexpected error {e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn prop_chunks_preserve_order_and_balance() {
+        for (len, parts) in (0..80).flat_map(|len| (1..30).map(move |parts| (len, parts))) {
+            let items: Vec<usize> = (0..len).collect();
+            let chunks = chunks(&items, parts).unwrap();
+
+            assert_eq!(chunks.len(), parts);
+            assert_eq!(chunks.concat(), items);
+            let max = chunks.iter().map(|c| c.len()).max().unwrap();
+            let min = chunks.iter().map(|c| c.len()).min().unwrap();
+            assert!(max - min <= 1);
+        }
+    }
+}
@@ -0,0 +1,1101 @@
+// Runtime version of the 16.x patterns.
+// `match` only works on types known at compile time, so this module parses
+// patterns like `{ x: [1, b], y } if b == y` and matches them against a dynamic
+// `Value` tree, returning the bindings. Semantics follow Rust where they can:
+// object patterns must list every field unless they end with `..`, a name may
+// only be bound once, and every `|` alternative binds the same names.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// Parses a JSON document.
+    pub fn parse(input: &str) -> Result<Value, ParseError> {
+        let mut parser = Parser::new(input)?;
+        let value = parser.value()?;
+        parser.expect_end()?;
+        Ok(value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.get(key),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Number(n) => write!(f, "{n}"),
+            Value::String(s) => write!(f, "{s:?}"),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{key:?}: {value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Value::Number(f64::from(n))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(items: Vec<Value>) -> Self {
+        Value::Array(items)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// `_`
+    Wildcard,
+    /// `1`, `"login"`, `true`, `null`
+    Literal(Value),
+    /// `0..=9`
+    Range(f64, f64),
+    /// `x`, or `x @ pattern`
+    Bind(String, Box<Pattern>),
+    /// `[first, .., last]`; `rest` is the index of `..` if present.
+    Array { items: Vec<Pattern>, rest: Option<usize> },
+    /// `{ x: (1, b), y, .. }`
+    Object { fields: Vec<(String, Pattern)>, rest: bool },
+    /// `"a" | "s" | "w" | "d"`
+    Or(Vec<Pattern>),
+}
+
+pub type Bindings = BTreeMap<String, Value>;
+
+impl Pattern {
+    pub fn parse(input: &str) -> Result<Pattern, ParseError> {
+        let mut parser = Parser::new(input)?;
+        let pattern = parser.pattern()?;
+        parser.expect_end()?;
+        Ok(pattern)
+    }
+
+    pub fn matches(&self, value: &Value) -> Option<Bindings> {
+        let mut bindings = Bindings::new();
+        if self.match_into(value, &mut bindings) {
+            Some(bindings)
+        } else {
+            None
+        }
+    }
+
+    fn match_into(&self, value: &Value, bindings: &mut Bindings) -> bool {
+        match (self, value) {
+            (Pattern::Wildcard, _) => true,
+            (Pattern::Literal(lit), _) => lit == value,
+            (Pattern::Range(lo, hi), Value::Number(n)) => lo <= n && n <= hi,
+            (Pattern::Range(..), _) => false,
+            (Pattern::Bind(name, inner), _) => {
+                if !inner.match_into(value, bindings) {
+                    return false;
+                }
+                bindings.insert(name.clone(), value.clone());
+                true
+            }
+            (Pattern::Array { items, rest: None }, Value::Array(values)) => {
+                items.len() == values.len()
+                    && items.iter().zip(values).all(|(p, v)| p.match_into(v, bindings))
+            }
+            (Pattern::Array { items, rest: Some(at) }, Value::Array(values)) => {
+                let (before, after) = items.split_at(*at);
+                if values.len() < before.len() + after.len() {
+                    return false;
+                }
+                let tail = &values[values.len() - after.len()..];
+                before.iter().zip(values).all(|(p, v)| p.match_into(v, bindings))
+                    && after.iter().zip(tail).all(|(p, v)| p.match_into(v, bindings))
+            }
+            (Pattern::Object { fields, rest }, Value::Object(values)) => {
+                if !rest && fields.len() != values.len() {
+                    return false;
+                }
+                fields.iter().all(|(key, p)| match values.get(key) {
+                    Some(v) => p.match_into(v, bindings),
+                    None => false,
+                })
+            }
+            (Pattern::Or(alternatives), _) => alternatives.iter().any(|alt| {
+                let mut attempt = bindings.clone();
+                if alt.match_into(value, &mut attempt) {
+                    *bindings = attempt;
+                    true
+                } else {
+                    false
+                }
+            }),
+            _ => false,
+        }
+    }
+
+    /// Names bound by this pattern, in the order they appear.
+    fn bound_names(&self, names: &mut Vec<String>) {
+        match self {
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range(..) => {}
+            Pattern::Bind(name, inner) => {
+                names.push(name.clone());
+                inner.bound_names(names);
+            }
+            Pattern::Array { items, .. } => items.iter().for_each(|p| p.bound_names(names)),
+            Pattern::Object { fields, .. } => fields.iter().for_each(|(_, p)| p.bound_names(names)),
+            // Alternatives bind the same names, checked by the parser.
+            Pattern::Or(alternatives) => alternatives[0].bound_names(names),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    /// `pos` is where the name appears in the query text.
+    Var { name: String, pos: usize },
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// `None` when the operands have the wrong types, e.g. `"a" + 1`.
+    fn eval(&self, bindings: &Bindings) -> Option<Value> {
+        match self {
+            Expr::Literal(value) => Some(value.clone()),
+            Expr::Var { name, .. } => bindings.get(name).cloned(),
+            Expr::Not(inner) => match inner.eval(bindings)? {
+                Value::Bool(b) => Some(Value::Bool(!b)),
+                _ => None,
+            },
+            Expr::Neg(inner) => match inner.eval(bindings)? {
+                Value::Number(n) => Some(Value::Number(-n)),
+                _ => None,
+            },
+            Expr::Binary(BinOp::And, l, r) => {
+                let l = l.eval(bindings)?;
+                match l {
+                    Value::Bool(false) => Some(l),
+                    Value::Bool(true) => match r.eval(bindings)? {
+                        r @ Value::Bool(_) => Some(r),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            Expr::Binary(BinOp::Or, l, r) => {
+                let l = l.eval(bindings)?;
+                match l {
+                    Value::Bool(true) => Some(l),
+                    Value::Bool(false) => match r.eval(bindings)? {
+                        r @ Value::Bool(_) => Some(r),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            Expr::Binary(op, l, r) => {
+                let (l, r) = (l.eval(bindings)?, r.eval(bindings)?);
+                match (op, &l, &r) {
+                    (BinOp::Eq, _, _) => Some(Value::Bool(l == r)),
+                    (BinOp::Ne, _, _) => Some(Value::Bool(l != r)),
+                    (BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge, _, _) => {
+                        let ord = match (&l, &r) {
+                            (Value::Number(a), Value::Number(b)) => a.partial_cmp(b)?,
+                            (Value::String(a), Value::String(b)) => a.cmp(b),
+                            _ => return None,
+                        };
+                        let result = match op {
+                            BinOp::Lt => ord.is_lt(),
+                            BinOp::Le => ord.is_le(),
+                            BinOp::Gt => ord.is_gt(),
+                            _ => ord.is_ge(),
+                        };
+                        Some(Value::Bool(result))
+                    }
+                    (BinOp::Add, Value::String(a), Value::String(b)) => {
+                        Some(Value::String(format!("{a}{b}")))
+                    }
+                    (_, Value::Number(a), Value::Number(b)) => {
+                        let n = match op {
+                            BinOp::Add => a + b,
+                            BinOp::Sub => a - b,
+                            BinOp::Mul => a * b,
+                            BinOp::Div => a / b,
+                            BinOp::Rem => a % b,
+                            _ => unreachable!("logical and comparison ops handled above"),
+                        };
+                        Some(Value::Number(n))
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn vars<'e>(&'e self, out: &mut Vec<(&'e str, usize)>) {
+        match self {
+            Expr::Literal(_) => {}
+            Expr::Var { name, pos } => out.push((name, *pos)),
+            Expr::Not(inner) | Expr::Neg(inner) => inner.vars(out),
+            Expr::Binary(_, l, r) => {
+                l.vars(out);
+                r.vars(out);
+            }
+        }
+    }
+}
+
+/// A pattern with an optional `if` guard, e.g. `(x, y) if x + y == 0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pattern: Pattern,
+    guard: Option<Expr>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, ParseError> {
+        let mut parser = Parser::new(input)?;
+        let pattern = parser.pattern()?;
+
+        let guard = if parser.eat_keyword("if") {
+            let guard = parser.expr()?;
+            let mut bound = Vec::new();
+            pattern.bound_names(&mut bound);
+            let mut used = Vec::new();
+            guard.vars(&mut used);
+            if let Some((name, pos)) = used.into_iter().find(|(name, _)| !bound.iter().any(|b| b == name)) {
+                return Err(ParseError::new(pos, format!("`{name}` is not bound by the pattern")));
+            }
+            Some(guard)
+        } else {
+            None
+        };
+
+        parser.expect_end()?;
+        Ok(Query { pattern, guard })
+    }
+
+    /// Returns the bindings if the pattern matches and the guard holds.
+    /// A guard that fails to evaluate (e.g. comparing a string to a number) counts as false.
+    pub fn matches(&self, value: &Value) -> Option<Bindings> {
+        let bindings = self.pattern.matches(value)?;
+        match &self.guard {
+            None => Some(bindings),
+            Some(guard) => match guard.eval(&bindings) {
+                Some(Value::Bool(true)) => Some(bindings),
+                _ => None,
+            },
+        }
+    }
+
+    /// Keeps the values that match, paired with their bindings.
+    pub fn filter<'a, I>(&'a self, values: I) -> impl Iterator<Item = (&'a Value, Bindings)> + 'a
+    where
+        I: IntoIterator<Item = &'a Value>,
+        I::IntoIter: 'a,
+    {
+        values
+            .into_iter()
+            .filter_map(move |value| self.matches(value).map(|b| (value, b)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset into the input.
+    pub pos: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(pos: usize, message: impl Into<String>) -> Self {
+        ParseError { pos, message: message.into() }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.pos)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+    DotDot,
+    DotDotEq,
+    At,
+    Pipe,
+    Bang,
+    Op(BinOp),
+    Ident(String),
+    Number(f64),
+    Str(String),
+    End,
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let two = &bytes[i..(i + 2).min(bytes.len())];
+
+        let (token, len) = match c {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                i += 1;
+                continue;
+            }
+            b'[' => (Token::LBracket, 1),
+            b']' => (Token::RBracket, 1),
+            b'{' => (Token::LBrace, 1),
+            b'}' => (Token::RBrace, 1),
+            b'(' => (Token::LParen, 1),
+            b')' => (Token::RParen, 1),
+            b',' => (Token::Comma, 1),
+            b':' => (Token::Colon, 1),
+            b'@' => (Token::At, 1),
+            b'.' if bytes.get(i..i + 3) == Some(b"..=") => (Token::DotDotEq, 3),
+            b'.' if two == b".." => (Token::DotDot, 2),
+            b'|' if two == b"||" => (Token::Op(BinOp::Or), 2),
+            b'|' => (Token::Pipe, 1),
+            b'&' if two == b"&&" => (Token::Op(BinOp::And), 2),
+            b'=' if two == b"==" => (Token::Op(BinOp::Eq), 2),
+            b'!' if two == b"!=" => (Token::Op(BinOp::Ne), 2),
+            b'!' => (Token::Bang, 1),
+            b'<' if two == b"<=" => (Token::Op(BinOp::Le), 2),
+            b'<' => (Token::Op(BinOp::Lt), 1),
+            b'>' if two == b">=" => (Token::Op(BinOp::Ge), 2),
+            b'>' => (Token::Op(BinOp::Gt), 1),
+            b'+' => (Token::Op(BinOp::Add), 1),
+            b'-' => (Token::Op(BinOp::Sub), 1),
+            b'*' => (Token::Op(BinOp::Mul), 1),
+            b'/' => (Token::Op(BinOp::Div), 1),
+            b'%' => (Token::Op(BinOp::Rem), 1),
+            b'"' => {
+                let (s, end) = lex_string(input, i)?;
+                (Token::Str(s), end - i)
+            }
+            b'0'..=b'9' => {
+                // Stop before `..` so `0..=9` lexes as a range.
+                let mut end = i;
+                while end < bytes.len()
+                    && (bytes[end].is_ascii_digit()
+                        || (bytes[end] == b'.' && bytes.get(end + 1) != Some(&b'.'))
+                        || matches!(bytes[end], b'e' | b'E')
+                        || (matches!(bytes[end], b'+' | b'-') && matches!(bytes[end - 1], b'e' | b'E')))
+                {
+                    end += 1;
+                }
+                let text = &input[i..end];
+                let n = text
+                    .parse()
+                    .map_err(|_| ParseError::new(i, format!("invalid number `{text}`")))?;
+                (Token::Number(n), end - i)
+            }
+            c if c == b'_' || c.is_ascii_alphabetic() => {
+                let mut end = i;
+                while end < bytes.len() && (bytes[end] == b'_' || bytes[end].is_ascii_alphanumeric()) {
+                    end += 1;
+                }
+                (Token::Ident(input[i..end].to_string()), end - i)
+            }
+            _ => {
+                let ch = input[i..].chars().next().unwrap();
+                return Err(ParseError::new(i, format!("unexpected character `{ch}`")));
+            }
+        };
+
+        tokens.push((token, i));
+        i += len;
+    }
+
+    tokens.push((Token::End, input.len()));
+    Ok(tokens)
+}
+
+/// Lexes a double-quoted string starting at `start`; returns it and the offset after the closing quote.
+fn lex_string(input: &str, start: usize) -> Result<(String, usize), ParseError> {
+    let mut out = String::new();
+    let mut chars = input[start + 1..].char_indices();
+
+    while let Some((offset, c)) = chars.next() {
+        let pos = start + 1 + offset;
+        match c {
+            '"' => return Ok((out, pos + 1)),
+            '\\' => {
+                let escaped = match chars.next() {
+                    Some((_, '"')) => '"',
+                    Some((_, '\\')) => '\\',
+                    Some((_, '/')) => '/',
+                    Some((_, 'n')) => '\n',
+                    Some((_, 't')) => '\t',
+                    Some((_, 'r')) => '\r',
+                    Some((_, 'u')) => {
+                        let invalid = || ParseError::new(pos, "invalid \\u escape");
+                        let code = match utf16_unit(&mut chars).ok_or_else(invalid)? {
+                            // A high surrogate only counts with the low one right after it.
+                            high @ 0xD800..=0xDBFF => {
+                                let low = match (chars.next(), chars.next()) {
+                                    (Some((_, '\\')), Some((_, 'u'))) => utf16_unit(&mut chars).ok_or_else(invalid)?,
+                                    _ => return Err(invalid()),
+                                };
+                                if !(0xDC00..=0xDFFF).contains(&low) {
+                                    return Err(invalid());
+                                }
+                                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                            }
+                            code => code,
+                        };
+                        char::from_u32(code).ok_or_else(invalid)?
+                    }
+                    _ => return Err(ParseError::new(pos, "invalid escape")),
+                };
+                out.push(escaped);
+            }
+            c => out.push(c),
+        }
+    }
+
+    Err(ParseError::new(start, "unterminated string"))
+}
+
+/// How deeply arrays, objects, patterns and guard expressions may nest. Parsing
+/// and matching recurse, so without a limit hostile input would overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// The four hex digits of a `\\u` escape.
+fn utf16_unit(chars: &mut std::str::CharIndices) -> Option<u32> {
+    let hex: String = chars.take(4).map(|(_, c)| c).collect();
+    u32::from_str_radix(&hex, 16).ok().filter(|_| hex.len() == 4)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    at: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Result<Self, ParseError> {
+        Ok(Parser { tokens: tokenize(input)?, at: 0, depth: 0 })
+    }
+
+    /// Enters one more level of nesting; the caller steps back out with `self.depth -= 1`.
+    fn descend(&mut self) -> Result<(), ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(ParseError::new(self.pos(), format!("nested more than {MAX_DEPTH} levels deep")));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.at].0
+    }
+
+    fn pos(&self) -> usize {
+        self.tokens[self.at].1
+    }
+
+    fn bump(&mut self) -> Token {
+        let token = self.tokens[self.at].0.clone();
+        if token != Token::End {
+            self.at += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == token {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Token::Ident(s) if s == keyword) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), ParseError> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(self.unexpected(what))
+        }
+    }
+
+    fn expect_end(&mut self) -> Result<(), ParseError> {
+        self.expect(Token::End, "end of input")
+    }
+
+    fn unexpected(&self, what: &str) -> ParseError {
+        let found = match self.peek() {
+            Token::End => "end of input".to_string(),
+            token => format!("{token:?}"),
+        };
+        ParseError::new(self.pos(), format!("expected {what}, found {found}"))
+    }
+
+    // JSON values: literals, arrays and objects with string keys.
+    fn value(&mut self) -> Result<Value, ParseError> {
+        match self.peek().clone() {
+            Token::LBracket => {
+                self.descend()?;
+                self.bump();
+                let mut items = Vec::new();
+                if !self.eat(&Token::RBracket) {
+                    loop {
+                        items.push(self.value()?);
+                        if self.eat(&Token::RBracket) {
+                            break;
+                        }
+                        self.expect(Token::Comma, "`,` or `]`")?;
+                    }
+                }
+                self.depth -= 1;
+                Ok(Value::Array(items))
+            }
+            Token::LBrace => {
+                self.descend()?;
+                self.bump();
+                let mut fields = BTreeMap::new();
+                if !self.eat(&Token::RBrace) {
+                    loop {
+                        let key = match self.bump() {
+                            Token::Str(key) => key,
+                            _ => {
+                                self.at -= 1;
+                                return Err(self.unexpected("string key"));
+                            }
+                        };
+                        self.expect(Token::Colon, "`:`")?;
+                        fields.insert(key, self.value()?);
+                        if self.eat(&Token::RBrace) {
+                            break;
+                        }
+                        self.expect(Token::Comma, "`,` or `}`")?;
+                    }
+                }
+                self.depth -= 1;
+                Ok(Value::Object(fields))
+            }
+            _ => self.literal()?.ok_or_else(|| self.unexpected("a value")),
+        }
+    }
+
+    /// `null`, `true`, `false`, numbers (optionally negative) and strings.
+    fn literal(&mut self) -> Result<Option<Value>, ParseError> {
+        let value = match self.peek().clone() {
+            Token::Ident(ident) if ident == "null" => Value::Null,
+            Token::Ident(ident) if ident == "true" => Value::Bool(true),
+            Token::Ident(ident) if ident == "false" => Value::Bool(false),
+            Token::Number(n) => Value::Number(n),
+            Token::Str(s) => Value::String(s),
+            Token::Op(BinOp::Sub) => {
+                self.bump();
+                return match self.bump() {
+                    Token::Number(n) => Ok(Some(Value::Number(-n))),
+                    _ => {
+                        self.at -= 1;
+                        Err(self.unexpected("a number after `-`"))
+                    }
+                };
+            }
+            _ => return Ok(None),
+        };
+        self.bump();
+        Ok(Some(value))
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, ParseError> {
+        let start = self.pos();
+        let first = self.single_pattern()?;
+        if self.peek() != &Token::Pipe {
+            check_unique(&first, start)?;
+            return Ok(first);
+        }
+
+        let mut alternatives = vec![(first, start)];
+        while self.eat(&Token::Pipe) {
+            let pos = self.pos();
+            alternatives.push((self.single_pattern()?, pos));
+        }
+
+        let mut expected = Vec::new();
+        alternatives[0].0.bound_names(&mut expected);
+        expected.sort();
+        for (alt, pos) in &alternatives {
+            check_unique(alt, *pos)?;
+            let mut names = Vec::new();
+            alt.bound_names(&mut names);
+            names.sort();
+            if names != expected {
+                return Err(ParseError::new(*pos, "all alternatives must bind the same names"));
+            }
+        }
+        Ok(Pattern::Or(alternatives.into_iter().map(|(p, _)| p).collect()))
+    }
+
+    fn single_pattern(&mut self) -> Result<Pattern, ParseError> {
+        if let Some(value) = self.literal()? {
+            if self.eat(&Token::DotDotEq) {
+                let hi_pos = self.pos();
+                return match (value, self.literal()?) {
+                    (Value::Number(lo), Some(Value::Number(hi))) => Ok(Pattern::Range(lo, hi)),
+                    _ => Err(ParseError::new(hi_pos, "ranges need numeric bounds")),
+                };
+            }
+            return Ok(Pattern::Literal(value));
+        }
+
+        self.descend()?;
+        let pattern = self.compound_pattern();
+        self.depth -= 1;
+        pattern
+    }
+
+    /// Everything but literals and ranges: the patterns that can contain others.
+    fn compound_pattern(&mut self) -> Result<Pattern, ParseError> {
+        match self.peek().clone() {
+            Token::Ident(name) if name == "_" => {
+                self.bump();
+                Ok(Pattern::Wildcard)
+            }
+            Token::Ident(name) => {
+                self.bump();
+                let inner = if self.eat(&Token::At) { self.single_pattern()? } else { Pattern::Wildcard };
+                Ok(Pattern::Bind(name, Box::new(inner)))
+            }
+            // Parentheses group alternatives and double as tuple syntax: `(1, b)` is `[1, b]`.
+            Token::LParen => {
+                self.bump();
+                if self.peek() == &Token::DotDot {
+                    return self.sequence(None, Token::RParen);
+                }
+                let first = self.pattern()?;
+                if self.eat(&Token::RParen) {
+                    return Ok(first);
+                }
+                self.expect(Token::Comma, "`,` or `)`")?;
+                self.at -= 1;
+                self.sequence(Some(first), Token::RParen)
+            }
+            Token::LBracket => {
+                self.bump();
+                self.sequence(None, Token::RBracket)
+            }
+            Token::LBrace => {
+                self.bump();
+                self.object()
+            }
+            _ => Err(self.unexpected("a pattern")),
+        }
+    }
+
+    fn sequence(&mut self, first: Option<Pattern>, close: Token) -> Result<Pattern, ParseError> {
+        let mut items: Vec<Pattern> = first.into_iter().collect();
+        let mut rest = None;
+        let mut need_comma = !items.is_empty();
+
+        while !self.eat(&close) {
+            if need_comma {
+                self.expect(Token::Comma, "`,`")?;
+                if self.eat(&close) {
+                    break;
+                }
+            }
+            if self.peek() == &Token::DotDot {
+                if rest.is_some() {
+                    return Err(ParseError::new(self.pos(), "`..` can only be used once per pattern"));
+                }
+                self.bump();
+                rest = Some(items.len());
+            } else {
+                items.push(self.pattern()?);
+            }
+            need_comma = true;
+        }
+
+        Ok(Pattern::Array { items, rest })
+    }
+
+    fn object(&mut self) -> Result<Pattern, ParseError> {
+        let mut fields: Vec<(String, Pattern)> = Vec::new();
+        let mut rest = false;
+
+        while !self.eat(&Token::RBrace) {
+            if rest {
+                return Err(ParseError::new(self.pos(), "`..` must be the last field"));
+            }
+            let key_pos = self.pos();
+            let key = match self.bump() {
+                Token::DotDot => {
+                    rest = true;
+                    if !self.eat(&Token::Comma) && self.peek() != &Token::RBrace {
+                        return Err(self.unexpected("`}`"));
+                    }
+                    continue;
+                }
+                Token::Ident(name) | Token::Str(name) => name,
+                _ => {
+                    self.at -= 1;
+                    return Err(self.unexpected("a field name"));
+                }
+            };
+            if fields.iter().any(|(k, _)| *k == key) {
+                return Err(ParseError::new(key_pos, format!("field `{key}` listed twice")));
+            }
+
+            // `{ y }` is shorthand for `{ y: y }`.
+            let pattern = if self.eat(&Token::Colon) {
+                self.pattern()?
+            } else {
+                Pattern::Bind(key.clone(), Box::new(Pattern::Wildcard))
+            };
+            fields.push((key, pattern));
+
+            if !self.eat(&Token::Comma) && self.peek() != &Token::RBrace {
+                return Err(self.unexpected("`,` or `}`"));
+            }
+        }
+
+        Ok(Pattern::Object { fields, rest })
+    }
+
+    // Guard expressions, loosest binding first: || && comparisons + - * / % unary.
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        self.binary(0)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, ParseError> {
+        const LEVELS: [&[BinOp]; 5] = [
+            &[BinOp::Or],
+            &[BinOp::And],
+            &[BinOp::Eq, BinOp::Ne, BinOp::Lt, BinOp::Le, BinOp::Gt, BinOp::Ge],
+            &[BinOp::Add, BinOp::Sub],
+            &[BinOp::Mul, BinOp::Div, BinOp::Rem],
+        ];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let depth = self.depth;
+        let mut lhs = self.binary(level + 1)?;
+        while let Token::Op(op) = *self.peek() {
+            if !LEVELS[level].contains(&op) {
+                break;
+            }
+            // Each operator puts `lhs` one level further down the tree.
+            self.descend()?;
+            self.bump();
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        self.descend()?;
+        let expr = self.operand();
+        self.depth -= 1;
+        expr
+    }
+
+    fn operand(&mut self) -> Result<Expr, ParseError> {
+        match self.peek().clone() {
+            Token::Bang => {
+                self.bump();
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Token::Op(BinOp::Sub) => {
+                self.bump();
+                Ok(Expr::Neg(Box::new(self.unary()?)))
+            }
+            Token::LParen => {
+                self.bump();
+                let inner = self.expr()?;
+                self.expect(Token::RParen, "`)`")?;
+                Ok(inner)
+            }
+            Token::Ident(name) if !matches!(name.as_str(), "null" | "true" | "false") => {
+                let pos = self.pos();
+                self.bump();
+                Ok(Expr::Var { name, pos })
+            }
+            _ => match self.literal()? {
+                Some(value) => Ok(Expr::Literal(value)),
+                None => Err(self.unexpected("an expression")),
+            },
+        }
+    }
+}
+
+fn check_unique(pattern: &Pattern, pos: usize) -> Result<(), ParseError> {
+    let mut names = Vec::new();
+    pattern.bound_names(&mut names);
+    let mut seen = BTreeSet::new();
+    match names.into_iter().find(|name| !seen.insert(name.clone())) {
+        Some(name) => Err(ParseError::new(pos, format!("`{name}` is bound more than once"))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(json: &str) -> Value {
+        Value::parse(json).unwrap()
+    }
+
+    fn query(q: &str) -> Query {
+        Query::parse(q).unwrap()
+    }
+
+    #[test]
+    fn test_value_parse() {
+        let v = value(r#"{"x": [1, -2.5, "a\"b"], "y": null, "ok": true}"#);
+        assert_eq!(v.get("y"), Some(&Value::Null));
+        assert_eq!(
+            v.get("x"),
+            Some(&Value::Array(vec![1.into(), (-2.5).into(), "a\"b".into()]))
+        );
+        assert_eq!(value("[]"), Value::Array(vec![]));
+        assert!(Value::parse("[1, 2").is_err());
+    }
+
+    #[test]
+    fn test_unicode_escapes() {
+        assert_eq!(value(r#""\u00e9\ud83d\ude00!""#), "é😀!".into());
+        assert_eq!(value(r#""\uD834\uDD1E""#), "𝄞".into());
+        for lone in [r#""\ud83d""#, r#""\ud83dx""#, r#""\ud83d\n""#, r#""\ud83d\u0041""#, r#""\ude00""#, r#""\u12""#] {
+            let err = Value::parse(lone).unwrap_err();
+            assert_eq!((err.pos, err.message.as_str()), (1, "invalid \\u escape"), "{lone}");
+        }
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth: usize, open: &str, close: &str, inner: &str| {
+            format!("{}{inner}{}", open.repeat(depth), close.repeat(depth))
+        };
+        assert!(Value::parse(&nested(MAX_DEPTH, "[", "]", "1")).is_ok());
+        let err = Value::parse(&nested(MAX_DEPTH + 1, "[", "]", "1")).unwrap_err();
+        assert_eq!((err.pos, err.message.as_str()), (MAX_DEPTH, "nested more than 128 levels deep"));
+        assert!(Value::parse(&nested(100_000, "[", "]", "")).is_err());
+        assert!(Value::parse(&nested(100_000, "{\"a\": ", "}", "1")).is_err());
+
+        assert!(Pattern::parse(&nested(MAX_DEPTH - 1, "[", "]", "x")).is_ok());
+        for (open, close) in [("[", "]"), ("(", ")"), ("{ a: ", " }"), ("x @ ", "")] {
+            assert!(Pattern::parse(&nested(100_000, open, close, "_")).is_err(), "{open}");
+        }
+
+        for (open, close) in [("(", ")"), ("!", ""), ("-", ""), ("1 + ", "")] {
+            assert!(Query::parse(&nested(100_000, open, close, "x").replace("x", "[x] if x")).is_err(), "{open}");
+        }
+        assert!(Query::parse(&format!("[x] if x{}", " + 1".repeat(100))).is_ok());
+    }
+
+    #[test]
+    fn test_literals_and_alternatives() {
+        let q = query(r#""q" | "a" | "s""#);
+        assert!(q.matches(&"a".into()).is_some());
+        assert!(q.matches(&"x".into()).is_none());
+
+        let q = query("0..=9");
+        assert!(q.matches(&5.into()).is_some());
+        assert!(q.matches(&10.into()).is_none());
+        assert!(q.matches(&"5".into()).is_none());
+    }
+
+    #[test]
+    fn test_destructuring_objects() {
+        let q = query("{ x: (1, b), y }");
+        let bindings = q.matches(&value(r#"{"x": [1, 2], "y": 3}"#)).unwrap();
+        assert_eq!(bindings["b"], 2.into());
+        assert_eq!(bindings["y"], 3.into());
+
+        // Without `..` every field has to be listed, like a Rust struct pattern.
+        assert!(q.matches(&value(r#"{"x": [1, 2], "y": 3, "z": 4}"#)).is_none());
+        let q = query("{ y, .. }");
+        assert!(q.matches(&value(r#"{"x": [1, 2], "y": 3, "z": 4}"#)).is_some());
+    }
+
+    #[test]
+    fn test_destructuring_arrays() {
+        let q = query("[0, y, z]");
+        let bindings = q.matches(&value("[0, -2, 3]")).unwrap();
+        assert_eq!(bindings["y"], (-2).into());
+        assert_eq!(bindings["z"], 3.into());
+        assert!(q.matches(&value("[0, 1]")).is_none());
+
+        let q = query("[first, .., last]");
+        let bindings = q.matches(&value("[1, 2, 3, 4]")).unwrap();
+        assert_eq!((&bindings["first"], &bindings["last"]), (&1.into(), &4.into()));
+        assert!(q.matches(&value("[1]")).is_none());
+
+        let q = query("[1, ..]");
+        assert!(q.matches(&value("[1]")).is_some());
+        assert!(q.matches(&value("[2, 1]")).is_none());
+    }
+
+    #[test]
+    fn test_at_bindings() {
+        let q = query(r#"{ user: u @ { name: "bob", .. }, .. }"#);
+        let bindings = q.matches(&value(r#"{"user": {"name": "bob", "id": 7}}"#)).unwrap();
+        assert_eq!(bindings["u"].get("id"), Some(&7.into()));
+    }
+
+    #[test]
+    fn test_guards() {
+        let twins = query("(x, y) if x == y");
+        let antimatter = query("(x, y) if x + y == 0");
+        let odd = query("(x, _) if x % 2 == 1");
+
+        assert!(twins.matches(&value("[0, 0]")).is_some());
+        assert!(twins.matches(&value("[1, -1]")).is_none());
+        assert!(antimatter.matches(&value("[1, -1]")).is_some());
+        assert!(odd.matches(&value("[3, 8]")).is_some());
+        assert!(odd.matches(&value("[4, 8]")).is_none());
+
+        let q = query(r#"{ level, ms, .. } if (level == "warn" || level == "error") && !(ms < 100)"#);
+        assert!(q.matches(&value(r#"{"level": "warn", "ms": 250}"#)).is_some());
+        assert!(q.matches(&value(r#"{"level": "warn", "ms": 50}"#)).is_none());
+        assert!(q.matches(&value(r#"{"level": "info", "ms": 250}"#)).is_none());
+    }
+
+    #[test]
+    fn test_guard_type_errors_do_not_match() {
+        let q = query("[x] if x > 1");
+        assert!(q.matches(&value(r#"["a"]"#)).is_none());
+        assert!(q.matches(&value("[2]")).is_some());
+    }
+
+    #[test]
+    fn test_filter_event_log() {
+        let events: Vec<Value> = [
+            r#"{"kind": "login", "user": "bob", "ok": true}"#,
+            r#"{"kind": "login", "user": "eve", "ok": false}"#,
+            r#"{"kind": "logout", "user": "bob"}"#,
+            r#"{"kind": "login", "user": "eve", "ok": false}"#,
+        ]
+        .iter()
+        .map(|e| value(e))
+        .collect();
+
+        let failed = query(r#"{ kind: "login", user, ok: false }"#);
+        let users: Vec<Value> = failed.filter(&events).map(|(_, b)| b["user"].clone()).collect();
+        assert_eq!(users, vec!["eve".into(), "eve".into()]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Query::parse("[x, x]").unwrap_err();
+        assert_eq!(err.message, "`x` is bound more than once");
+
+        let err = Query::parse("[x] if x == y").unwrap_err();
+        assert_eq!(err.message, "`y` is not bound by the pattern");
+        assert_eq!(err.pos, 12);
+
+        let err = Query::parse("(x, _) if x > 0 && !(z < 2)").unwrap_err();
+        assert_eq!(err.message, "`z` is not bound by the pattern");
+        assert_eq!(err.pos, 21);
+
+        let err = Query::parse("[x] | [1]").unwrap_err();
+        assert_eq!(err.message, "all alternatives must bind the same names");
+        assert_eq!(err.pos, 6);
+
+        let err = Query::parse("[.., ..]").unwrap_err();
+        assert_eq!(err.pos, 5);
+
+        let err = Query::parse("{ a: 1 ~ }").unwrap_err();
+        assert_eq!(err.to_string(), "unexpected character `~` at position 7");
+    }
+}
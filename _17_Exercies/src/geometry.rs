@@ -0,0 +1,270 @@
+// The `Geometry` trait lets new shapes plug into everything that works on
+// bounding boxes, areas and transforms without adding variants to `Shape`.
+// `shapes.rs` adds Rectangle, Ellipse and Segment this way. Transforming a
+// geometry gives whatever it has become, as another `Box<dyn Geometry>`.
+
+use std::fmt;
+
+use crate::transform::Transform;
+use crate::{Circle, Point, Polygon, Shape};
+
+/// Axis-aligned bounding box; `min` is the bottom-left corner, `max` the top-right.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    /// The smallest box containing both corners, in any order.
+    pub fn new(a: Point, b: Point) -> Self {
+        BoundingBox {
+            min: Point::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Point::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    /// `None` for an empty iterator.
+    pub fn from_points(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        points
+            .into_iter()
+            .map(|p| BoundingBox::new(p, p))
+            .reduce(|acc, b| acc.union(&b))
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width() * self.height()
+    }
+
+    pub fn center(&self) -> Point {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min: Point::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            max: Point::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        }
+    }
+
+    /// Boxes that only share an edge or a corner count as intersecting.
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    pub fn contains_point(&self, p: Point) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+    }
+
+    /// Distance from `p` to the closest point of the box; zero if `p` is inside.
+    pub fn distance_to(&self, p: Point) -> f64 {
+        let dx = (self.min.x - p.x).max(0.0).max(p.x - self.max.x);
+        let dy = (self.min.y - p.y).max(0.0).max(p.y - self.max.y);
+        dx.hypot(dy)
+    }
+}
+
+pub trait Geometry: fmt::Debug {
+    /// `None` for geometries with no points, such as an empty polygon.
+    fn bounding_box(&self) -> Option<BoundingBox>;
+
+    fn area(&self) -> f64;
+
+    fn perimeter(&self) -> f64;
+
+    fn centroid(&self) -> Option<Point>;
+
+    /// The image of this geometry under `t`. Types that affine maps keep
+    /// (points, polygons, segments, ellipses) come back as themselves; the rest
+    /// as whatever they turned into, e.g. a stretched circle is an ellipse.
+    fn transform(&self, t: &Transform) -> Box<dyn Geometry>;
+}
+
+impl Geometry for Point {
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::new(*self, *self))
+    }
+
+    fn area(&self) -> f64 {
+        0.0
+    }
+
+    fn perimeter(&self) -> f64 {
+        0.0
+    }
+
+    fn centroid(&self) -> Option<Point> {
+        Some(*self)
+    }
+
+    fn transform(&self, t: &Transform) -> Box<dyn Geometry> {
+        Box::new(self.transformed(t))
+    }
+}
+
+impl Geometry for Polygon {
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::from_points(self.points.iter().copied())
+    }
+
+    fn area(&self) -> f64 {
+        Polygon::area(self)
+    }
+
+    fn perimeter(&self) -> f64 {
+        Polygon::perimeter(self)
+    }
+
+    fn centroid(&self) -> Option<Point> {
+        Polygon::centroid(self)
+    }
+
+    fn transform(&self, t: &Transform) -> Box<dyn Geometry> {
+        Box::new(self.transformed(t))
+    }
+}
+
+impl Geometry for Circle {
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let r = Point::new(self.radius, self.radius);
+        Some(BoundingBox::new(self.center - r, self.center + r))
+    }
+
+    fn area(&self) -> f64 {
+        Circle::area(self)
+    }
+
+    fn perimeter(&self) -> f64 {
+        Circle::perimeter(self)
+    }
+
+    fn centroid(&self) -> Option<Point> {
+        Some(Circle::centroid(self))
+    }
+
+    fn transform(&self, t: &Transform) -> Box<dyn Geometry> {
+        match self.transformed(t).as_circle() {
+            Some(circle) => Box::new(circle),
+            None => Box::new(self.transformed(t)),
+        }
+    }
+}
+
+impl Geometry for Shape {
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        match self {
+            Shape::Polygon(poly) => poly.bounding_box(),
+            Shape::Circle(circle) => circle.bounding_box(),
+        }
+    }
+
+    fn area(&self) -> f64 {
+        Shape::area(self)
+    }
+
+    fn perimeter(&self) -> f64 {
+        Shape::perimeter(self)
+    }
+
+    fn centroid(&self) -> Option<Point> {
+        Shape::centroid(self)
+    }
+
+    /// Polygons stay `Shape`s; circles do too unless they became ellipses.
+    fn transform(&self, t: &Transform) -> Box<dyn Geometry> {
+        match self {
+            Shape::Polygon(poly) => Box::new(Shape::Polygon(poly.transformed(t))),
+            Shape::Circle(circle) => match circle.transformed(t).as_circle() {
+                Some(circle) => Box::new(Shape::Circle(circle)),
+                None => Box::new(circle.transformed(t)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::{Ellipse, Rectangle, Segment};
+
+    #[test]
+    fn test_bounding_box() {
+        let b = BoundingBox::new(Point::new(3.0, -1.0), Point::new(1.0, 2.0));
+        assert_eq!(b.min, Point::new(1.0, -1.0));
+        assert_eq!((b.width(), b.height(), b.area()), (2.0, 3.0, 6.0));
+        assert_eq!(b.center(), Point::new(2.0, 0.5));
+        assert!(b.contains_point(Point::new(1.0, 2.0)));
+        assert_eq!(b.distance_to(Point::new(6.0, 6.0)), 5.0);
+        assert_eq!(b.distance_to(Point::new(2.0, 0.0)), 0.0);
+
+        let touching = BoundingBox::new(Point::new(3.0, 2.0), Point::new(4.0, 4.0));
+        assert!(b.intersects(&touching));
+        assert!(!b.intersects(&BoundingBox::new(Point::new(3.1, 0.0), Point::new(4.0, 1.0))));
+        assert_eq!(b.union(&touching), BoundingBox::new(Point::new(1.0, -1.0), Point::new(4.0, 4.0)));
+        assert_eq!(BoundingBox::from_points(Vec::new()), None);
+    }
+
+    #[test]
+    fn test_mixed_collection() {
+        let mut triangle = Polygon::new();
+        for (x, y) in [(0.0, 0.0), (4.0, 0.0), (0.0, 3.0)] {
+            triangle.add_point(Point::new(x, y));
+        }
+        let geometries: Vec<Box<dyn Geometry>> = vec![
+            Box::new(triangle),
+            Box::new(Circle::new(Point::new(10.0, 10.0), 1.0)),
+            Box::new(Rectangle::new(Point::new(-2.0, -2.0), Point::new(-1.0, 0.0))),
+            Box::new(Ellipse::new(Point::new(0.0, 10.0), 2.0, 1.0, 0.0)),
+            Box::new(Segment::new(Point::new(5.0, 5.0), Point::new(8.0, 9.0))),
+            Box::new(Point::new(-5.0, 3.0)),
+        ];
+
+        let all = geometries
+            .iter()
+            .filter_map(|g| g.bounding_box())
+            .reduce(|a, b| a.union(&b))
+            .unwrap();
+        assert_eq!(all, BoundingBox::new(Point::new(-5.0, -2.0), Point::new(11.0, 11.0)));
+
+        // Translating moves every bounding box by the same amount.
+        let t = Transform::translate(1.0, 2.0);
+        for g in &geometries {
+            let before = g.bounding_box().unwrap();
+            let after = g.transform(&t).bounding_box().unwrap();
+            assert!(after.min.dist(before.min + Point::new(1.0, 2.0)) < 1e-9, "{g:?}");
+            assert!((g.area() - g.transform(&t).area()).abs() < 1e-9);
+        }
+
+        // Each result is whatever the geometry turned into.
+        let kind = |g: &dyn Geometry| format!("{g:?}").split([' ', '(']).next().unwrap().to_string();
+        let kinds = |t: Transform| -> Vec<String> { geometries.iter().map(|g| kind(&*g.transform(&t))).collect() };
+        assert_eq!(kinds(Transform::shear(0.5, 0.0)), ["Polygon", "Ellipse", "Polygon", "Ellipse", "Segment", "Point"]);
+        assert_eq!(kinds(Transform::scale(2.0, 2.0)), ["Polygon", "Circle", "Rectangle", "Ellipse", "Segment", "Point"]);
+
+        // Transforms chain through the boxes; an ellipse stays an ellipse, even a round one.
+        let stretch = Transform::scale(3.0, 1.0);
+        let circle = &geometries[1];
+        let restored = circle.transform(&stretch).transform(&stretch.inverse().unwrap());
+        assert_eq!(kind(&*restored), "Ellipse");
+        assert!((restored.area() - circle.area()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_shape_transform() {
+        let shape = Shape::Circle(Circle::new(Point::new(0.0, 0.0), 1.0));
+        let stretched = shape.transform(&Transform::scale(3.0, 1.0));
+        let b = stretched.bounding_box().unwrap();
+        assert!((b.width() - 6.0).abs() < 1e-9 && (b.height() - 2.0).abs() < 1e-9);
+    }
+}
@@ -1,93 +1,86 @@
 #![allow(unused_variables, dead_code)]
-use std::ops::Add;
+mod geometry;
+mod polygon_ops;
+mod rtree;
+mod shapes;
+mod svg;
+mod transform;
+mod user;
+mod wkt;
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use geometry::Geometry;
+use rtree::RTree;
+use shapes::Rectangle;
+use transform::Transform;
+use user::{User, Weight, WeightUnit};
+use wkt::{FromWkt, ToWkt};
 
-// test for my question
-struct Person {
-    age: u32,
-}
-
-struct User {
-    name: String,
-    age: u32,
-    weight: f32,
-    person: Person,
-}
-
-impl User {
-    pub fn new(name: String, age: u32, weight: f32) -> Self {
-        User { name: name, age: age, weight: weight, person: Person{age: 1} }
-    }
-
-    pub fn name(&self) -> &str {
-        return &self.name;
-    }
+fn main() {
+    let mut bob = User::new(String::from("Bob"), 32, Weight::lb(155.2)).unwrap();
+    let p = bob.person();
+    println!("person age is {}", p.age());
 
-    pub fn age(&self) -> u32 {
-        return self.age;
+    if let Err(e) = bob.set_age(10_000) {
+        println!("rejected: {e}");
     }
-
-    pub fn weight(&self) -> f32 {
-        return self.weight;
+    bob.set_age(33).unwrap();
+    bob.set_weight(bob.weight().to(WeightUnit::Kg)).unwrap();
+    bob.undo(1).unwrap();
+    for entry in bob.audit_log() {
+        println!("{entry}");
     }
+    println!("I'm {} and my age is {}, weight {}", bob.name(), bob.age(), bob.weight());
 
-    // copy를 구현하지 않았기 때문에 borrowing만 가능.
-    pub fn person(&mut self) -> &mut Person {
-        return &mut self.person;
+    // A notched rectangle: its hull fills the notch back in.
+    let mut poly = Polygon::new();
+    for (x, y) in [(0.0, 0.0), (4.0, 0.0), (4.0, 3.0), (2.0, 1.0), (0.0, 3.0), (0.0, 1.5)] {
+        poly.add_point(Point::new(x, y));
     }
-
-    pub fn set_age(&mut self, new_age: u32) {
-        self.age = new_age;
+    let hull = poly.convex_hull();
+    println!("area {} -> hull area {} ({} points)", poly.area(), hull.area(), hull.points.len());
+    println!("simplified to {} points", poly.simplify(0.1).points.len());
+    let notch = Point::new(2.0, 2.0);
+    println!("notch: {:?} in polygon, {:?} in hull", poly.contains(notch), hull.contains(notch));
+    println!("self-intersecting: {}", poly.is_self_intersecting());
+
+    // Every shape is a Geometry, and transforms keep what they can: a stretched circle is an ellipse.
+    let circle = Circle::new(Point::new(6.0, 1.0), 1.0);
+    let stretched = circle.transform(&Transform::scale(2.0, 1.0));
+    let turned = Rectangle::new(Point::new(0.0, 0.0), Point::new(2.0, 1.0)).transform(&Transform::rotate(0.5));
+    println!("stretched: {stretched:?}, area {:.3}", stretched.area());
+    println!("turned rectangle: {:?}", turned.bounding_box());
+
+    let shapes = vec![Shape::Polygon(poly), Shape::Circle(circle)];
+    for shape in &shapes {
+        let text = shape.to_wkt();
+        println!("{text} round-trips: {}", Shape::from_wkt(&text).as_ref() == Ok(shape));
     }
+    print!("{}", svg::render_svg(&shapes));
 
-    pub fn set_weight(&mut self, new_weight: f32) {
-        self.weight = new_weight;
-    }
+    let tree = RTree::bulk_load(shapes);
+    let nearest = tree.nearest(Point::new(7.0, 3.0)).and_then(|shape| shape.centroid());
+    println!("{} shapes, nearest to (7, 3) is centred at {nearest:?}", tree.len());
 }
 
-fn main() {
-    let mut bob = User::new(String::from("Bob"), 32, 155.2);
-    let mut age = bob.age();
-    let p = bob.person();
-    p.age = 5;
-    age = 15;
-    println!("I'm {} and my age is {}", bob.name(), bob.age());
-}
-
-#[test]
-fn test_weight() {
-    let bob = User::new(String::from("Bob"), 32, 155.2);
-    assert_eq!(bob.weight(), 155.2);
-}
-
-#[test]
-fn test_set_age() {
-    let mut bob = User::new(String::from("Bob"), 32, 155.2);
-    assert_eq!(bob.age(), 32);
-    bob.set_age(33);
-    assert_eq!(bob.age(), 33);
-}
-
-
-
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Point {
-    x: i32,
-    y: i32,
+    x: f64,
+    y: f64,
 }
 
 impl Point {
-    fn new(x: i32, y: i32) -> Self {
+    fn new(x: f64, y: f64) -> Self {
         Point {x, y}
     }
 
     fn magnitude(&self) -> f64 {
-        let sqr = i32::pow(self.x, 2) + i32::pow(self.y, 2);
-        return f64::from(sqr).sqrt();
+        self.x.hypot(self.y)
     }
 
     fn dist(&self, p1: Point) -> f64 {
-        let distanceSqr = i32::pow(self.x-p1.x, 2) + i32::pow(self.y-p1.y, 2);
-        return f64::from(distanceSqr).sqrt();
+        (self.x - p1.x).hypot(self.y - p1.y)
     }
 }
 
@@ -102,6 +95,40 @@ impl Add for Point {
     }
 }
 
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl Mul<f64> for Point {
+    type Output = Self;
+
+    fn mul(self, factor: f64) -> Self {
+        Self {
+            x: self.x * factor,
+            y: self.y * factor,
+        }
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Polygon {
     points: Vec<Point>
 }
@@ -116,56 +143,114 @@ impl Polygon {
     }
 
     fn left_most_point(&self) -> Option<Point> {
-        let len = self.points.len();
-        if len == 0 {
-            return None;
-        }
-
-        let mut leftMostPoint = self.points[0];
-        for point in &self.points[1..len] {
-            if point.x < leftMostPoint.x {
-                leftMostPoint = *point;
+        let mut left_most_point = *self.points.first()?;
+        for point in &self.points[1..] {
+            if point.x < left_most_point.x {
+                left_most_point = *point;
             }
         }
 
-        return Some(leftMostPoint);
+        Some(left_most_point)
+    }
+
+    // Each point paired with the next one, wrapping around to close the loop.
+    fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        let next = self.points.iter().cycle().skip(1);
+        self.points.iter().copied().zip(next.copied())
+    }
+
+    fn perimeter(&self) -> f64 {
+        self.edges().map(|(a, b)| a.dist(b)).sum()
+    }
+
+    // Shoelace formula: positive when the points go counter-clockwise.
+    fn signed_area(&self) -> f64 {
+        self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum::<f64>() / 2.0
     }
 
-    fn length(&self) -> i32 {
-        let mut s = 0;
-        for point in &self.points {
-            s += point.x + point.y;
+    fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    fn centroid(&self) -> Option<Point> {
+        let n = self.points.len();
+        if n == 0 {
+            return None;
         }
 
-        return s;
+        // Work relative to the first vertex, so far-away polygons don't lose
+        // their small area to rounding. `scale` is the size of the cross
+        // products before they cancel; an area that small next to it is
+        // rounding noise, whatever the polygon's size.
+        let origin = self.points[0];
+        let (mut twice_area, mut scale, mut cx, mut cy) = (0.0, 0.0, 0.0, 0.0);
+        for (a, b) in self.edges() {
+            let (a, b) = (a - origin, b - origin);
+            let cross = a.x * b.y - b.x * a.y;
+            twice_area += cross;
+            scale += (a.x * b.y).abs() + (b.x * a.y).abs();
+            cx += (a.x + b.x) * cross;
+            cy += (a.y + b.y) * cross;
+        }
+        if twice_area.abs() <= scale * 1e-12 {
+            // Degenerate polygon (a point or a line): fall back to the vertex average.
+            let sum = self.points.iter().fold(Point::new(0.0, 0.0), |acc, p| acc + *p);
+            return Some(Point::new(sum.x / n as f64, sum.y / n as f64));
+        }
+
+        Some(origin + Point::new(cx / (3.0 * twice_area), cy / (3.0 * twice_area)))
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Circle {
     center: Point,
-    radius: i32,
+    radius: f64,
 }
 
 impl Circle {
-    fn new(center: Point, radius: i32) -> Self {
+    fn new(center: Point, radius: f64) -> Self {
         Circle{center, radius }
     }
 
-    fn length(&self) -> i32 {
-        self.radius
+    fn perimeter(&self) -> f64 {
+        2.0 * std::f64::consts::PI * self.radius
+    }
+
+    fn area(&self) -> f64 {
+        std::f64::consts::PI * self.radius * self.radius
+    }
+
+    fn centroid(&self) -> Point {
+        self.center
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum Shape {
     Polygon(Polygon),
     Circle(Circle),
 }
 
 impl Shape {
-    fn perimeter(&self) -> i32 {
+    fn perimeter(&self) -> f64 {
         match self {
-            Shape::Polygon(poly) => poly.length(),
-            Shape::Circle(circle) => circle.length(),
+            Shape::Polygon(poly) => poly.perimeter(),
+            Shape::Circle(circle) => circle.perimeter(),
+        }
+    }
+
+    fn area(&self) -> f64 {
+        match self {
+            Shape::Polygon(poly) => poly.area(),
+            Shape::Circle(circle) => circle.area(),
+        }
+    }
+
+    fn centroid(&self) -> Option<Point> {
+        match self {
+            Shape::Polygon(poly) => poly.centroid(),
+            Shape::Circle(circle) => Some(circle.centroid()),
         }
     }
 }
@@ -178,67 +263,154 @@ mod tests {
         (x * 100.0).round() / 100.0
     }
 
+    fn polygon(points: &[(f64, f64)]) -> Polygon {
+        let mut poly = Polygon::new();
+        for &(x, y) in points {
+            poly.add_point(Point::new(x, y));
+        }
+        poly
+    }
+
     #[test]
     fn test_point_magnitude() {
-        let p1 = Point::new(12, 13);
+        let p1 = Point::new(12.0, 13.0);
         assert_eq!(round_two_digits(p1.magnitude()), 17.69);
     }
 
     #[test]
     fn test_point_dist() {
-        let p1 = Point::new(10, 10);
-        let p2 = Point::new(14, 13);
+        let p1 = Point::new(10.0, 10.0);
+        let p2 = Point::new(14.0, 13.0);
         assert_eq!(round_two_digits(p1.dist(p2)), 5.00);
     }
 
     #[test]
     fn test_point_add() {
-        let p1 = Point::new(16, 16);
-        let p2 = p1 + Point::new(-4, 3);
-        assert_eq!(p2, Point::new(12, 19));
+        let p1 = Point::new(16.0, 16.0);
+        let p2 = p1 + Point::new(-4.0, 3.0);
+        assert_eq!(p2, Point::new(12.0, 19.0));
+    }
+
+    #[test]
+    fn test_point_sub_mul_neg() {
+        let p1 = Point::new(16.0, 16.0);
+        assert_eq!(p1 - Point::new(-4.0, 3.0), Point::new(20.0, 13.0));
+        assert_eq!(p1 * 0.5, Point::new(8.0, 8.0));
+        assert_eq!(-p1, Point::new(-16.0, -16.0));
+        assert_eq!(p1 - p1, p1 * 0.0);
     }
 
     #[test]
     fn test_polygon_left_most_point() {
-        let p1 = Point::new(12, 13);
-        let p2 = Point::new(16, 16);
+        let p1 = Point::new(12.0, 13.0);
+        let p2 = Point::new(16.0, 16.0);
 
         let mut poly = Polygon::new();
         poly.add_point(p1);
         poly.add_point(p2);
         assert_eq!(poly.left_most_point(), Some(p1));
+        assert_eq!(Polygon::new().left_most_point(), None);
     }
 
     #[test]
     fn test_polygon_iter() {
-        let p1 = Point::new(12, 13);
-        let p2 = Point::new(16, 16);
+        let p1 = Point::new(12.0, 13.0);
+        let p2 = Point::new(16.0, 16.0);
 
         let mut poly = Polygon::new();
         poly.add_point(p1);
         poly.add_point(p2);
 
-        let points = poly.points.iter().cloned().collect::<Vec<_>>();
-        assert_eq!(points, vec![Point::new(12, 13), Point::new(16, 16)]);
+        let points = poly.points.to_vec();
+        assert_eq!(points, vec![Point::new(12.0, 13.0), Point::new(16.0, 16.0)]);
     }
 
     #[test]
     fn test_shape_perimeters() {
-        let mut poly = Polygon::new();
-        poly.add_point(Point::new(12, 13));
-        poly.add_point(Point::new(17, 11));
-        poly.add_point(Point::new(16, 16));
-        let shapes = vec![
-            Shape::from(Shape::Polygon(poly)),
-            Shape::from(Shape::Circle(Circle::new(Point::new(10, 20), 5))),
+        let poly = polygon(&[(12.0, 13.0), (17.0, 11.0), (16.0, 16.0)]);
+        let shapes = [
+            Shape::Polygon(poly),
+            Shape::Circle(Circle::new(Point::new(10.0, 20.0), 5.0)),
         ];
         let perimeters = shapes
             .iter()
             .map(Shape::perimeter)
+            .map(round_two_digits)
             .collect::<Vec<_>>();
-        assert_eq!(perimeters, vec![85, 5]);
+        // sqrt(29) + sqrt(26) + 5, and 2 * pi * 5
+        assert_eq!(perimeters, vec![15.48, 31.42]);
+    }
+
+    #[test]
+    fn test_rectangle() {
+        let rect = polygon(&[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0), (0.0, 4.0)]);
+        assert_eq!(rect.perimeter(), 14.0);
+        assert_eq!(rect.area(), 12.0);
+        assert_eq!(rect.centroid(), Some(Point::new(1.5, 2.0)));
+    }
+
+    #[test]
+    fn test_area_ignores_winding() {
+        let ccw = polygon(&[(0.0, 0.0), (4.0, 0.0), (0.0, 3.0)]);
+        let cw = polygon(&[(0.0, 0.0), (0.0, 3.0), (4.0, 0.0)]);
+        assert_eq!(ccw.signed_area(), 6.0);
+        assert_eq!(cw.signed_area(), -6.0);
+        assert_eq!(ccw.area(), cw.area());
+        assert_eq!(ccw.perimeter(), 12.0);
+        assert_eq!(cw.centroid(), ccw.centroid());
+    }
+
+    #[test]
+    fn test_triangle_centroid() {
+        let tri = polygon(&[(0.0, 0.0), (6.0, 0.0), (0.0, 3.0)]);
+        assert_eq!(tri.centroid(), Some(Point::new(2.0, 1.0)));
+    }
+
+    #[test]
+    fn test_concave_polygon() {
+        // An L shape: a 2x2 square with the top-right 1x1 square removed.
+        let l = polygon(&[(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0), (1.0, 2.0), (0.0, 2.0)]);
+        assert_eq!(l.area(), 3.0);
+        assert_eq!(l.perimeter(), 8.0);
+        let c = l.centroid().unwrap();
+        assert_eq!((round_two_digits(c.x), round_two_digits(c.y)), (0.83, 0.83));
     }
-}
 
+    #[test]
+    fn test_degenerate_polygons() {
+        assert_eq!(Polygon::new().perimeter(), 0.0);
+        assert_eq!(Polygon::new().area(), 0.0);
+        assert_eq!(Polygon::new().centroid(), None);
+
+        let segment = polygon(&[(0.0, 0.0), (4.0, 0.0)]);
+        assert_eq!(segment.perimeter(), 8.0);
+        assert_eq!(segment.area(), 0.0);
+        assert_eq!(segment.centroid(), Some(Point::new(2.0, 0.0)));
+
+        // Collinear, but rounding leaves the shoelace sum at about -6e-5.
+        let on_line = |x: f64| (x, x / 3.0);
+        let line = polygon(&[on_line(1e6 + 0.1), on_line(2e6 + 0.1), on_line(5e6 + 0.1)]);
+        assert_ne!(line.signed_area(), 0.0);
+        let c = line.centroid().unwrap();
+        assert!((c.x - 8e6 / 3.0).abs() < 1.0 && (c.y - 8e6 / 9.0).abs() < 1.0, "{c:?}");
+    }
 
+    #[test]
+    fn test_tiny_polygon_centroid() {
+        // The L shape from above at 1e-9 scale: its area is far below
+        // f64::EPSILON, but it is not degenerate.
+        let l = [(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0), (1.0, 2.0), (0.0, 2.0)];
+        let tiny = polygon(&l.map(|(x, y)| (x * 1e-9, y * 1e-9)));
+        let c = tiny.centroid().unwrap();
+        assert_eq!((round_two_digits(c.x * 1e9), round_two_digits(c.y * 1e9)), (0.83, 0.83));
+        let far = polygon(&[(1e7, 1e7), (1e7 + 3.0, 1e7), (1e7 + 3.0, 1e7 + 4.0), (1e7, 1e7 + 4.0)]);
+        assert_eq!(far.centroid(), Some(Point::new(1e7 + 1.5, 1e7 + 2.0)));
+    }
 
+    #[test]
+    fn test_circle() {
+        let circle = Shape::Circle(Circle::new(Point::new(1.0, 2.0), 2.0));
+        assert_eq!(round_two_digits(circle.area()), 12.57);
+        assert_eq!(circle.centroid(), Some(Point::new(1.0, 2.0)));
+    }
+}
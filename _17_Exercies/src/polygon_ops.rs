@@ -0,0 +1,451 @@
+// Computational geometry on top of Point and Polygon.
+// Everything works in f64 with a small tolerance, so points that are "almost"
+// collinear or duplicated are treated the same way as exact ones. Rounding
+// errors grow with the coordinates, so the tolerance is relative: it is scaled
+// by the lengths being compared, and the results don't depend on the units.
+
+use crate::geometry::BoundingBox;
+use crate::{Point, Polygon};
+
+/// Relative tolerance; see the comment at the top.
+const EPSILON: f64 = 1e-12;
+
+/// Cross product of `a - o` and `b - o`:
+/// positive for a left (counter-clockwise) turn, negative for a right turn, zero if collinear.
+pub fn cross(o: Point, a: Point, b: Point) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+/// `cross(o, a, b)`, or exactly zero if that is within rounding of the lengths it multiplies.
+fn turn(o: Point, a: Point, b: Point) -> f64 {
+    let c = cross(o, a, b);
+    if c.abs() <= EPSILON * o.dist(a) * o.dist(b) {
+        0.0
+    } else {
+        c
+    }
+}
+
+/// `scale` is the size of what the points belong to, e.g. an edge length.
+fn same_point(a: Point, b: Point, scale: f64) -> bool {
+    a.dist(b) <= EPSILON * scale
+}
+
+/// Length of the diagonal of the points' bounding box.
+fn extent(points: &[Point]) -> f64 {
+    BoundingBox::from_points(points.iter().copied()).map_or(0.0, |b| b.min.dist(b.max))
+}
+
+/// True if `p` lies on the closed segment `a`-`b`.
+pub fn on_segment(p: Point, a: Point, b: Point) -> bool {
+    let margin = EPSILON * a.dist(b);
+    turn(a, b, p) == 0.0
+        && p.x >= a.x.min(b.x) - margin
+        && p.x <= a.x.max(b.x) + margin
+        && p.y >= a.y.min(b.y) - margin
+        && p.y <= a.y.max(b.y) + margin
+}
+
+/// Distance from `p` to the closed segment `a`-`b`.
+pub fn distance_to_segment(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len_sqr = dx * dx + dy * dy;
+    if len_sqr == 0.0 {
+        return p.dist(a);
+    }
+    let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / len_sqr).clamp(0.0, 1.0);
+    p.dist(Point::new(a.x + t * dx, a.y + t * dy))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentIntersection {
+    None,
+    Point(Point),
+    /// The segments are collinear and share the sub-segment between these points.
+    Overlap(Point, Point),
+}
+
+/// Intersection of the closed segments `a1`-`a2` and `b1`-`b2`.
+pub fn segment_intersection(a1: Point, a2: Point, b1: Point, b2: Point) -> SegmentIntersection {
+    let d1 = turn(b1, b2, a1);
+    let d2 = turn(b1, b2, a2);
+    let d3 = turn(a1, a2, b1);
+    let d4 = turn(a1, a2, b2);
+    let scale = a1.dist(a2).max(b1.dist(b2));
+
+    let collinear = d1 == 0.0 && d2 == 0.0;
+    if collinear {
+        // Project everything onto the longer axis of `a` and intersect the intervals.
+        let use_x = (a2.x - a1.x).abs() >= (a2.y - a1.y).abs();
+        let key = |p: &Point| if use_x { p.x } else { p.y };
+        let (a_lo, a_hi) = if key(&a1) <= key(&a2) { (a1, a2) } else { (a2, a1) };
+        let (b_lo, b_hi) = if key(&b1) <= key(&b2) { (b1, b2) } else { (b2, b1) };
+        let lo = if key(&a_lo) >= key(&b_lo) { a_lo } else { b_lo };
+        let hi = if key(&a_hi) <= key(&b_hi) { a_hi } else { b_hi };
+
+        // Degenerate (zero length) segments fall through to the point checks below.
+        if !same_point(a1, a2, scale) || !same_point(b1, b2, scale) {
+            if key(&lo) > key(&hi) + EPSILON * scale || !on_segment(lo, b1, b2) || !on_segment(lo, a1, a2) {
+                return SegmentIntersection::None;
+            }
+            return if same_point(lo, hi, scale) {
+                SegmentIntersection::Point(lo)
+            } else {
+                SegmentIntersection::Overlap(lo, hi)
+            };
+        }
+    }
+
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        let t = d1 / (d1 - d2);
+        return SegmentIntersection::Point(Point::new(
+            a1.x + t * (a2.x - a1.x),
+            a1.y + t * (a2.y - a1.y),
+        ));
+    }
+
+    // Touching cases: an endpoint lies on the other segment.
+    for (p, s1, s2) in [(a1, b1, b2), (a2, b1, b2), (b1, a1, a2), (b2, a1, a2)] {
+        if on_segment(p, s1, s2) {
+            return SegmentIntersection::Point(p);
+        }
+    }
+    SegmentIntersection::None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Outside,
+    Boundary,
+}
+
+impl Polygon {
+    /// Convex hull in counter-clockwise order, starting from the left-most
+    /// (then lowest) point, using Andrew's monotone chain.
+    /// Duplicate and collinear points are dropped; if every point is collinear
+    /// the hull is the two extreme points, and a single point is its own hull.
+    pub fn convex_hull(&self) -> Polygon {
+        let mut points = self.points.clone();
+        points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        let scale = extent(&points);
+        points.dedup_by(|a, b| same_point(*a, *b, scale));
+
+        if points.len() < 3 {
+            return Polygon { points };
+        }
+
+        // Lower hull left to right, then upper hull right to left; each drops its
+        // last point because it starts the other one.
+        let mut lower = half_hull(points.iter());
+        let upper = half_hull(points.iter().rev());
+        lower.pop();
+        lower.extend(&upper[..upper.len() - 1]);
+        Polygon { points: lower }
+    }
+
+    /// Where `p` lies relative to this polygon, treated as a closed ring.
+    /// Uses ray casting, after checking the edges so boundary points are reported as such.
+    pub fn contains(&self, p: Point) -> Containment {
+        if self.edges().any(|(a, b)| on_segment(p, a, b)) {
+            return Containment::Boundary;
+        }
+        if self.points.len() < 3 {
+            return Containment::Outside;
+        }
+
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            // Half-open test on y so a ray through a vertex is counted once.
+            if (a.y > p.y) != (b.y > p.y) {
+                let x = a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if p.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+        if inside {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        }
+    }
+
+    /// True if two edges of the ring touch anywhere other than the vertex they share.
+    /// Repeated vertices and edges that fold back onto their neighbour count as intersections.
+    pub fn is_self_intersecting(&self) -> bool {
+        let edges: Vec<(Point, Point)> = self.edges().collect();
+        let n = edges.len();
+        if n < 3 {
+            return false;
+        }
+
+        for i in 0..n {
+            for j in i + 1..n {
+                let (a1, a2) = edges[i];
+                let (b1, b2) = edges[j];
+                let adjacent = j == i + 1 || (i == 0 && j == n - 1);
+                match segment_intersection(a1, a2, b1, b2) {
+                    SegmentIntersection::None => {}
+                    SegmentIntersection::Overlap(..) => return true,
+                    SegmentIntersection::Point(p) => {
+                        let shared = if j == i + 1 { a2 } else { a1 };
+                        if !adjacent || !same_point(p, shared, a1.dist(a2).max(b1.dist(b2))) {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Simplifies the ring with Ramer-Douglas-Peucker: vertices closer than
+    /// `tolerance` to the simplified outline are removed. Consecutive duplicates
+    /// are always removed, and a tolerance of zero removes exactly collinear points only.
+    pub fn simplify(&self, tolerance: f64) -> Polygon {
+        let mut points = self.points.clone();
+        let scale = extent(&points);
+        let tolerance = tolerance.max(EPSILON * scale);
+        points.dedup_by(|a, b| same_point(*a, *b, scale));
+        while points.len() > 1 && same_point(points[0], points[points.len() - 1], scale) {
+            points.pop();
+        }
+        if points.len() < 3 {
+            return Polygon { points };
+        }
+
+        // Split the ring at its first point and the vertex farthest from it,
+        // then simplify the two open chains independently.
+        let far = (1..points.len())
+            .max_by(|&i, &j| points[0].dist(points[i]).total_cmp(&points[0].dist(points[j])))
+            .unwrap();
+        let mut keep = vec![false; points.len()];
+        keep[0] = true;
+        keep[far] = true;
+
+        let mut ring = points.clone();
+        ring.push(points[0]);
+        rdp(&ring, 0, far, tolerance, &mut keep);
+        let mut closing = vec![false; ring.len()];
+        rdp(&ring, far, ring.len() - 1, tolerance, &mut closing);
+        for (i, k) in closing.into_iter().enumerate().take(points.len()) {
+            keep[i] |= k;
+        }
+
+        let mut simplified: Vec<Point> = points
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(p, k)| k.then_some(p))
+            .collect();
+
+        // The split point itself may be collinear with its neighbours.
+        if simplified.len() > 3 {
+            let n = simplified.len();
+            if distance_to_segment(simplified[0], simplified[n - 1], simplified[1]) <= tolerance {
+                simplified.remove(0);
+            }
+        }
+        Polygon { points: simplified }
+    }
+}
+
+fn half_hull<'a>(points: impl Iterator<Item = &'a Point>) -> Vec<Point> {
+    let mut hull: Vec<Point> = Vec::new();
+    for &p in points {
+        while hull.len() >= 2 && turn(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    hull
+}
+
+fn rdp(points: &[Point], first: usize, last: usize, tolerance: f64, keep: &mut [bool]) {
+    if last <= first + 1 {
+        return;
+    }
+    let (a, b) = (points[first], points[last]);
+    let (index, dist) = (first + 1..last)
+        .map(|i| (i, distance_to_segment(points[i], a, b)))
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+        .unwrap();
+    if dist > tolerance {
+        keep[index] = true;
+        rdp(points, first, index, tolerance, keep);
+        rdp(points, index, last, tolerance, keep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(points: &[(f64, f64)]) -> Polygon {
+        Polygon { points: points.iter().map(|&(x, y)| Point::new(x, y)).collect() }
+    }
+
+    fn polygon_of(points: &[Point]) -> Polygon {
+        Polygon { points: points.to_vec() }
+    }
+
+    fn square() -> Polygon {
+        polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)])
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let poly = polygon(&[(2.0, 2.0), (0.0, 4.0), (4.0, 0.0), (1.0, 1.0), (0.0, 0.0), (4.0, 4.0), (3.0, 1.0)]);
+        assert_eq!(poly.convex_hull().points, square().points);
+        assert_eq!(poly.convex_hull().left_most_point(), Some(Point::new(0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_convex_hull_degenerate() {
+        // Duplicates and points on the hull edges are dropped.
+        let poly = polygon(&[(0.0, 0.0), (2.0, 0.0), (4.0, 0.0), (4.0, 4.0), (4.0, 4.0), (0.0, 4.0), (0.0, 2.0)]);
+        assert_eq!(poly.convex_hull().points, square().points);
+
+        let collinear = polygon(&[(1.0, 1.0), (3.0, 3.0), (0.0, 0.0), (2.0, 2.0)]);
+        assert_eq!(collinear.convex_hull().points, vec![Point::new(0.0, 0.0), Point::new(3.0, 3.0)]);
+
+        let same = polygon(&[(1.0, 1.0), (1.0, 1.0), (1.0, 1.0)]);
+        assert_eq!(same.convex_hull().points, vec![Point::new(1.0, 1.0)]);
+        assert!(Polygon::new().convex_hull().points.is_empty());
+    }
+
+    /// `poly` scaled by `factor` and moved by `offset` in both directions.
+    fn moved(poly: &Polygon, factor: f64, offset: f64) -> Polygon {
+        polygon(&poly.points.iter().map(|p| (p.x * factor + offset, p.y * factor + offset)).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_tolerance_scales_with_coordinates() {
+        // Corners, an interior point and a point a third of the way along the bottom edge,
+        // which is collinear only up to rounding.
+        let poly = polygon(&[(0.0, 0.0), (3.0, 0.0), (3.0, 3.0), (0.0, 3.0), (1.0, 1.0), (1.0, 0.0)]);
+        for (factor, offset) in [(1e-6, 0.0), (1e-6, 1e-3), (1.0 / 3.0, 0.0), (1e9 / 3.0, 1e9), (1e9, -7e9)] {
+            let poly = moved(&poly, factor, offset);
+            let hull = poly.convex_hull();
+            assert_eq!(hull.points.len(), 4, "{factor} {offset}: {hull:?}");
+            assert!((hull.area() - 9.0 * factor * factor).abs() <= 1e-9 * hull.area(), "{factor} {offset}");
+
+            let [a, b, c, d, inner, third] = poly.points[..] else { unreachable!() };
+            let sq = polygon_of(&[a, b, c, d]);
+            assert_eq!(sq.contains(inner), Containment::Inside, "{factor} {offset}");
+            assert_eq!(sq.contains(third), Containment::Boundary, "{factor} {offset}");
+            assert!(!sq.is_self_intersecting(), "{factor} {offset}");
+            assert!(polygon_of(&[a, c, b, d]).is_self_intersecting(), "{factor} {offset}");
+            assert_eq!(polygon_of(&[a, third, b, c, d]).simplify(0.0).points, sq.points, "{factor} {offset}");
+            assert_eq!(
+                segment_intersection(a, third, third, b),
+                SegmentIntersection::Point(third),
+                "{factor} {offset}"
+            );
+            let beyond = Point::new(b.x * 2.0 - a.x, b.y);
+            let overlap = segment_intersection(a, b, third, beyond);
+            assert!(matches!(overlap, SegmentIntersection::Overlap(..)), "{factor} {offset}: {overlap:?}");
+            assert_eq!(segment_intersection(a, b, d, c), SegmentIntersection::None, "{factor} {offset}");
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        let sq = square();
+        assert_eq!(sq.contains(Point::new(2.0, 2.0)), Containment::Inside);
+        assert_eq!(sq.contains(Point::new(5.0, 2.0)), Containment::Outside);
+        assert_eq!(sq.contains(Point::new(4.0, 2.0)), Containment::Boundary);
+        assert_eq!(sq.contains(Point::new(0.0, 0.0)), Containment::Boundary);
+        // The ray from this point passes exactly through the vertex at (4, 4).
+        assert_eq!(sq.contains(Point::new(-1.0, 4.0)), Containment::Outside);
+    }
+
+    #[test]
+    fn test_contains_concave() {
+        let l = polygon(&[(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0), (1.0, 2.0), (0.0, 2.0)]);
+        assert_eq!(l.contains(Point::new(0.5, 1.5)), Containment::Inside);
+        assert_eq!(l.contains(Point::new(1.5, 1.5)), Containment::Outside);
+        assert_eq!(l.contains(Point::new(1.5, 1.0)), Containment::Boundary);
+    }
+
+    #[test]
+    fn test_segment_intersection() {
+        let p = Point::new;
+        assert_eq!(
+            segment_intersection(p(0.0, 0.0), p(4.0, 4.0), p(0.0, 4.0), p(4.0, 0.0)),
+            SegmentIntersection::Point(p(2.0, 2.0))
+        );
+        assert_eq!(
+            segment_intersection(p(0.0, 0.0), p(1.0, 1.0), p(0.0, 4.0), p(4.0, 0.0)),
+            SegmentIntersection::None
+        );
+        // T junction.
+        assert_eq!(
+            segment_intersection(p(0.0, 0.0), p(4.0, 0.0), p(2.0, 0.0), p(2.0, 3.0)),
+            SegmentIntersection::Point(p(2.0, 0.0))
+        );
+        // Parallel but apart.
+        assert_eq!(
+            segment_intersection(p(0.0, 0.0), p(4.0, 0.0), p(0.0, 1.0), p(4.0, 1.0)),
+            SegmentIntersection::None
+        );
+    }
+
+    #[test]
+    fn test_segment_intersection_collinear() {
+        let p = Point::new;
+        assert_eq!(
+            segment_intersection(p(0.0, 0.0), p(4.0, 0.0), p(6.0, 0.0), p(2.0, 0.0)),
+            SegmentIntersection::Overlap(p(2.0, 0.0), p(4.0, 0.0))
+        );
+        assert_eq!(
+            segment_intersection(p(0.0, 0.0), p(2.0, 2.0), p(2.0, 2.0), p(3.0, 3.0)),
+            SegmentIntersection::Point(p(2.0, 2.0))
+        );
+        assert_eq!(
+            segment_intersection(p(0.0, 0.0), p(1.0, 0.0), p(2.0, 0.0), p(3.0, 0.0)),
+            SegmentIntersection::None
+        );
+        // Zero-length segments behave like points.
+        assert_eq!(
+            segment_intersection(p(1.0, 0.0), p(1.0, 0.0), p(0.0, 0.0), p(3.0, 0.0)),
+            SegmentIntersection::Point(p(1.0, 0.0))
+        );
+        assert_eq!(
+            segment_intersection(p(1.0, 1.0), p(1.0, 1.0), p(0.0, 0.0), p(3.0, 0.0)),
+            SegmentIntersection::None
+        );
+    }
+
+    #[test]
+    fn test_self_intersection() {
+        assert!(!square().is_self_intersecting());
+        let bowtie = polygon(&[(0.0, 0.0), (4.0, 4.0), (4.0, 0.0), (0.0, 4.0)]);
+        assert!(bowtie.is_self_intersecting());
+        let spike = polygon(&[(0.0, 0.0), (4.0, 0.0), (2.0, 0.0), (2.0, 2.0)]);
+        assert!(spike.is_self_intersecting());
+        // Collinear vertices along an edge are fine.
+        let square_with_midpoint = polygon(&[(0.0, 0.0), (2.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+        assert!(!square_with_midpoint.is_self_intersecting());
+    }
+
+    #[test]
+    fn test_simplify() {
+        let noisy = polygon(&[
+            (0.0, 0.0), (2.0, 0.05), (4.0, 0.0), (4.0, 4.0), (4.0, 4.0), (2.0, 4.0), (0.0, 4.0), (-0.05, 2.0),
+        ]);
+        assert_eq!(noisy.simplify(0.1).points, square().points);
+        // The bumps are larger than this tolerance, so only the duplicate and the collinear point go.
+        assert_eq!(noisy.simplify(0.0).points.len(), 6);
+    }
+
+    #[test]
+    fn test_simplify_drops_collinear_start() {
+        let poly = polygon(&[(2.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0), (0.0, 0.0), (2.0, 0.0)]);
+        let simplified = poly.simplify(0.0);
+        assert_eq!(simplified.points.len(), 4);
+        assert_eq!(simplified.area(), 16.0);
+    }
+}
@@ -0,0 +1,722 @@
+// R-tree over anything with a bounding box.
+// Nodes hold between MIN_ENTRIES and MAX_ENTRIES children (the root may hold
+// fewer). Overfull nodes are split at the median along the axis where their
+// children are most spread out; underfull nodes after a removal are dissolved
+// and their items reinserted. `bulk_load` packs the tree bottom-up with
+// Sort-Tile-Recursive, which gives tighter boxes than inserting one by one.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::geometry::{BoundingBox, Geometry};
+use crate::Point;
+
+const MAX_ENTRIES: usize = 16;
+const MIN_ENTRIES: usize = 4;
+
+#[derive(Debug)]
+struct Entry<T> {
+    bbox: BoundingBox,
+    item: T,
+}
+
+#[derive(Debug)]
+struct Child<T> {
+    bbox: BoundingBox,
+    node: Node<T>,
+}
+
+#[derive(Debug)]
+enum Node<T> {
+    Leaf(Vec<Entry<T>>),
+    Internal(Vec<Child<T>>),
+}
+
+trait Boxed {
+    fn bbox(&self) -> BoundingBox;
+}
+
+impl<T> Boxed for Entry<T> {
+    fn bbox(&self) -> BoundingBox {
+        self.bbox
+    }
+}
+
+impl<T> Boxed for Child<T> {
+    fn bbox(&self) -> BoundingBox {
+        self.bbox
+    }
+}
+
+fn union_all<E: Boxed>(items: &[E]) -> Option<BoundingBox> {
+    items.iter().map(Boxed::bbox).reduce(|a, b| a.union(&b))
+}
+
+impl<T> Node<T> {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(entries) => entries.len(),
+            Node::Internal(children) => children.len(),
+        }
+    }
+
+    fn bbox(&self) -> Option<BoundingBox> {
+        match self {
+            Node::Leaf(entries) => union_all(entries),
+            Node::Internal(children) => union_all(children),
+        }
+    }
+
+    fn into_child(self) -> Child<T> {
+        let bbox = self.bbox().expect("only non-empty nodes become children");
+        Child { bbox, node: self }
+    }
+
+    fn drain_into(self, out: &mut Vec<Entry<T>>) {
+        match self {
+            Node::Leaf(entries) => out.extend(entries),
+            Node::Internal(children) => {
+                for child in children {
+                    child.node.drain_into(out);
+                }
+            }
+        }
+    }
+}
+
+/// Splits off the upper half of `items`, ordered along the axis where their centers spread the most.
+fn split<E: Boxed>(items: &mut Vec<E>) -> Vec<E> {
+    let centers = || items.iter().map(|e| e.bbox().center());
+    let spread = |key: fn(&Point) -> f64| {
+        let (lo, hi) = centers()
+            .map(|c| key(&c))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        hi - lo
+    };
+    let key: fn(&Point) -> f64 = if spread(|p| p.x) >= spread(|p| p.y) { |p| p.x } else { |p| p.y };
+
+    items.sort_by(|a, b| key(&a.bbox().center()).total_cmp(&key(&b.bbox().center())));
+    items.split_off(items.len() / 2)
+}
+
+fn enlargement(bbox: &BoundingBox, added: &BoundingBox) -> f64 {
+    bbox.union(added).area() - bbox.area()
+}
+
+fn insert<T>(node: &mut Node<T>, entry: Entry<T>) -> Option<Node<T>> {
+    match node {
+        Node::Leaf(entries) => {
+            entries.push(entry);
+            if entries.len() > MAX_ENTRIES {
+                return Some(Node::Leaf(split(entries)));
+            }
+        }
+        Node::Internal(children) => {
+            let best = children
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    enlargement(&a.bbox, &entry.bbox)
+                        .total_cmp(&enlargement(&b.bbox, &entry.bbox))
+                        .then(a.bbox.area().total_cmp(&b.bbox.area()))
+                })
+                .map(|(i, _)| i)
+                .expect("internal nodes are never empty");
+
+            let child = &mut children[best];
+            child.bbox = child.bbox.union(&entry.bbox);
+            if let Some(sibling) = insert(&mut child.node, entry) {
+                child.bbox = child.node.bbox().expect("split keeps both halves non-empty");
+                children.push(sibling.into_child());
+                if children.len() > MAX_ENTRIES {
+                    return Some(Node::Internal(split(children)));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Removes the first entry inside `bbox` for which `matches` is true. Children
+/// that drop below MIN_ENTRIES are removed and their entries pushed to `orphans`.
+fn remove<T>(
+    node: &mut Node<T>,
+    bbox: &BoundingBox,
+    matches: &dyn Fn(&T) -> bool,
+    orphans: &mut Vec<Entry<T>>,
+) -> Option<T> {
+    match node {
+        Node::Leaf(entries) => {
+            let index = entries
+                .iter()
+                .position(|e| e.bbox.intersects(bbox) && matches(&e.item))?;
+            Some(entries.swap_remove(index).item)
+        }
+        Node::Internal(children) => {
+            for i in 0..children.len() {
+                if !children[i].bbox.intersects(bbox) {
+                    continue;
+                }
+                let Some(item) = remove(&mut children[i].node, bbox, matches, orphans) else {
+                    continue;
+                };
+                if children[i].node.len() < MIN_ENTRIES {
+                    children.swap_remove(i).node.drain_into(orphans);
+                } else {
+                    children[i].bbox = children[i].node.bbox().expect("node is not empty");
+                }
+                return Some(item);
+            }
+            None
+        }
+    }
+}
+
+/// Orders heap entries by distance, closest first.
+struct Candidate<'a, T> {
+    dist: f64,
+    kind: CandidateKind<'a, T>,
+}
+
+enum CandidateKind<'a, T> {
+    Node(&'a Node<T>),
+    Item(&'a T),
+}
+
+impl<T> PartialEq for Candidate<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.dist == other.dist
+    }
+}
+
+impl<T> Eq for Candidate<'_, T> {}
+
+impl<T> PartialOrd for Candidate<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Candidate<'_, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.dist.total_cmp(&self.dist)
+    }
+}
+
+#[derive(Debug)]
+pub struct RTree<T> {
+    root: Node<T>,
+    /// Items without a bounding box (e.g. empty polygons); never returned by spatial queries.
+    unbounded: Vec<T>,
+    len: usize,
+}
+
+impl<T: Geometry> Default for RTree<T> {
+    fn default() -> Self {
+        RTree::new()
+    }
+}
+
+impl<T: Geometry> RTree<T> {
+    pub fn new() -> Self {
+        RTree { root: Node::Leaf(Vec::new()), unbounded: Vec::new(), len: 0 }
+    }
+
+    /// Builds a packed tree with Sort-Tile-Recursive: sort by x, cut into vertical
+    /// slices, sort each slice by y and fill nodes, then repeat one level up.
+    pub fn bulk_load(items: impl IntoIterator<Item = T>) -> Self {
+        let mut tree = RTree::new();
+        let mut entries = Vec::new();
+        for item in items {
+            tree.len += 1;
+            match item.bounding_box() {
+                Some(bbox) => entries.push(Entry { bbox, item }),
+                None => tree.unbounded.push(item),
+            }
+        }
+        if entries.is_empty() {
+            return tree;
+        }
+
+        let mut level: Vec<Node<T>> = pack(entries).into_iter().map(Node::Leaf).collect();
+        while level.len() > 1 {
+            let children = level.into_iter().map(Node::into_child).collect();
+            level = pack(children).into_iter().map(Node::Internal).collect();
+        }
+        tree.root = level.pop().unwrap();
+        tree
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, item: T) {
+        self.len += 1;
+        let Some(bbox) = item.bounding_box() else {
+            self.unbounded.push(item);
+            return;
+        };
+        self.insert_entry(Entry { bbox, item });
+    }
+
+    fn insert_entry(&mut self, entry: Entry<T>) {
+        if let Some(sibling) = insert(&mut self.root, entry) {
+            let old_root = std::mem::replace(&mut self.root, Node::Leaf(Vec::new()));
+            self.root = Node::Internal(vec![old_root.into_child(), sibling.into_child()]);
+        }
+    }
+
+    /// Removes and returns an item equal to `item`, if there is one.
+    pub fn remove(&mut self, item: &T) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        let removed = match item.bounding_box() {
+            None => {
+                let index = self.unbounded.iter().position(|u| u == item)?;
+                Some(self.unbounded.swap_remove(index))
+            }
+            Some(bbox) => {
+                let mut orphans = Vec::new();
+                let removed = remove(&mut self.root, &bbox, &|candidate| candidate == item, &mut orphans);
+
+                // A root with a single child is replaced by that child.
+                while let Node::Internal(children) = &mut self.root {
+                    if children.len() != 1 {
+                        break;
+                    }
+                    self.root = children.pop().unwrap().node;
+                }
+                if let Node::Internal(children) = &self.root {
+                    if children.is_empty() {
+                        self.root = Node::Leaf(Vec::new());
+                    }
+                }
+                for orphan in orphans {
+                    self.insert_entry(orphan);
+                }
+                removed
+            }
+        };
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Items whose bounding box intersects `area`.
+    pub fn query(&self, area: &BoundingBox) -> Vec<&T> {
+        let mut found = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Leaf(entries) => {
+                    found.extend(entries.iter().filter(|e| e.bbox.intersects(area)).map(|e| &e.item));
+                }
+                Node::Internal(children) => {
+                    stack.extend(children.iter().filter(|c| c.bbox.intersects(area)).map(|c| &c.node));
+                }
+            }
+        }
+        found
+    }
+
+    /// Items within `radius` of `center`, measured to their bounding boxes.
+    pub fn within(&self, center: Point, radius: f64) -> Vec<&T> {
+        let r = Point::new(radius, radius);
+        let area = BoundingBox::new(center - r, center + r);
+        let mut found = self.query(&area);
+        found.retain(|item| {
+            let bbox = item.bounding_box().expect("indexed items have a bounding box");
+            bbox.distance_to(center) <= radius
+        });
+        found
+    }
+
+    /// The item whose bounding box is closest to `p`.
+    pub fn nearest(&self, p: Point) -> Option<&T> {
+        self.nearest_k(p, 1).pop()
+    }
+
+    /// Up to `k` items, closest first, by distance from `p` to their bounding boxes.
+    pub fn nearest_k(&self, p: Point, k: usize) -> Vec<&T> {
+        self.nearest_k_by(p, k, |item, p| {
+            item.bounding_box().expect("indexed items have a bounding box").distance_to(p)
+        })
+    }
+
+    /// Like `nearest_k` with a custom distance, e.g. to the exact outline of a shape.
+    /// `distance(item, p)` must never be smaller than the distance from `p` to the
+    /// item's bounding box, which holds for any distance to a point of the shape.
+    pub fn nearest_k_by(&self, p: Point, k: usize, distance: impl Fn(&T, Point) -> f64) -> Vec<&T> {
+        let mut found = Vec::with_capacity(k);
+        let mut heap = BinaryHeap::new();
+        if let Some(bbox) = self.root.bbox() {
+            heap.push(Candidate { dist: bbox.distance_to(p), kind: CandidateKind::Node(&self.root) });
+        }
+
+        // Best-first search: an item popped off the heap is closer than anything still queued.
+        while let Some(Candidate { kind, .. }) = heap.pop() {
+            if found.len() == k {
+                break;
+            }
+            match kind {
+                CandidateKind::Item(item) => found.push(item),
+                CandidateKind::Node(Node::Leaf(entries)) => {
+                    for e in entries {
+                        heap.push(Candidate { dist: distance(&e.item, p), kind: CandidateKind::Item(&e.item) });
+                    }
+                }
+                CandidateKind::Node(Node::Internal(children)) => {
+                    for c in children {
+                        heap.push(Candidate { dist: c.bbox.distance_to(p), kind: CandidateKind::Node(&c.node) });
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let mut stack = vec![&self.root];
+        let mut items: Vec<&T> = self.unbounded.iter().collect();
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Leaf(entries) => items.extend(entries.iter().map(|e| &e.item)),
+                Node::Internal(children) => stack.extend(children.iter().map(|c| &c.node)),
+            }
+        }
+        items.into_iter()
+    }
+
+    /// Height of the tree; a single leaf has depth 1.
+    pub fn depth(&self) -> usize {
+        let mut depth = 1;
+        let mut node = &self.root;
+        while let Node::Internal(children) = node {
+            depth += 1;
+            node = &children[0].node;
+        }
+        depth
+    }
+}
+
+/// Groups `items` into nodes of MIN_ENTRIES..=MAX_ENTRIES for one level of
+/// Sort-Tile-Recursive. A lone group may be smaller; it becomes the root.
+fn pack<E: Boxed>(mut items: Vec<E>) -> Vec<Vec<E>> {
+    let node_count = items.len().div_ceil(MAX_ENTRIES);
+    let slice_count = (node_count as f64).sqrt().ceil() as usize;
+    let slice_len = slice_count * MAX_ENTRIES;
+
+    items.sort_by(|a, b| a.bbox().center().x.total_cmp(&b.bbox().center().x));
+    let mut groups = Vec::with_capacity(node_count);
+    let mut items = items.into_iter();
+    loop {
+        let mut slice: Vec<E> = items.by_ref().take(slice_len).collect();
+        if slice.is_empty() {
+            break;
+        }
+        slice.sort_by(|a, b| a.bbox().center().y.total_cmp(&b.bbox().center().y));
+        let mut slice = slice.into_iter();
+        loop {
+            let group: Vec<E> = slice.by_ref().take(MAX_ENTRIES).collect();
+            if group.is_empty() {
+                break;
+            }
+            groups.push(group);
+        }
+        // Only the last group of a slice can come up short. Evening it out
+        // with the group before it (in this slice or the previous one) keeps
+        // every node at MIN_ENTRIES or more.
+        if groups.len() > 1 && groups[groups.len() - 1].len() < MIN_ENTRIES {
+            let short = groups.pop().unwrap();
+            let previous = groups.last_mut().unwrap();
+            previous.extend(short);
+            if previous.len() > MAX_ENTRIES {
+                let second_half = previous.split_off(previous.len() / 2);
+                groups.push(second_half);
+            }
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Circle, Polygon, Shape};
+    use std::time::Instant;
+
+    /// Scatters the test shapes; a fixed seed gives the same layout every run.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn float(&mut self, max: f64) -> f64 {
+            (self.next() % 1_000_000) as f64 / 1_000_000.0 * max
+        }
+    }
+
+    fn random_shapes(rng: &mut Rng, count: usize) -> Vec<Shape> {
+        (0..count)
+            .map(|i| {
+                let center = Point::new(rng.float(1000.0), rng.float(1000.0));
+                if i % 2 == 0 {
+                    Shape::Circle(Circle::new(center, rng.float(5.0)))
+                } else {
+                    let mut poly = Polygon::new();
+                    for _ in 0..3 {
+                        poly.add_point(center + Point::new(rng.float(8.0), rng.float(8.0)));
+                    }
+                    Shape::Polygon(poly)
+                }
+            })
+            .collect()
+    }
+
+    fn brute_query<'a>(shapes: &'a [Shape], area: &BoundingBox) -> Vec<&'a Shape> {
+        shapes
+            .iter()
+            .filter(|s| s.bounding_box().is_some_and(|b| b.intersects(area)))
+            .collect()
+    }
+
+    fn sorted(mut found: Vec<&Shape>) -> Vec<String> {
+        let mut keys: Vec<String> = found.drain(..).map(|s| format!("{s:?}")).collect();
+        keys.sort();
+        keys
+    }
+
+    /// Checks node sizes, that every box covers its children, and that all leaves are at the same depth.
+    fn check_node<T>(node: &Node<T>, is_root: bool, depth: usize, leaf_depth: &mut Option<usize>) {
+        assert!(node.len() <= MAX_ENTRIES);
+        if !is_root {
+            assert!(node.len() >= MIN_ENTRIES, "node with {} entries", node.len());
+        }
+        match node {
+            Node::Leaf(_) => {
+                assert_eq!(*leaf_depth.get_or_insert(depth), depth, "leaves at different depths");
+            }
+            Node::Internal(children) => {
+                for child in children {
+                    assert_eq!(Some(child.bbox), child.node.bbox());
+                    check_node(&child.node, false, depth + 1, leaf_depth);
+                }
+            }
+        }
+    }
+
+    fn check_tree<T: Geometry>(tree: &RTree<T>) {
+        let mut leaf_depth = None;
+        check_node(&tree.root, true, 1, &mut leaf_depth);
+        assert_eq!(leaf_depth, Some(tree.depth()));
+        assert_eq!(tree.iter().count(), tree.len());
+    }
+
+    #[test]
+    fn test_insert_and_query() {
+        let mut rng = Rng(7);
+        let shapes = random_shapes(&mut rng, 2000);
+        let mut tree = RTree::new();
+        for s in shapes.clone() {
+            tree.insert(s);
+        }
+        check_tree(&tree);
+        assert_eq!(tree.len(), 2000);
+        assert!(tree.depth() > 1);
+
+        for _ in 0..50 {
+            let corner = Point::new(rng.float(1000.0), rng.float(1000.0));
+            let area = BoundingBox::new(corner, corner + Point::new(rng.float(100.0), rng.float(100.0)));
+            assert_eq!(sorted(tree.query(&area)), sorted(brute_query(&shapes, &area)));
+        }
+    }
+
+    #[test]
+    fn test_bulk_load_matches_brute_force() {
+        let mut rng = Rng(11);
+        let shapes = random_shapes(&mut rng, 3000);
+        let tree = RTree::bulk_load(shapes.clone());
+        check_tree(&tree);
+
+        for _ in 0..50 {
+            let corner = Point::new(rng.float(1000.0), rng.float(1000.0));
+            let area = BoundingBox::new(corner, corner + Point::new(50.0, 50.0));
+            assert_eq!(sorted(tree.query(&area)), sorted(brute_query(&shapes, &area)));
+        }
+    }
+
+    #[test]
+    fn test_bulk_load_fills_nodes() {
+        let mut rng = Rng(5);
+        let shapes = random_shapes(&mut rng, 600);
+        // Sizes around multiples of MAX_ENTRIES leave a short last group before rebalancing.
+        for n in (1..=70).chain([257, 273, 289, 545, 600]) {
+            let tree = RTree::bulk_load(shapes[..n].to_vec());
+            check_tree(&tree);
+            assert_eq!(tree.len(), n);
+        }
+    }
+
+    #[test]
+    fn test_nearest() {
+        let mut rng = Rng(13);
+        let shapes = random_shapes(&mut rng, 1500);
+        let tree = RTree::bulk_load(shapes.clone());
+        let bbox_dist = |s: &Shape, p: Point| s.bounding_box().unwrap().distance_to(p);
+
+        for _ in 0..100 {
+            let p = Point::new(rng.float(1200.0) - 100.0, rng.float(1200.0) - 100.0);
+            let mut expected: Vec<f64> = shapes.iter().map(|s| bbox_dist(s, p)).collect();
+            expected.sort_by(f64::total_cmp);
+
+            let nearest = tree.nearest_k(p, 5);
+            let dists: Vec<f64> = nearest.iter().map(|s| bbox_dist(s, p)).collect();
+            assert_eq!(dists, expected[..5]);
+            assert_eq!(bbox_dist(tree.nearest(p).unwrap(), p), expected[0]);
+        }
+    }
+
+    #[test]
+    fn test_nearest_by_exact_distance() {
+        let mut tree = RTree::new();
+        // The big circle's bounding box is closer to the query point than the small one,
+        // but its outline is farther away.
+        tree.insert(Shape::Circle(Circle::new(Point::new(0.0, 0.0), 10.0)));
+        tree.insert(Shape::Circle(Circle::new(Point::new(12.0, 12.0), 1.0)));
+        let p = Point::new(9.5, 9.5);
+        let to_circle = |s: &Shape, p: Point| match s {
+            Shape::Circle(c) => (c.center.dist(p) - c.radius).max(0.0),
+            Shape::Polygon(_) => unreachable!(),
+        };
+
+        let by_box = tree.nearest(p).unwrap();
+        let by_outline = tree.nearest_k_by(p, 1, to_circle)[0];
+        assert_eq!(by_box.area(), Circle::new(Point::new(0.0, 0.0), 10.0).area());
+        assert_eq!(by_outline.centroid(), Some(Point::new(12.0, 12.0)));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut rng = Rng(17);
+        let shapes = random_shapes(&mut rng, 1000);
+        let mut tree = RTree::bulk_load(shapes.clone());
+
+        for s in &shapes[..900] {
+            assert_eq!(tree.remove(s).as_ref(), Some(s));
+        }
+        check_tree(&tree);
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.remove(&shapes[0]), None);
+
+        let everything = BoundingBox::new(Point::new(-10.0, -10.0), Point::new(1100.0, 1100.0));
+        assert_eq!(sorted(tree.query(&everything)), sorted(shapes[900..].iter().collect()));
+
+        for s in &shapes[900..] {
+            assert!(tree.remove(s).is_some());
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.depth(), 1);
+        assert!(tree.nearest(Point::new(0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_interleaved_insert_remove() {
+        let mut rng = Rng(19);
+        let shapes = random_shapes(&mut rng, 1200);
+        let mut tree = RTree::new();
+        let mut model: Vec<Shape> = Vec::new();
+
+        for (i, s) in shapes.into_iter().enumerate() {
+            tree.insert(s.clone());
+            model.push(s);
+            if i % 3 == 2 {
+                let victim = model.swap_remove((rng.next() % model.len() as u64) as usize);
+                assert!(tree.remove(&victim).is_some());
+            }
+        }
+        check_tree(&tree);
+        assert_eq!(tree.len(), model.len());
+        let area = BoundingBox::new(Point::new(200.0, 200.0), Point::new(600.0, 500.0));
+        assert_eq!(sorted(tree.query(&area)), sorted(brute_query(&model, &area)));
+    }
+
+    #[test]
+    fn test_within_and_unbounded_items() {
+        let mut tree = RTree::new();
+        tree.insert(Shape::Polygon(Polygon::new()));
+        tree.insert(Shape::Circle(Circle::new(Point::new(3.0, 4.0), 1.0)));
+        tree.insert(Shape::Circle(Circle::new(Point::new(30.0, 40.0), 1.0)));
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.within(Point::new(0.0, 0.0), 4.0).len(), 1);
+        assert_eq!(tree.within(Point::new(0.0, 0.0), 3.0).len(), 0);
+
+        // Empty polygons are kept but never found by spatial queries.
+        assert_eq!(tree.iter().count(), 3);
+        assert_eq!(tree.remove(&Shape::Polygon(Polygon::new())), Some(Shape::Polygon(Polygon::new())));
+        assert_eq!(tree.len(), 2);
+    }
+
+    // cargo test --release bench_ -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_against_brute_force() {
+        for count in [10_000, 100_000] {
+            let mut rng = Rng(23);
+            let shapes = random_shapes(&mut rng, count);
+            let queries: Vec<Point> = (0..1000).map(|_| Point::new(rng.float(1000.0), rng.float(1000.0))).collect();
+
+            let start = Instant::now();
+            let tree = RTree::bulk_load(shapes.clone());
+            let bulk = start.elapsed();
+
+            let start = Instant::now();
+            let mut inserted = RTree::new();
+            for s in shapes.clone() {
+                inserted.insert(s);
+            }
+            let one_by_one = start.elapsed();
+
+            let start = Instant::now();
+            let mut hits = 0;
+            for &q in &queries {
+                let area = BoundingBox::new(q, q + Point::new(10.0, 10.0));
+                hits += brute_query(&shapes, &area).len();
+                shapes
+                    .iter()
+                    .min_by(|a, b| {
+                        let (a, b) = (a.bounding_box().unwrap(), b.bounding_box().unwrap());
+                        a.distance_to(q).total_cmp(&b.distance_to(q))
+                    })
+                    .unwrap();
+            }
+            let brute = start.elapsed();
+
+            let start = Instant::now();
+            let mut tree_hits = 0;
+            for &q in &queries {
+                let area = BoundingBox::new(q, q + Point::new(10.0, 10.0));
+                tree_hits += tree.query(&area).len();
+                tree.nearest(q).unwrap();
+            }
+            let indexed = start.elapsed();
+            assert_eq!(hits, tree_hits);
+
+            println!(
+                "{count} shapes: bulk load {bulk:?}, insert one by one {one_by_one:?}, \
+                 1000 range + nearest queries: brute force {brute:?}, r-tree {indexed:?}"
+            );
+        }
+    }
+}
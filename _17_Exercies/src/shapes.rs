@@ -0,0 +1,269 @@
+// Rectangle, Ellipse and Segment are added through the `Geometry` trait rather
+// than as `Shape` variants, the same way a downstream crate would add its own.
+
+use std::f64::consts::PI;
+
+use crate::geometry::{BoundingBox, Geometry};
+use crate::transform::Transform;
+use crate::{Circle, Point, Polygon};
+
+/// Axis-aligned rectangle. Transforming it gives a polygon unless the transform
+/// keeps the sides axis-aligned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    corners: BoundingBox,
+}
+
+impl Rectangle {
+    /// Any two opposite corners.
+    pub fn new(a: Point, b: Point) -> Self {
+        Rectangle { corners: BoundingBox::new(a, b) }
+    }
+
+    pub fn to_polygon(self) -> Polygon {
+        let BoundingBox { min, max } = self.corners;
+        Polygon {
+            points: vec![min, Point::new(max.x, min.y), max, Point::new(min.x, max.y)],
+        }
+    }
+
+    pub fn transformed(&self, t: &Transform) -> Polygon {
+        self.to_polygon().transformed(t)
+    }
+}
+
+impl Geometry for Rectangle {
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(self.corners)
+    }
+
+    fn area(&self) -> f64 {
+        self.corners.area()
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.corners.width() + self.corners.height())
+    }
+
+    fn centroid(&self) -> Option<Point> {
+        Some(self.corners.center())
+    }
+
+    fn transform(&self, t: &Transform) -> Box<dyn Geometry> {
+        if t.keeps_axes() {
+            Box::new(Rectangle::new(t.apply(self.corners.min), t.apply(self.corners.max)))
+        } else {
+            Box::new(self.transformed(t))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ellipse {
+    center: Point,
+    radius_x: f64,
+    radius_y: f64,
+    /// Counter-clockwise rotation of the x radius, in radians.
+    rotation: f64,
+}
+
+impl Ellipse {
+    pub fn new(center: Point, radius_x: f64, radius_y: f64, rotation: f64) -> Self {
+        Ellipse { center, radius_x: radius_x.abs(), radius_y: radius_y.abs(), rotation }
+    }
+
+    pub fn center(&self) -> Point {
+        self.center
+    }
+
+    pub fn radius_x(&self) -> f64 {
+        self.radius_x
+    }
+
+    pub fn radius_y(&self) -> f64 {
+        self.radius_y
+    }
+
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    /// `Some` if both radii are equal (within rounding).
+    pub fn as_circle(&self) -> Option<Circle> {
+        let tolerance = 1e-9 * self.radius_x.max(self.radius_y).max(1.0);
+        if (self.radius_x - self.radius_y).abs() <= tolerance {
+            Some(Circle::new(self.center, self.radius_x))
+        } else {
+            None
+        }
+    }
+
+    /// The ellipse is the unit circle mapped through `R(rotation) * diag(radius_x, radius_y)`.
+    /// Applying `t` multiplies that matrix by t's linear part; its singular value
+    /// decomposition gives the new radii and rotation.
+    pub fn transformed(&self, t: &Transform) -> Ellipse {
+        let (sin, cos) = self.rotation.sin_cos();
+        let axis_x = t.apply_vector(Point::new(cos, sin) * self.radius_x);
+        let axis_y = t.apply_vector(Point::new(-sin, cos) * self.radius_y);
+        // Columns of the new matrix [[a, b], [c, d]].
+        let (a, b, c, d) = (axis_x.x, axis_y.x, axis_x.y, axis_y.y);
+
+        let e = (a + d) / 2.0;
+        let f = (a - d) / 2.0;
+        let g = (c + b) / 2.0;
+        let h = (c - b) / 2.0;
+        let q = e.hypot(h);
+        let r = f.hypot(g);
+        let rotation = (g.atan2(f) + h.atan2(e)) / 2.0;
+
+        Ellipse::new(t.apply(self.center), q + r, q - r, rotation)
+    }
+}
+
+impl Geometry for Ellipse {
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let (sin, cos) = self.rotation.sin_cos();
+        let half_w = (self.radius_x * cos).hypot(self.radius_y * sin);
+        let half_h = (self.radius_x * sin).hypot(self.radius_y * cos);
+        let half = Point::new(half_w, half_h);
+        Some(BoundingBox::new(self.center - half, self.center + half))
+    }
+
+    fn area(&self) -> f64 {
+        PI * self.radius_x * self.radius_y
+    }
+
+    /// Ramanujan's second approximation; exact for circles.
+    fn perimeter(&self) -> f64 {
+        let (a, b) = (self.radius_x, self.radius_y);
+        if a + b == 0.0 {
+            return 0.0;
+        }
+        let h = ((a - b) / (a + b)).powi(2);
+        PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+    }
+
+    fn centroid(&self) -> Option<Point> {
+        Some(self.center)
+    }
+
+    fn transform(&self, t: &Transform) -> Box<dyn Geometry> {
+        Box::new(self.transformed(t))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    start: Point,
+    end: Point,
+}
+
+impl Segment {
+    pub fn new(start: Point, end: Point) -> Self {
+        Segment { start, end }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.start.dist(self.end)
+    }
+
+    pub fn transformed(&self, t: &Transform) -> Segment {
+        Segment::new(t.apply(self.start), t.apply(self.end))
+    }
+}
+
+impl Geometry for Segment {
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::new(self.start, self.end))
+    }
+
+    fn area(&self) -> f64 {
+        0.0
+    }
+
+    fn perimeter(&self) -> f64 {
+        self.length()
+    }
+
+    fn centroid(&self) -> Option<Point> {
+        Some((self.start + self.end) * 0.5)
+    }
+
+    fn transform(&self, t: &Transform) -> Box<dyn Geometry> {
+        Box::new(self.transformed(t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_rectangle() {
+        let rect = Rectangle::new(Point::new(4.0, 3.0), Point::new(0.0, 0.0));
+        assert_eq!((rect.area(), rect.perimeter()), (12.0, 14.0));
+        assert_eq!(rect.centroid(), Some(Point::new(2.0, 1.5)));
+        assert_eq!(rect.to_polygon().area(), 12.0);
+
+        let rotated = rect.transformed(&Transform::rotate(PI / 2.0));
+        let b = rotated.bounding_box().unwrap();
+        assert!(close(b.width(), 3.0) && close(b.height(), 4.0));
+
+        // Scaling keeps it a rectangle; a shear does not.
+        let scaled = rect.transform(&Transform::scale(-2.0, 1.0).then(&Transform::translate(1.0, 1.0)));
+        assert_eq!(format!("{scaled:?}"), format!("{:?}", Rectangle::new(Point::new(-7.0, 1.0), Point::new(1.0, 4.0))));
+        let sheared = rect.transform(&Transform::shear(1.0, 0.0));
+        assert!(format!("{sheared:?}").starts_with("Polygon"), "{sheared:?}");
+        assert!(close(sheared.area(), 12.0));
+    }
+
+    #[test]
+    fn test_ellipse() {
+        let e = Ellipse::new(Point::new(1.0, 1.0), 2.0, 1.0, PI / 2.0);
+        assert!(close(e.area(), 2.0 * PI));
+        let b = e.bounding_box().unwrap();
+        assert!(close(b.width(), 2.0) && close(b.height(), 4.0));
+        assert!(e.as_circle().is_none());
+
+        let circle = Ellipse::new(Point::new(0.0, 0.0), 3.0, 3.0, 0.0);
+        assert!(close(circle.perimeter(), 6.0 * PI));
+    }
+
+    #[test]
+    fn test_ellipse_transformed() {
+        let e = Ellipse::new(Point::new(0.0, 0.0), 2.0, 1.0, 0.0);
+        let rotated = e.transformed(&Transform::rotate(PI / 2.0));
+        let b = rotated.bounding_box().unwrap();
+        assert!(close(b.width(), 2.0) && close(b.height(), 4.0), "{rotated:?}");
+
+        // A sheared ellipse keeps its area (shear has determinant 1)
+        // and its bounding box matches the sheared polygon approximation.
+        let shear = Transform::shear(1.0, 0.0);
+        let sheared = e.transformed(&shear);
+        assert!(close(sheared.area(), e.area()));
+
+        let outline: Vec<Point> = (0..3600)
+            .map(|i| {
+                let angle = i as f64 / 3600.0 * 2.0 * PI;
+                shear.apply(Point::new(2.0 * angle.cos(), angle.sin()))
+            })
+            .collect();
+        let expected = BoundingBox::from_points(outline).unwrap();
+        let actual = sheared.bounding_box().unwrap();
+        assert!((expected.width() - actual.width()).abs() < 1e-4, "{expected:?} {actual:?}");
+        assert!((expected.height() - actual.height()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_segment() {
+        let s = Segment::new(Point::new(0.0, 0.0), Point::new(3.0, 4.0));
+        assert_eq!(s.perimeter(), 5.0);
+        assert_eq!(s.centroid(), Some(Point::new(1.5, 2.0)));
+        let scaled = s.transformed(&Transform::scale(2.0, 2.0));
+        assert_eq!(scaled.length(), 10.0);
+    }
+}
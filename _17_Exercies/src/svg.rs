@@ -0,0 +1,172 @@
+// SVG rendering for a collection of shapes.
+// The viewBox is fitted to the shapes' bounds plus a margin, and the y axis is
+// flipped so shapes appear the way they would on a maths graph (y up).
+
+use std::fmt::Write;
+
+use crate::geometry::{BoundingBox, Geometry};
+use crate::{Point, Shape};
+
+pub struct SvgOptions {
+    pub width: u32,
+    pub height: u32,
+    pub stroke: String,
+    pub fill: String,
+    /// Extra space around the shapes, as a fraction of the larger side of their bounds.
+    pub margin: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            width: 400,
+            height: 400,
+            stroke: "black".to_string(),
+            fill: "none".to_string(),
+            margin: 0.05,
+        }
+    }
+}
+
+/// The bounds of every shape, or `None` if there is nothing to draw.
+fn bounds(shapes: &[Shape]) -> Option<BoundingBox> {
+    shapes.iter().filter_map(Geometry::bounding_box).reduce(|acc, b| acc.union(&b))
+}
+
+pub fn render_svg(shapes: &[Shape]) -> String {
+    render_svg_with(shapes, &SvgOptions::default())
+}
+
+pub fn render_svg_with(shapes: &[Shape], options: &SvgOptions) -> String {
+    let BoundingBox { min, max } =
+        bounds(shapes).unwrap_or(BoundingBox::new(Point::new(0.0, 0.0), Point::new(1.0, 1.0)));
+    let (min_x, min_y, max_x, max_y) = (min.x, min.y, max.x, max.y);
+    let size = (max_x - min_x).max(max_y - min_y).max(1.0);
+    let margin = size * options.margin;
+    // A single point or a line still gets a visible box, even without a
+    // margin: each side is at least 1 unit, centred on the shapes.
+    let (view_x, view_w) = at_least_one(min_x - margin, max_x - min_x + 2.0 * margin);
+    let (view_y, view_h) = at_least_one(-max_y - margin, max_y - min_y + 2.0 * margin);
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        options.width, options.height, view_x, view_y, view_w, view_h
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"  <g transform="scale(1 -1)" stroke="{}" fill="{}" stroke-width="1">"#,
+        escape(&options.stroke),
+        escape(&options.fill)
+    )
+    .unwrap();
+
+    for shape in shapes {
+        match shape {
+            Shape::Polygon(poly) if poly.points.is_empty() => {}
+            Shape::Polygon(poly) => {
+                let points: Vec<String> = poly.points.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+                writeln!(svg, r#"    <polygon points="{}" vector-effect="non-scaling-stroke"/>"#, points.join(" ")).unwrap();
+            }
+            Shape::Circle(circle) => {
+                writeln!(
+                    svg,
+                    r#"    <circle cx="{}" cy="{}" r="{}" vector-effect="non-scaling-stroke"/>"#,
+                    circle.center.x, circle.center.y, circle.radius
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    svg.push_str("  </g>\n</svg>\n");
+    svg
+}
+
+/// Grows `(start, length)` around its middle to a length of at least 1.
+fn at_least_one(start: f64, length: f64) -> (f64, f64) {
+    if length >= 1.0 {
+        (start, length)
+    } else {
+        (start - (1.0 - length) / 2.0, 1.0)
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Circle, Polygon};
+
+    fn triangle() -> Polygon {
+        let mut poly = Polygon::new();
+        for (x, y) in [(0.0, 0.0), (10.0, 0.0), (0.0, 20.0)] {
+            poly.add_point(Point::new(x, y));
+        }
+        poly
+    }
+
+    #[test]
+    fn test_view_box_fits_shapes() {
+        let shapes = vec![
+            Shape::Polygon(triangle()),
+            Shape::Circle(Circle::new(Point::new(20.0, 0.0), 5.0)),
+        ];
+        let svg = render_svg_with(&shapes, &SvgOptions { margin: 0.0, ..SvgOptions::default() });
+        // x spans 0..25 and y spans -5..20; the y axis is flipped.
+        assert!(svg.contains(r#"viewBox="0 -20 25 25""#), "{svg}");
+        assert!(svg.contains(r#"<polygon points="0,0 10,0 0,20" "#));
+        assert!(svg.contains(r#"<circle cx="20" cy="0" r="5" "#));
+    }
+
+    #[test]
+    fn test_margin() {
+        let svg = render_svg(&[Shape::Polygon(triangle())]);
+        assert!(svg.contains(r#"viewBox="-1 -21 12 22""#), "{svg}");
+    }
+
+    #[test]
+    fn test_empty_and_degenerate() {
+        let svg = render_svg(&[]);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(!svg.contains("<polygon"));
+
+        let dot = [Shape::Circle(Circle::new(Point::new(3.0, 3.0), 0.0))];
+        let svg = render_svg_with(&dot, &SvgOptions { margin: 0.5, ..SvgOptions::default() });
+        assert!(svg.contains(r#"viewBox="2.5 -3.5 1 1""#), "{svg}");
+
+        let no_margin = SvgOptions { margin: 0.0, ..SvgOptions::default() };
+        let svg = render_svg_with(&dot, &no_margin);
+        assert!(svg.contains(r#"viewBox="2.5 -3.5 1 1""#), "{svg}");
+
+        let mut line = Polygon::new();
+        line.add_point(Point::new(0.0, 2.0));
+        line.add_point(Point::new(4.0, 2.0));
+        let svg = render_svg_with(&[Shape::Polygon(line)], &no_margin);
+        assert!(svg.contains(r#"viewBox="0 -2.5 4 1""#), "{svg}");
+    }
+
+    #[test]
+    fn test_transformed_shapes() {
+        use crate::transform::Transform;
+
+        let t = Transform::scale(2.0, 1.0).then(&Transform::rotate(std::f64::consts::FRAC_PI_2));
+        let shapes = [Shape::Polygon(triangle().transformed(&t))];
+        let svg = render_svg_with(&shapes, &SvgOptions { margin: 0.0, ..SvgOptions::default() });
+        // The triangle now spans x -20..0 and y 0..20.
+        assert!(svg.contains(r#"viewBox="-20 -20 20 20""#), "{svg}");
+    }
+
+    #[test]
+    fn test_style_is_escaped() {
+        let options = SvgOptions { fill: r##"url("#a")"##.to_string(), ..SvgOptions::default() };
+        let svg = render_svg_with(&[], &options);
+        assert!(svg.contains(r##"fill="url(&quot;#a&quot;)""##));
+    }
+}
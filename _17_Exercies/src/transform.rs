@@ -0,0 +1,196 @@
+// 2D affine transformations.
+// Stored like an SVG `matrix(a b c d e f)`:
+//   x' = a * x + c * y + e
+//   y' = b * x + d * y + f
+
+use crate::{Circle, Point, Polygon};
+use crate::shapes::Ellipse;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+    f: f64,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 }
+    }
+
+    pub fn translate(dx: f64, dy: f64) -> Self {
+        Transform { e: dx, f: dy, ..Transform::identity() }
+    }
+
+    /// Counter-clockwise rotation about the origin, in radians.
+    pub fn rotate(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Transform { a: cos, b: sin, c: -sin, d: cos, e: 0.0, f: 0.0 }
+    }
+
+    pub fn rotate_about(angle: f64, center: Point) -> Self {
+        Transform::translate(-center.x, -center.y)
+            .then(&Transform::rotate(angle))
+            .then(&Transform::translate(center.x, center.y))
+    }
+
+    pub fn scale(sx: f64, sy: f64) -> Self {
+        Transform { a: sx, d: sy, ..Transform::identity() }
+    }
+
+    /// `x' = x + kx * y`, `y' = y + ky * x`.
+    pub fn shear(kx: f64, ky: f64) -> Self {
+        Transform { b: ky, c: kx, ..Transform::identity() }
+    }
+
+    /// Applies `self` first, then `next`.
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f,
+        }
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// `None` if the transform collapses the plane onto a line or a point.
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.determinant();
+        // Only an exact zero is singular: tiny scales like 1e-8 are still invertible.
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        Some(Transform {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det,
+        })
+    }
+
+    pub fn apply(&self, p: Point) -> Point {
+        Point::new(self.a * p.x + self.c * p.y + self.e, self.b * p.x + self.d * p.y + self.f)
+    }
+
+    /// Whether axis-aligned lines stay axis-aligned, as under scales,
+    /// translations and exact quarter turns.
+    pub fn keeps_axes(&self) -> bool {
+        (self.b == 0.0 && self.c == 0.0) || (self.a == 0.0 && self.d == 0.0)
+    }
+
+    /// Applies only the linear part, i.e. ignores the translation.
+    pub fn apply_vector(&self, v: Point) -> Point {
+        Point::new(self.a * v.x + self.c * v.y, self.b * v.x + self.d * v.y)
+    }
+}
+
+impl Point {
+    pub fn transformed(&self, t: &Transform) -> Point {
+        t.apply(*self)
+    }
+}
+
+impl Polygon {
+    pub fn transformed(&self, t: &Transform) -> Polygon {
+        Polygon { points: self.points.iter().map(|p| t.apply(*p)).collect() }
+    }
+}
+
+impl Circle {
+    /// Circles are ellipses with equal radii, and only stay that way under
+    /// rotations, translations and uniform scaling; `as_circle` tells which.
+    pub fn transformed(&self, t: &Transform) -> Ellipse {
+        Ellipse::new(self.center, self.radius, self.radius, 0.0).transformed(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Geometry;
+    use std::f64::consts::PI;
+
+    fn assert_close(a: Point, b: Point) {
+        assert!(a.dist(b) < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_basic_transforms() {
+        let p = Point::new(2.0, 1.0);
+        assert_eq!(Transform::identity().apply(p), p);
+        assert_eq!(Transform::translate(1.0, -1.0).apply(p), Point::new(3.0, 0.0));
+        assert_eq!(Transform::scale(2.0, 3.0).apply(p), Point::new(4.0, 3.0));
+        assert_eq!(Transform::shear(1.0, 0.0).apply(p), Point::new(3.0, 1.0));
+        assert_close(Transform::rotate(PI / 2.0).apply(p), Point::new(-1.0, 2.0));
+        assert_close(
+            Transform::rotate_about(PI, Point::new(1.0, 1.0)).apply(p),
+            Point::new(0.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn test_composition_order() {
+        let p = Point::new(1.0, 0.0);
+        let scale_then_move = Transform::scale(2.0, 2.0).then(&Transform::translate(1.0, 0.0));
+        let move_then_scale = Transform::translate(1.0, 0.0).then(&Transform::scale(2.0, 2.0));
+        assert_eq!(scale_then_move.apply(p), Point::new(3.0, 0.0));
+        assert_eq!(move_then_scale.apply(p), Point::new(4.0, 0.0));
+    }
+
+    #[test]
+    fn test_inverse() {
+        let t = Transform::rotate(0.3)
+            .then(&Transform::shear(0.5, 0.0))
+            .then(&Transform::scale(2.0, -1.0))
+            .then(&Transform::translate(5.0, 7.0));
+        let p = Point::new(-3.0, 4.5);
+        assert_close(t.inverse().unwrap().apply(t.apply(p)), p);
+        assert_eq!(Transform::scale(0.0, 1.0).inverse(), None);
+        assert_eq!(Transform::shear(2.0, 0.5).inverse(), None);
+        assert_eq!(Transform::scale(f64::INFINITY, 1.0).inverse(), None);
+
+        let tiny = Transform::scale(1e-8, 1e-8);
+        assert_close(tiny.inverse().unwrap().apply(Point::new(1e-8, 2e-8)), Point::new(1.0, 2.0));
+        let huge = Transform::scale(1e200, 1e200);
+        assert_eq!(huge.inverse(), None);
+    }
+
+    #[test]
+    fn test_polygon_transformed() {
+        let mut square = Polygon::new();
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            square.add_point(Point::new(x, y));
+        }
+        let t = Transform::scale(3.0, 2.0).then(&Transform::rotate(0.7));
+        let moved = square.transformed(&t);
+        assert!((moved.area() - 6.0).abs() < 1e-9);
+        assert!((moved.area() - square.area() * t.determinant().abs()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_circle_transformed() {
+        let circle = Circle::new(Point::new(1.0, 0.0), 2.0);
+        let similar = circle.transformed(&Transform::rotate(1.0).then(&Transform::scale(1.5, 1.5)));
+        let similar = similar.as_circle().unwrap();
+        assert!((similar.radius - 3.0).abs() < 1e-9);
+
+        let stretched = circle.transformed(&Transform::scale(2.0, 1.0));
+        assert!(stretched.as_circle().is_none(), "{stretched:?}");
+        assert!((stretched.area() - circle.area() * 2.0).abs() < 1e-9);
+        // Back to a circle through the inverse.
+        let inverse = Transform::scale(2.0, 1.0).inverse().unwrap();
+        let restored = stretched.transformed(&inverse).as_circle();
+        assert!(matches!(&restored, Some(c) if (c.radius - 2.0).abs() < 1e-9), "{restored:?}");
+    }
+}
@@ -0,0 +1,375 @@
+// User profile with validated setters.
+// Every successful change is recorded with its old and new value twice: on
+// the undo stack, which `undo` pops, and in the audit log, which only ever
+// grows. Undoing a change adds an `Undone` entry rather than erasing it.
+
+use std::fmt;
+
+pub const MAX_AGE: u32 = 150;
+pub const MAX_WEIGHT_KG: f64 = 700.0;
+const KG_PER_LB: f64 = 0.453_592_37;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightUnit {
+    Kg,
+    Lb,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weight {
+    value: f64,
+    unit: WeightUnit,
+}
+
+impl Weight {
+    pub fn kg(value: f64) -> Self {
+        Weight { value, unit: WeightUnit::Kg }
+    }
+
+    pub fn lb(value: f64) -> Self {
+        Weight { value, unit: WeightUnit::Lb }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn unit(&self) -> WeightUnit {
+        self.unit
+    }
+
+    pub fn in_kg(&self) -> f64 {
+        match self.unit {
+            WeightUnit::Kg => self.value,
+            WeightUnit::Lb => self.value * KG_PER_LB,
+        }
+    }
+
+    pub fn in_lb(&self) -> f64 {
+        match self.unit {
+            WeightUnit::Kg => self.value / KG_PER_LB,
+            WeightUnit::Lb => self.value,
+        }
+    }
+
+    pub fn to(&self, unit: WeightUnit) -> Weight {
+        match unit {
+            WeightUnit::Kg => Weight::kg(self.in_kg()),
+            WeightUnit::Lb => Weight::lb(self.in_lb()),
+        }
+    }
+}
+
+impl fmt::Display for Weight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let unit = match self.unit {
+            WeightUnit::Kg => "kg",
+            WeightUnit::Lb => "lb",
+        };
+        write!(f, "{} {unit}", self.value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UserError {
+    EmptyName,
+    AgeOutOfRange { age: u32 },
+    /// NaN, infinite, zero or negative.
+    InvalidWeight { weight: Weight },
+    WeightOutOfRange { weight: Weight },
+    NothingToUndo { requested: usize, available: usize },
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UserError::EmptyName => write!(f, "name must not be empty"),
+            UserError::AgeOutOfRange { age } => write!(f, "age {age} is over {MAX_AGE}"),
+            UserError::InvalidWeight { weight } => write!(f, "weight {weight} must be a positive number"),
+            UserError::WeightOutOfRange { weight } => {
+                write!(f, "weight {weight} is over {MAX_WEIGHT_KG} kg")
+            }
+            UserError::NothingToUndo { requested, available } => {
+                write!(f, "cannot undo {requested} changes, only {available} recorded")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UserError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Name { old: String, new: String },
+    Age { old: u32, new: u32 },
+    Weight { old: Weight, new: Weight },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Name { old, new } => write!(f, "name: {old} -> {new}"),
+            Change::Age { old, new } => write!(f, "age: {old} -> {new}"),
+            Change::Weight { old, new } => write!(f, "weight: {old} -> {new}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditEntry {
+    Changed(Change),
+    /// The change that was reverted, as it was originally made.
+    Undone(Change),
+}
+
+impl fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuditEntry::Changed(change) => write!(f, "changed {change}"),
+            AuditEntry::Undone(change) => write!(f, "undid {change}"),
+        }
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), UserError> {
+    if name.trim().is_empty() {
+        return Err(UserError::EmptyName);
+    }
+    Ok(())
+}
+
+fn validate_age(age: u32) -> Result<(), UserError> {
+    if age > MAX_AGE {
+        return Err(UserError::AgeOutOfRange { age });
+    }
+    Ok(())
+}
+
+fn validate_weight(weight: Weight) -> Result<(), UserError> {
+    let kg = weight.in_kg();
+    if !kg.is_finite() || kg <= 0.0 {
+        return Err(UserError::InvalidWeight { weight });
+    }
+    if kg > MAX_WEIGHT_KG {
+        return Err(UserError::WeightOutOfRange { weight });
+    }
+    Ok(())
+}
+
+// test for my question
+#[derive(Debug)]
+pub struct Person {
+    age: u32,
+}
+
+impl Person {
+    pub fn age(&self) -> u32 {
+        self.age
+    }
+}
+
+#[derive(Debug)]
+pub struct User {
+    name: String,
+    age: u32,
+    weight: Weight,
+    person: Person,
+    history: Vec<Change>,
+    audit_log: Vec<AuditEntry>,
+}
+
+impl User {
+    pub fn new(name: String, age: u32, weight: Weight) -> Result<Self, UserError> {
+        validate_name(&name)?;
+        validate_age(age)?;
+        validate_weight(weight)?;
+        Ok(User { name, age, weight, person: Person { age: 1 }, history: Vec::new(), audit_log: Vec::new() })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn age(&self) -> u32 {
+        self.age
+    }
+
+    pub fn weight(&self) -> Weight {
+        self.weight
+    }
+
+    // copy를 구현하지 않았기 때문에 borrowing만 가능.
+    // Only a shared borrow: handing out `&mut` would let callers skip the setters.
+    pub fn person(&self) -> &Person {
+        &self.person
+    }
+
+    pub fn set_name(&mut self, new_name: String) -> Result<(), UserError> {
+        validate_name(&new_name)?;
+        let old = std::mem::replace(&mut self.name, new_name.clone());
+        self.record(Change::Name { old, new: new_name });
+        Ok(())
+    }
+
+    pub fn set_age(&mut self, new_age: u32) -> Result<(), UserError> {
+        validate_age(new_age)?;
+        self.record(Change::Age { old: self.age, new: new_age });
+        self.age = new_age;
+        Ok(())
+    }
+
+    pub fn set_weight(&mut self, new_weight: Weight) -> Result<(), UserError> {
+        validate_weight(new_weight)?;
+        self.record(Change::Weight { old: self.weight, new: new_weight });
+        self.weight = new_weight;
+        Ok(())
+    }
+
+    fn record(&mut self, change: Change) {
+        self.audit_log.push(AuditEntry::Changed(change.clone()));
+        self.history.push(change);
+    }
+
+    /// The changes `undo` can still revert, oldest first.
+    pub fn history(&self) -> &[Change] {
+        &self.history
+    }
+
+    /// Every change and every undo, oldest first. Nothing is ever removed.
+    pub fn audit_log(&self) -> &[AuditEntry] {
+        &self.audit_log
+    }
+
+    /// Reverts the last `n` changes, newest first, and returns them.
+    /// Fails without changing anything if fewer than `n` changes were recorded.
+    pub fn undo(&mut self, n: usize) -> Result<Vec<Change>, UserError> {
+        if n > self.history.len() {
+            return Err(UserError::NothingToUndo { requested: n, available: self.history.len() });
+        }
+
+        let undone: Vec<Change> = self.history.drain(self.history.len() - n..).rev().collect();
+        for change in &undone {
+            match change {
+                Change::Name { old, .. } => self.name = old.clone(),
+                Change::Age { old, .. } => self.age = *old,
+                Change::Weight { old, .. } => self.weight = *old,
+            }
+            self.audit_log.push(AuditEntry::Undone(change.clone()));
+        }
+        Ok(undone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bob() -> User {
+        User::new(String::from("Bob"), 32, Weight::lb(155.2)).unwrap()
+    }
+
+    #[test]
+    fn test_weight() {
+        let bob = bob();
+        assert_eq!(bob.weight(), Weight::lb(155.2));
+    }
+
+    #[test]
+    fn test_set_age() {
+        let mut bob = bob();
+        assert_eq!(bob.age(), 32);
+        bob.set_age(33).unwrap();
+        assert_eq!(bob.age(), 33);
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        let mut bob = bob();
+        assert_eq!(bob.set_age(10_000), Err(UserError::AgeOutOfRange { age: 10_000 }));
+        assert_eq!(
+            bob.set_weight(Weight::kg(-3.0)),
+            Err(UserError::InvalidWeight { weight: Weight::kg(-3.0) })
+        );
+        assert!(matches!(bob.set_weight(Weight::kg(f64::NAN)), Err(UserError::InvalidWeight { .. })));
+        assert!(matches!(bob.set_weight(Weight::lb(2000.0)), Err(UserError::WeightOutOfRange { .. })));
+        assert_eq!(bob.set_name("  ".to_string()), Err(UserError::EmptyName));
+
+        // Nothing changed and nothing was recorded.
+        assert_eq!((bob.age(), bob.weight()), (32, Weight::lb(155.2)));
+        assert!(bob.history().is_empty());
+        assert!(bob.audit_log().is_empty());
+
+        assert!(User::new(String::from("Old"), 151, Weight::kg(60.0)).is_err());
+    }
+
+    #[test]
+    fn test_weight_units() {
+        let w = Weight::kg(100.0);
+        assert!((w.in_lb() - 220.462).abs() < 1e-3);
+        assert_eq!(w.to(WeightUnit::Kg), w);
+        let round_trip = w.to(WeightUnit::Lb).to(WeightUnit::Kg);
+        assert!((round_trip.value() - 100.0).abs() < 1e-9);
+        assert_eq!(round_trip.unit(), WeightUnit::Kg);
+        assert_eq!(Weight::lb(1.0).in_kg(), 0.453_592_37);
+        assert_eq!(Weight::lb(155.2).to_string(), "155.2 lb");
+    }
+
+    #[test]
+    fn test_history() {
+        let mut bob = bob();
+        bob.set_age(33).unwrap();
+        bob.set_weight(Weight::kg(70.0)).unwrap();
+        bob.set_name(String::from("Robert")).unwrap();
+
+        let log: Vec<String> = bob.history().iter().map(Change::to_string).collect();
+        assert_eq!(log, vec!["age: 32 -> 33", "weight: 155.2 lb -> 70 kg", "name: Bob -> Robert"]);
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut bob = bob();
+        bob.set_age(33).unwrap();
+        bob.set_age(34).unwrap();
+        bob.set_weight(Weight::kg(70.0)).unwrap();
+
+        let undone = bob.undo(2).unwrap();
+        assert_eq!(
+            undone,
+            vec![
+                Change::Weight { old: Weight::lb(155.2), new: Weight::kg(70.0) },
+                Change::Age { old: 33, new: 34 },
+            ]
+        );
+        assert_eq!((bob.age(), bob.weight()), (33, Weight::lb(155.2)));
+        assert_eq!(bob.history().len(), 1);
+
+        assert_eq!(bob.undo(2), Err(UserError::NothingToUndo { requested: 2, available: 1 }));
+        assert_eq!(bob.age(), 33);
+        bob.undo(1).unwrap();
+        assert_eq!(bob.age(), 32);
+        assert_eq!(bob.undo(0), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_audit_log_keeps_undone_changes() {
+        let mut bob = bob();
+        bob.set_age(33).unwrap();
+        bob.set_weight(Weight::kg(70.0)).unwrap();
+        bob.undo(2).unwrap();
+        bob.set_name(String::from("Robert")).unwrap();
+        assert!(bob.undo(2).is_err());
+
+        assert_eq!(bob.history(), [Change::Name { old: String::from("Bob"), new: String::from("Robert") }]);
+        let log: Vec<String> = bob.audit_log().iter().map(AuditEntry::to_string).collect();
+        assert_eq!(
+            log,
+            vec![
+                "changed age: 32 -> 33",
+                "changed weight: 155.2 lb -> 70 kg",
+                "undid weight: 155.2 lb -> 70 kg",
+                "undid age: 32 -> 33",
+                "changed name: Bob -> Robert",
+            ]
+        );
+    }
+}
@@ -0,0 +1,391 @@
+// Well-Known Text for points and shapes.
+// POINT and POLYGON follow the OGC spec (without holes); circles and ellipses
+// use `CIRCLE (x y, r)` and `ELLIPSE (x y, rx, ry, rotation)` extensions since
+// WKT has neither. The rotation is in radians.
+// Keywords are case-insensitive, and f64s are written with `{}` so they
+// parse back to exactly the same value.
+
+use std::fmt;
+
+use crate::shapes::Ellipse;
+use crate::{Circle, Point, Polygon, Shape};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WktError {
+    /// Byte offset into the input.
+    pub pos: usize,
+    pub message: String,
+}
+
+impl WktError {
+    fn new(pos: usize, message: impl Into<String>) -> Self {
+        WktError { pos, message: message.into() }
+    }
+}
+
+impl fmt::Display for WktError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.pos)
+    }
+}
+
+impl std::error::Error for WktError {}
+
+pub trait ToWkt {
+    fn to_wkt(&self) -> String;
+}
+
+pub trait FromWkt: Sized {
+    fn from_wkt(input: &str) -> Result<Self, WktError>;
+}
+
+impl ToWkt for Point {
+    fn to_wkt(&self) -> String {
+        format!("POINT ({} {})", self.x, self.y)
+    }
+}
+
+impl ToWkt for Polygon {
+    fn to_wkt(&self) -> String {
+        if self.points.is_empty() {
+            return "POLYGON EMPTY".to_string();
+        }
+        // WKT rings are closed: the first point is repeated at the end.
+        let ring: Vec<String> = self
+            .points
+            .iter()
+            .chain(self.points.first())
+            .map(|p| format!("{} {}", p.x, p.y))
+            .collect();
+        format!("POLYGON (({}))", ring.join(", "))
+    }
+}
+
+impl ToWkt for Circle {
+    fn to_wkt(&self) -> String {
+        format!("CIRCLE ({} {}, {})", self.center.x, self.center.y, self.radius)
+    }
+}
+
+impl ToWkt for Ellipse {
+    fn to_wkt(&self) -> String {
+        let c = self.center();
+        format!("ELLIPSE ({} {}, {}, {}, {})", c.x, c.y, self.radius_x(), self.radius_y(), self.rotation())
+    }
+}
+
+impl ToWkt for Shape {
+    fn to_wkt(&self) -> String {
+        match self {
+            Shape::Polygon(poly) => poly.to_wkt(),
+            Shape::Circle(circle) => circle.to_wkt(),
+        }
+    }
+}
+
+enum Parsed {
+    Point(Point),
+    Polygon(Polygon),
+    Circle(Circle),
+    Ellipse(Ellipse),
+}
+
+impl Parsed {
+    fn kind(&self) -> &'static str {
+        match self {
+            Parsed::Point(_) => "POINT",
+            Parsed::Polygon(_) => "POLYGON",
+            Parsed::Circle(_) => "CIRCLE",
+            Parsed::Ellipse(_) => "ELLIPSE",
+        }
+    }
+}
+
+/// `pos` is where the geometry type keyword starts.
+fn wrong_kind(expected: &str, parsed: Parsed, pos: usize) -> WktError {
+    WktError::new(pos, format!("expected {expected}, found {}", parsed.kind()))
+}
+
+impl FromWkt for Point {
+    fn from_wkt(input: &str) -> Result<Self, WktError> {
+        match parse(input)? {
+            (Parsed::Point(p), _) => Ok(p),
+            (other, pos) => Err(wrong_kind("POINT", other, pos)),
+        }
+    }
+}
+
+impl FromWkt for Polygon {
+    fn from_wkt(input: &str) -> Result<Self, WktError> {
+        match parse(input)? {
+            (Parsed::Polygon(poly), _) => Ok(poly),
+            (other, pos) => Err(wrong_kind("POLYGON", other, pos)),
+        }
+    }
+}
+
+impl FromWkt for Circle {
+    fn from_wkt(input: &str) -> Result<Self, WktError> {
+        match parse(input)? {
+            (Parsed::Circle(circle), _) => Ok(circle),
+            (other, pos) => Err(wrong_kind("CIRCLE", other, pos)),
+        }
+    }
+}
+
+impl FromWkt for Ellipse {
+    fn from_wkt(input: &str) -> Result<Self, WktError> {
+        match parse(input)? {
+            (Parsed::Ellipse(ellipse), _) => Ok(ellipse),
+            (other, pos) => Err(wrong_kind("ELLIPSE", other, pos)),
+        }
+    }
+}
+
+impl FromWkt for Shape {
+    fn from_wkt(input: &str) -> Result<Self, WktError> {
+        match parse(input)? {
+            (Parsed::Polygon(poly), _) => Ok(Shape::Polygon(poly)),
+            (Parsed::Circle(circle), _) => Ok(Shape::Circle(circle)),
+            (other, pos) => Err(wrong_kind("POLYGON or CIRCLE", other, pos)),
+        }
+    }
+}
+
+/// Also returns where the geometry type keyword starts.
+fn parse(input: &str) -> Result<(Parsed, usize), WktError> {
+    let mut parser = Parser { input, pos: 0 };
+    let keyword_pos = parser.skip_whitespace();
+    let keyword = parser.word();
+
+    let parsed = match keyword.to_ascii_uppercase().as_str() {
+        "POINT" => {
+            parser.expect('(')?;
+            let p = parser.point()?;
+            parser.expect(')')?;
+            Parsed::Point(p)
+        }
+        "POLYGON" => Parsed::Polygon(parser.polygon()?),
+        "CIRCLE" => {
+            parser.expect('(')?;
+            let center = parser.point()?;
+            parser.expect(',')?;
+            let radius = parser.radius()?;
+            parser.expect(')')?;
+            Parsed::Circle(Circle::new(center, radius))
+        }
+        "ELLIPSE" => {
+            parser.expect('(')?;
+            let center = parser.point()?;
+            parser.expect(',')?;
+            let radius_x = parser.radius()?;
+            parser.expect(',')?;
+            let radius_y = parser.radius()?;
+            parser.expect(',')?;
+            let rotation = parser.number()?;
+            parser.expect(')')?;
+            Parsed::Ellipse(Ellipse::new(center, radius_x, radius_y, rotation))
+        }
+        "" => return Err(WktError::new(keyword_pos, "expected a geometry type")),
+        _ => return Err(WktError::new(keyword_pos, format!("unknown geometry type `{keyword}`"))),
+    };
+
+    let end = parser.skip_whitespace();
+    if end < input.len() {
+        return Err(WktError::new(end, "unexpected trailing input"));
+    }
+    Ok((parsed, keyword_pos))
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) -> usize {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+        self.pos
+    }
+
+    fn word(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), WktError> {
+        match self.peek() {
+            Some(found) if found == c => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(found) => Err(WktError::new(self.pos, format!("expected `{c}`, found `{found}`"))),
+            None => Err(WktError::new(self.pos, format!("expected `{c}`, found end of input"))),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, WktError> {
+        let start = self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E')))
+            .unwrap_or(rest.len());
+        let text = &rest[..len];
+        if text.is_empty() {
+            return Err(WktError::new(start, "expected a number"));
+        }
+        let n: f64 = text
+            .parse()
+            .map_err(|_| WktError::new(start, format!("invalid number `{text}`")))?;
+        if !n.is_finite() {
+            return Err(WktError::new(start, format!("number `{text}` is out of range")));
+        }
+        self.pos += len;
+        Ok(n)
+    }
+
+    fn radius(&mut self) -> Result<f64, WktError> {
+        let radius_pos = self.skip_whitespace();
+        let radius = self.number()?;
+        if radius < 0.0 {
+            return Err(WktError::new(radius_pos, "radius must not be negative"));
+        }
+        Ok(radius)
+    }
+
+    fn point(&mut self) -> Result<Point, WktError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok(Point::new(x, y))
+    }
+
+    fn polygon(&mut self) -> Result<Polygon, WktError> {
+        let empty_pos = self.skip_whitespace();
+        if self.word().eq_ignore_ascii_case("EMPTY") {
+            return Ok(Polygon::new());
+        }
+        self.pos = empty_pos;
+
+        self.expect('(')?;
+        let ring_pos = self.skip_whitespace();
+        self.expect('(')?;
+        let mut points = vec![self.point()?];
+        while self.peek() == Some(',') {
+            self.pos += 1;
+            points.push(self.point()?);
+        }
+        self.expect(')')?;
+        if self.peek() == Some(',') {
+            return Err(WktError::new(self.pos, "polygons with holes are not supported"));
+        }
+        self.expect(')')?;
+
+        // The spec asks for at least 4 points, but degenerate rings are accepted
+        // so that every Polygon we write can be read back.
+        if points.len() < 2 {
+            return Err(WktError::new(ring_pos, "polygon ring is not closed"));
+        }
+        if points.first() != points.last() {
+            return Err(WktError::new(ring_pos, "polygon ring is not closed"));
+        }
+        points.pop();
+        Ok(Polygon { points })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Polygon {
+        let mut poly = Polygon::new();
+        for (x, y) in [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)] {
+            poly.add_point(Point::new(x, y));
+        }
+        poly
+    }
+
+    #[test]
+    fn test_write() {
+        assert_eq!(Point::new(1.5, -2.0).to_wkt(), "POINT (1.5 -2)");
+        assert_eq!(square().to_wkt(), "POLYGON ((0 0, 4 0, 4 4, 0 4, 0 0))");
+        assert_eq!(Polygon::new().to_wkt(), "POLYGON EMPTY");
+        assert_eq!(Circle::new(Point::new(1.0, 2.0), 3.0).to_wkt(), "CIRCLE (1 2, 3)");
+        assert_eq!(Ellipse::new(Point::new(1.0, 2.0), 3.0, 1.0, 0.5).to_wkt(), "ELLIPSE (1 2, 3, 1, 0.5)");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Point::from_wkt("  point(1e2   -0.5) "), Ok(Point::new(100.0, -0.5)));
+        assert_eq!(Polygon::from_wkt("POLYGON((0 0,4 0,4 4,0 4,0 0))"), Ok(square()));
+        assert_eq!(Polygon::from_wkt("Polygon Empty"), Ok(Polygon::new()));
+        assert_eq!(
+            Shape::from_wkt("CIRCLE (1 2, 3)"),
+            Ok(Shape::Circle(Circle::new(Point::new(1.0, 2.0), 3.0)))
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let shapes = vec![
+            Shape::Polygon(square()),
+            Shape::Polygon(Polygon::new()),
+            Shape::Polygon(Polygon { points: vec![Point::new(1.0, 1.0)] }),
+            Shape::Circle(Circle::new(Point::new(0.1, 1.0 / 3.0), 2.5e-7)),
+        ];
+        for shape in shapes {
+            assert_eq!(Shape::from_wkt(&shape.to_wkt()), Ok(shape));
+        }
+
+        let ellipse = Ellipse::new(Point::new(-1.0, 2.0), 3.0, 0.5, 0.1);
+        assert_eq!(Ellipse::from_wkt(&ellipse.to_wkt()), Ok(ellipse));
+
+        let p = Point::new(-123.456, f64::MAX);
+        assert_eq!(Point::from_wkt(&p.to_wkt()), Ok(p));
+    }
+
+    #[test]
+    fn test_errors_have_positions() {
+        let err = Point::from_wkt("POINT (1 x)").unwrap_err();
+        assert_eq!((err.pos, err.message.as_str()), (9, "expected a number"));
+
+        let err = Point::from_wkt("LINESTRING (1 2, 3 4)").unwrap_err();
+        assert_eq!(err.to_string(), "unknown geometry type `LINESTRING` at position 0");
+
+        let err = Polygon::from_wkt("POLYGON ((0 0, 1 0, 1 1, 0 0)").unwrap_err();
+        assert_eq!((err.pos, err.message.as_str()), (29, "expected `)`, found end of input"));
+
+        let err = Polygon::from_wkt("POLYGON ((0 0, 1 0, 1 1, 0 1))").unwrap_err();
+        assert_eq!((err.pos, err.message.as_str()), (9, "polygon ring is not closed"));
+
+        let err = Polygon::from_wkt("POLYGON ((0 0, 4 0, 4 4, 0 0), (1 1, 2 1, 2 2, 1 1))").unwrap_err();
+        assert_eq!((err.pos, err.message.as_str()), (29, "polygons with holes are not supported"));
+
+        let err = Circle::from_wkt("CIRCLE (0 0, -1)").unwrap_err();
+        assert_eq!((err.pos, err.message.as_str()), (13, "radius must not be negative"));
+
+        let err = Point::from_wkt("POINT (1 2) junk").unwrap_err();
+        assert_eq!(err.pos, 12);
+
+        let err = Shape::from_wkt("  POINT (1 2)").unwrap_err();
+        assert_eq!((err.pos, err.message.as_str()), (2, "expected POLYGON or CIRCLE, found POINT"));
+
+        let err = Ellipse::from_wkt("ELLIPSE (0 0, 2, -1, 0)").unwrap_err();
+        assert_eq!((err.pos, err.message.as_str()), (17, "radius must not be negative"));
+
+        let err = Circle::from_wkt("\n POLYGON EMPTY").unwrap_err();
+        assert_eq!((err.pos, err.message.as_str()), (2, "expected CIRCLE, found POLYGON"));
+    }
+}
@@ -0,0 +1,408 @@
+// Grapheme clusters: what a reader sees as one character.
+// "🇨🇭" is 8 bytes and 2 chars but one cluster; so are "e\u{301}" (é written
+// as e plus a combining accent), "👍🏽" and the ZWJ family "👨‍👩‍👧".
+//
+// This is a simplified version of the extended grapheme cluster rules of
+// Unicode UAX #29, using only std. It handles CR LF, controls, Hangul
+// syllables, combining marks and other extenders (variation selectors, skin
+// tones, tags), regional indicator pairs (flags) and emoji ZWJ sequences.
+// The character tables only cover the common ranges; prepend characters and
+// most spacing marks are not special-cased.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+const ZWJ: char = '\u{200D}';
+const EMOJI_PRESENTATION: char = '\u{FE0F}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Cr,
+    Lf,
+    Control,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Pictographic,
+    HangulL,
+    HangulV,
+    HangulT,
+    HangulLv,
+    HangulLvt,
+    Other,
+}
+
+fn in_ranges(c: char, ranges: &[(u32, u32)]) -> bool {
+    let c = c as u32;
+    ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi)
+}
+
+/// Combining marks, variation selectors, skin tone modifiers, tags and ZWNJ.
+const EXTEND: &[(u32, u32)] = &[
+    (0x0300, 0x036F), (0x0483, 0x0489), (0x0591, 0x05BD), (0x05BF, 0x05BF), (0x05C1, 0x05C2),
+    (0x05C4, 0x05C5), (0x05C7, 0x05C7), (0x0610, 0x061A), (0x064B, 0x065F), (0x0670, 0x0670),
+    (0x06D6, 0x06DC), (0x06DF, 0x06E4), (0x06E7, 0x06E8), (0x06EA, 0x06ED), (0x0900, 0x0903),
+    (0x093A, 0x093C), (0x093E, 0x094F), (0x0951, 0x0957), (0x0962, 0x0963), (0x0981, 0x0983),
+    (0x09BC, 0x09BC), (0x09BE, 0x09CD), (0x0E31, 0x0E31), (0x0E34, 0x0E3A), (0x0E47, 0x0E4E),
+    (0x1AB0, 0x1AFF), (0x1DC0, 0x1DFF), (0x200C, 0x200C), (0x20D0, 0x20FF), (0x302A, 0x302F),
+    (0x3099, 0x309A), (0xFE00, 0xFE0F), (0xFE20, 0xFE2F), (0x1F3FB, 0x1F3FF), (0xE0020, 0xE007F),
+    (0xE0100, 0xE01EF),
+];
+
+/// Roughly Extended_Pictographic: the emoji blocks and the older symbols that
+/// have emoji forms.
+const PICTOGRAPHIC: &[(u32, u32)] = &[
+    (0x00A9, 0x00A9), (0x00AE, 0x00AE), (0x203C, 0x203C), (0x2049, 0x2049), (0x2122, 0x2122),
+    (0x2139, 0x2139), (0x2194, 0x2199), (0x21A9, 0x21AA), (0x231A, 0x231B), (0x2328, 0x2328),
+    (0x23CF, 0x23CF), (0x23E9, 0x23F3), (0x23F8, 0x23FA), (0x24C2, 0x24C2), (0x25AA, 0x25AB),
+    (0x25B6, 0x25B6), (0x25C0, 0x25C0), (0x25FB, 0x25FE), (0x2600, 0x27BF), (0x2934, 0x2935),
+    (0x2B05, 0x2B07), (0x2B1B, 0x2B1C), (0x2B50, 0x2B50), (0x2B55, 0x2B55), (0x3030, 0x3030),
+    (0x303D, 0x303D), (0x3297, 0x3297), (0x3299, 0x3299), (0x1F000, 0x1F1E5), (0x1F200, 0x1F3FA),
+    (0x1F400, 0x1FAFF), (0x1FC00, 0x1FFFD),
+];
+
+/// East Asian Wide and Fullwidth, plus emoji that are shown two columns wide.
+const WIDE: &[(u32, u32)] = &[
+    (0x1100, 0x115F), (0x231A, 0x231B), (0x23E9, 0x23EC), (0x23F0, 0x23F0), (0x23F3, 0x23F3),
+    (0x25FD, 0x25FE), (0x2614, 0x2615), (0x2648, 0x2653), (0x267F, 0x267F), (0x2693, 0x2693),
+    (0x26A1, 0x26A1), (0x26AA, 0x26AB), (0x26BD, 0x26BE), (0x26C4, 0x26C5), (0x26D4, 0x26D4),
+    (0x26EA, 0x26EA), (0x26F2, 0x26F5), (0x26FA, 0x26FD), (0x2705, 0x2705), (0x270A, 0x270B),
+    (0x2728, 0x2728), (0x274C, 0x274C), (0x2753, 0x2755), (0x2757, 0x2757), (0x2795, 0x2797),
+    (0x2B1B, 0x2B1C), (0x2B50, 0x2B50), (0x2B55, 0x2B55), (0x2E80, 0x303E), (0x3041, 0x33FF),
+    (0x3400, 0x4DBF), (0x4E00, 0x9FFF), (0xA000, 0xA4CF), (0xA960, 0xA97F), (0xAC00, 0xD7A3),
+    (0xF900, 0xFAFF), (0xFE30, 0xFE4F), (0xFF00, 0xFF60), (0xFFE0, 0xFFE6), (0x1F1E6, 0x1F1FF),
+    (0x1F300, 0x1F64F), (0x1F680, 0x1F6FF), (0x1F900, 0x1F9FF), (0x1FA70, 0x1FAFF),
+    (0x20000, 0x3FFFD),
+];
+
+fn class(c: char) -> Class {
+    match c {
+        '\r' => Class::Cr,
+        '\n' => Class::Lf,
+        ZWJ => Class::Zwj,
+        '\u{1F1E6}'..='\u{1F1FF}' => Class::RegionalIndicator,
+        '\u{1100}'..='\u{115F}' | '\u{A960}'..='\u{A97C}' => Class::HangulL,
+        '\u{1160}'..='\u{11A7}' | '\u{D7B0}'..='\u{D7C6}' => Class::HangulV,
+        '\u{11A8}'..='\u{11FF}' | '\u{D7CB}'..='\u{D7FB}' => Class::HangulT,
+        '\u{AC00}'..='\u{D7A3}' if (c as u32 - 0xAC00).is_multiple_of(28) => Class::HangulLv,
+        '\u{AC00}'..='\u{D7A3}' => Class::HangulLvt,
+        '\u{2028}' | '\u{2029}' => Class::Control,
+        c if c.is_control() => Class::Control,
+        c if in_ranges(c, EXTEND) => Class::Extend,
+        c if in_ranges(c, PICTOGRAPHIC) => Class::Pictographic,
+        _ => Class::Other,
+    }
+}
+
+/// What the current cluster ends with, as far as the multi-character rules care.
+#[derive(Default)]
+struct State {
+    /// Number of regional indicators in a row at the end of the cluster.
+    regional_indicators: usize,
+    /// The cluster ends with a pictographic character followed only by extenders.
+    after_pictographic: bool,
+    /// ... and then a ZWJ, so another pictographic character joins.
+    pictographic_zwj: bool,
+}
+
+impl State {
+    fn push(&mut self, class: Class) {
+        self.regional_indicators = if class == Class::RegionalIndicator { self.regional_indicators + 1 } else { 0 };
+        self.pictographic_zwj = class == Class::Zwj && self.after_pictographic;
+        self.after_pictographic = match class {
+            Class::Pictographic => true,
+            Class::Extend => self.after_pictographic,
+            _ => false,
+        };
+    }
+}
+
+/// True if a cluster boundary falls between `prev` and `next`.
+fn is_boundary(prev: Class, next: Class, state: &State) -> bool {
+    use Class::*;
+    match (prev, next) {
+        (Cr, Lf) => false,
+        (Cr | Lf | Control, _) | (_, Cr | Lf | Control) => true,
+        (HangulL, HangulL | HangulV | HangulLv | HangulLvt) => false,
+        (HangulLv | HangulV, HangulV | HangulT) => false,
+        (HangulLvt | HangulT, HangulT) => false,
+        (_, Extend | Zwj) => false,
+        (Zwj, Pictographic) => !state.pictographic_zwj,
+        (RegionalIndicator, RegionalIndicator) => state.regional_indicators.is_multiple_of(2),
+        _ => true,
+    }
+}
+
+/// Iterator over the grapheme clusters of a string, see `graphemes`.
+#[derive(Debug, Clone)]
+pub struct Graphemes<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let mut chars = self.rest.char_indices();
+        let (_, first) = chars.next()?;
+        let mut prev = class(first);
+        let mut state = State::default();
+        state.push(prev);
+
+        let mut end = self.rest.len();
+        for (i, c) in chars {
+            let next = class(c);
+            if is_boundary(prev, next, &state) {
+                end = i;
+                break;
+            }
+            state.push(next);
+            prev = next;
+        }
+
+        let (cluster, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(cluster)
+    }
+}
+
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { rest: s }
+}
+
+pub fn grapheme_count(s: &str) -> usize {
+    graphemes(s).count()
+}
+
+/// Terminal columns taken by one cluster: 0 for controls, 2 for wide CJK
+/// characters, flags and emoji, 1 otherwise.
+pub fn cluster_width(cluster: &str) -> usize {
+    let Some(first) = cluster.chars().next() else {
+        return 0;
+    };
+    if first.is_control() {
+        0
+    } else if in_ranges(first, WIDE) || cluster.contains(EMOJI_PRESENTATION) {
+        2
+    } else {
+        1
+    }
+}
+
+pub fn display_width(s: &str) -> usize {
+    graphemes(s).map(cluster_width).sum()
+}
+
+/// The longest prefix of whole clusters that is at most `max_width` columns wide.
+pub fn truncate(s: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    let mut end = 0;
+    for cluster in graphemes(s) {
+        width += cluster_width(cluster);
+        if width > max_width {
+            break;
+        }
+        end += cluster.len();
+    }
+    &s[..end]
+}
+
+/// Like `truncate`, but marks a cut with `ellipsis`, which counts towards the width.
+/// Strings that already fit are returned unchanged.
+pub fn truncate_with<'a>(s: &'a str, max_width: usize, ellipsis: &str) -> Cow<'a, str> {
+    if display_width(s) <= max_width {
+        return Cow::Borrowed(s);
+    }
+    let room = max_width.saturating_sub(display_width(ellipsis));
+    Cow::Owned(format!("{}{ellipsis}", truncate(s, room)))
+}
+
+/// Reverses the order of clusters; each cluster keeps its own char order, so
+/// accents stay on their letters and flags stay flags.
+pub fn reverse(s: &str) -> String {
+    let clusters: Vec<&str> = graphemes(s).collect();
+    clusters.into_iter().rev().collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+    /// Extra space goes to the right when it cannot be split evenly.
+    Center,
+}
+
+/// Pads with spaces up to `width` columns. Wider strings are returned unchanged.
+pub fn pad(s: &str, width: usize, align: Align) -> String {
+    let missing = width.saturating_sub(display_width(s));
+    let (left, right) = match align {
+        Align::Left => (0, missing),
+        Align::Right => (missing, 0),
+        Align::Center => (missing / 2, missing - missing / 2),
+    };
+    format!("{}{s}{}", " ".repeat(left), " ".repeat(right))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphemeError {
+    /// The range ends after the last cluster; `len` is the number of clusters.
+    OutOfRange { end: usize, len: usize },
+    StartAfterEnd { start: usize, end: usize },
+}
+
+impl fmt::Display for GraphemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphemeError::OutOfRange { end, len } => {
+                write!(f, "grapheme range ends at {end} but the string has {len} graphemes")
+            }
+            GraphemeError::StartAfterEnd { start, end } => {
+                write!(f, "grapheme range starts at {start} but ends at {end}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphemeError {}
+
+/// Substring from cluster `range.start` up to (not including) `range.end`.
+/// Unlike `s[0..4]` this never panics and never splits a character or cluster.
+pub fn slice(s: &str, range: impl RangeBounds<usize>) -> Result<&str, GraphemeError> {
+    let start = match range.start_bound() {
+        Bound::Included(&i) => i,
+        // Saturating, so `usize::MAX` ends up out of range instead of overflowing.
+        Bound::Excluded(&i) => i.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    // Byte offset of the start of every cluster, plus the end of the string.
+    let mut offsets: Vec<usize> = graphemes(s).scan(0, |pos, g| Some(std::mem::replace(pos, *pos + g.len()))).collect();
+    offsets.push(s.len());
+    let len = offsets.len() - 1;
+
+    let end = match range.end_bound() {
+        Bound::Included(&i) => i.saturating_add(1),
+        Bound::Excluded(&i) => i,
+        Bound::Unbounded => len,
+    };
+    if end > len {
+        return Err(GraphemeError::OutOfRange { end, len });
+    }
+    if start > end {
+        return Err(GraphemeError::StartAfterEnd { start, end });
+    }
+    Ok(&s[offsets[start]..offsets[end]])
+}
+
+/// The cluster at `index`, or `None` past the end.
+pub fn grapheme_at(s: &str, index: usize) -> Option<&str> {
+    graphemes(s).nth(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(s: &str) -> Vec<&str> {
+        graphemes(s).collect()
+    }
+
+    #[test]
+    fn test_flags() {
+        let swiss = "🇨🇭";
+        assert_eq!((swiss.len(), swiss.chars().count(), grapheme_count(swiss)), (8, 2, 1));
+        assert_eq!(split("🇨🇭🇩🇪"), vec!["🇨🇭", "🇩🇪"]);
+        // An odd regional indicator stands alone.
+        assert_eq!(split("🇨🇭🇩x"), vec!["🇨🇭", "🇩", "x"]);
+    }
+
+    #[test]
+    fn test_combining_marks_and_modifiers() {
+        assert_eq!(split("e\u{301}a"), vec!["e\u{301}", "a"]);
+        assert_eq!(split("a\u{308}\u{304}"), vec!["a\u{308}\u{304}"]);
+        assert_eq!(split("👍🏽!"), vec!["👍🏽", "!"]);
+        assert_eq!(split("❤\u{FE0F}x"), vec!["❤\u{FE0F}", "x"]);
+        assert_eq!(split("1\u{FE0F}\u{20E3}"), vec!["1\u{FE0F}\u{20E3}"]);
+        assert_eq!(split("नमस्ते"), vec!["न", "म", "स्", "ते"]);
+    }
+
+    #[test]
+    fn test_zwj_sequences() {
+        let family = "👨\u{200D}👩\u{200D}👧";
+        assert_eq!(split(family), vec![family]);
+        let woman_technologist_dark = "👩🏿\u{200D}💻";
+        assert_eq!(split(woman_technologist_dark), vec![woman_technologist_dark]);
+        // A ZWJ only joins pictographs.
+        assert_eq!(split("a\u{200D}b"), vec!["a\u{200D}", "b"]);
+    }
+
+    #[test]
+    fn test_line_breaks_controls_and_hangul() {
+        assert_eq!(split("a\r\nb\n\r"), vec!["a", "\r\n", "b", "\n", "\r"]);
+        assert_eq!(split("\u{301}\t\u{301}"), vec!["\u{301}", "\t", "\u{301}"]);
+        // Conjoining jamo and precomposed syllables.
+        assert_eq!(split("\u{1100}\u{1161}\u{11A8}한글"), vec!["\u{1100}\u{1161}\u{11A8}", "한", "글"]);
+        assert_eq!(split(""), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("🇨🇭"), 2);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("❤\u{FE0F}"), 2);
+        assert_eq!(display_width("a\tb"), 2);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("日本語", 5), "日本");
+        assert_eq!(truncate("🇨🇭🇩🇪", 3), "🇨🇭");
+        assert_eq!(truncate("ae\u{301}", 2), "ae\u{301}");
+        assert_eq!(truncate("abc", 0), "");
+        assert_eq!(truncate_with("Grüezi mitenand", 8, "…"), "Grüezi …");
+        assert_eq!(truncate_with("short", 8, "…"), "short");
+        assert_eq!(truncate_with("日本語", 4, "..."), "...");
+    }
+
+    #[test]
+    fn test_reverse() {
+        assert_eq!(reverse("abc"), "cba");
+        assert_eq!(reverse("e\u{301}x🇨🇭"), "🇨🇭xe\u{301}");
+        // Reversing chars would turn the flag into 🇭🇨 and move the accent.
+        assert_ne!("🇨🇭".chars().rev().collect::<String>(), reverse("🇨🇭"));
+    }
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad("日本", 6, Align::Left), "日本  ");
+        assert_eq!(pad("🇨🇭", 5, Align::Right), "   🇨🇭");
+        assert_eq!(pad("ab", 5, Align::Center), " ab  ");
+        assert_eq!(pad("too wide", 3, Align::Center), "too wide");
+    }
+
+    #[test]
+    fn test_slice() {
+        let s = "🇨🇭e\u{301}日x";
+        assert_eq!(slice(s, 0..1), Ok("🇨🇭"));
+        assert_eq!(slice(s, 1..=2), Ok("e\u{301}日"));
+        assert_eq!(slice(s, 2..), Ok("日x"));
+        assert_eq!(slice(s, ..), Ok(s));
+        assert_eq!(slice(s, 4..4), Ok(""));
+        assert_eq!(slice(s, 0..5), Err(GraphemeError::OutOfRange { end: 5, len: 4 }));
+        assert_eq!(slice(s, 5..), Err(GraphemeError::StartAfterEnd { start: 5, end: 4 }));
+        #[allow(clippy::reversed_empty_ranges)]
+        let backwards = slice(s, 3..1);
+        assert_eq!(backwards, Err(GraphemeError::StartAfterEnd { start: 3, end: 1 }));
+        assert_eq!(grapheme_at(s, 2), Some("日"));
+        assert_eq!(grapheme_at(s, 4), None);
+
+        assert_eq!(slice(s, ..=usize::MAX), Err(GraphemeError::OutOfRange { end: usize::MAX, len: 4 }));
+        assert_eq!(
+            slice(s, (Bound::Excluded(usize::MAX), Bound::Unbounded)),
+            Err(GraphemeError::StartAfterEnd { start: usize::MAX, end: 4 })
+        );
+        assert_eq!(slice(s, (Bound::Excluded(1), Bound::Included(2))), Ok("日"));
+    }
+}
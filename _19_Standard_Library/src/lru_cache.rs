@@ -0,0 +1,467 @@
+// Bounded cache that evicts the least recently used entry.
+// A HashMap finds the entry, and a doubly linked list threaded through the
+// entries themselves keeps the recency order. The links are indices into a
+// slot vector instead of pointers, so get, put and evict are all O(1)
+// without any unsafe code.
+//
+// The capacity is a total cost. By default every entry costs 1, i.e. the
+// capacity is a number of entries; `with_weigher` lets the cost depend on
+// the entry, e.g. the length of a cached string.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    cost: usize,
+    /// Towards the most recently used end.
+    prev: Option<usize>,
+    /// Towards the least recently used end.
+    next: Option<usize>,
+}
+
+type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize>;
+type EvictCallback<K, V> = Box<dyn FnMut(K, V)>;
+
+pub struct LruCache<K, V> {
+    map: HashMap<K, usize>,
+    slots: Vec<Option<Entry<K, V>>>,
+    free: Vec<usize>,
+    /// Most recently used.
+    head: Option<usize>,
+    /// Least recently used, evicted first.
+    tail: Option<usize>,
+    capacity: usize,
+    cost: usize,
+    weigher: Weigher<K, V>,
+    on_evict: Option<EvictCallback<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Holds at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        LruCache::with_weigher(capacity, |_, _| 1)
+    }
+
+    /// Holds entries as long as their total `weigher` cost is at most `max_cost`.
+    /// The weigher must give the same cost every time for the same entry.
+    pub fn with_weigher(max_cost: usize, weigher: impl Fn(&K, &V) -> usize + 'static) -> Self {
+        LruCache {
+            map: HashMap::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            head: None,
+            tail: None,
+            capacity: max_cost,
+            cost: 0,
+            weigher: Box::new(weigher),
+            on_evict: None,
+        }
+    }
+
+    /// Called with every entry pushed out to make room. Not called for `remove`,
+    /// `clear` or when `put` replaces the value of an existing key.
+    pub fn set_on_evict(&mut self, callback: impl FnMut(K, V) + 'static) {
+        self.on_evict = Some(Box::new(callback));
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Total cost of the entries currently held.
+    pub fn cost(&self) -> usize {
+        self.cost
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Marks the entry as most recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        self.move_to_front(index);
+        Some(&self.entry(index).value)
+    }
+
+    /// Marks the entry as most recently used. The cost is not recomputed, so
+    /// changes through the returned reference must not change the weight.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = *self.map.get(key)?;
+        self.move_to_front(index);
+        Some(&mut self.entry_mut(index).value)
+    }
+
+    /// Looks at the entry without changing the recency order.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&index| &self.entry(index).value)
+    }
+
+    /// Inserts or replaces the entry and marks it as most recently used, then
+    /// evicts least recently used entries until the cost fits the capacity.
+    /// Returns the value previously stored under `key`.
+    ///
+    /// An entry that costs more than the whole capacity is evicted right away.
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        let old = self.remove(&key);
+        let cost = (self.weigher)(&key, &value);
+        if cost > self.capacity {
+            self.evicted(key, value);
+            return old;
+        }
+
+        let entry = Entry { key: key.clone(), value, cost, prev: None, next: None };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index] = Some(entry);
+                index
+            }
+            None => {
+                self.slots.push(Some(entry));
+                self.slots.len() - 1
+            }
+        };
+        self.map.insert(key, index);
+        self.cost += cost;
+        self.push_front(index);
+
+        while self.cost > self.capacity {
+            let (key, value) = self.pop_lru().expect("cost is only positive with entries");
+            self.evicted(key, value);
+        }
+        old
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.map.remove(key)?;
+        Some(self.take(index).1)
+    }
+
+    /// Removes the least recently used entry without calling the eviction callback.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let index = self.tail?;
+        let (key, value) = self.take(index);
+        self.map.remove(&key);
+        Some((key, value))
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.slots.clear();
+        self.free.clear();
+        self.head = None;
+        self.tail = None;
+        self.cost = 0;
+    }
+
+    /// From most to least recently used.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { cache: self, next: self.head }
+    }
+
+    fn evicted(&mut self, key: K, value: V) {
+        if let Some(callback) = &mut self.on_evict {
+            callback(key, value);
+        }
+    }
+
+    fn entry(&self, index: usize) -> &Entry<K, V> {
+        self.slots[index].as_ref().expect("linked slot is occupied")
+    }
+
+    fn entry_mut(&mut self, index: usize) -> &mut Entry<K, V> {
+        self.slots[index].as_mut().expect("linked slot is occupied")
+    }
+
+    /// Unlinks the entry, frees its slot and returns its key and value.
+    /// The caller removes it from the map.
+    fn take(&mut self, index: usize) -> (K, V) {
+        self.unlink(index);
+        let entry = self.slots[index].take().expect("linked slot is occupied");
+        self.free.push(index);
+        self.cost -= entry.cost;
+        (entry.key, entry.value)
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let (prev, next) = {
+            let entry = self.entry(index);
+            (entry.prev, entry.next)
+        };
+        match prev {
+            Some(prev) => self.entry_mut(prev).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.entry_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    fn push_front(&mut self, index: usize) {
+        let old_head = self.head;
+        {
+            let entry = self.entry_mut(index);
+            entry.prev = None;
+            entry.next = old_head;
+        }
+        match old_head {
+            Some(head) => self.entry_mut(head).prev = Some(index),
+            None => self.tail = Some(index),
+        }
+        self.head = Some(index);
+    }
+
+    fn move_to_front(&mut self, index: usize) {
+        if self.head != Some(index) {
+            self.unlink(index);
+            self.push_front(index);
+        }
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    cache: &'a LruCache<K, V>,
+    next: Option<usize>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let entry = self.cache.slots[self.next?].as_ref()?;
+        self.next = entry.next;
+        Some((&entry.key, &entry.value))
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries = f.debug_map();
+        let mut next = self.head;
+        while let Some(index) = next {
+            let entry = self.slots[index].as_ref().expect("linked slot is occupied");
+            entries.entry(&entry.key, &entry.value);
+            next = entry.next;
+        }
+        entries.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Not `super::*`: the `Borrow` trait would make `RefCell::borrow` ambiguous.
+    use super::LruCache;
+    use std::cell::RefCell;
+    use std::hash::Hash;
+    use std::rc::Rc;
+
+    fn keys(cache: &LruCache<u32, u32>) -> Vec<u32> {
+        cache.iter().map(|(k, _)| *k).collect()
+    }
+
+    /// Returns a shared log that records every eviction of `cache`.
+    fn record_evictions<K: Hash + Eq + Clone + 'static, V: 'static>(cache: &mut LruCache<K, V>) -> Rc<RefCell<Vec<(K, V)>>> {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&log);
+        cache.set_on_evict(move |k, v| sink.borrow_mut().push((k, v)));
+        log
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        let evicted = record_evictions(&mut cache);
+        cache.put(1, 10);
+        cache.put(2, 20);
+        assert_eq!(cache.get(&1), Some(&10));
+        cache.put(3, 30);
+
+        assert_eq!(*evicted.borrow(), vec![(2, 20)]);
+        assert_eq!(keys(&cache), vec![3, 1]);
+        assert!(!cache.contains(&2));
+    }
+
+    #[test]
+    fn test_put_replaces_without_eviction() {
+        let mut cache = LruCache::new(2);
+        let evicted = record_evictions(&mut cache);
+        cache.put(1, 10);
+        cache.put(2, 20);
+        assert_eq!(cache.put(1, 11), Some(10));
+        assert_eq!(keys(&cache), vec![1, 2]);
+        assert!(evicted.borrow().is_empty());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_peek_and_get_mut() {
+        let mut cache = LruCache::new(3);
+        for k in 1..=3 {
+            cache.put(k, k * 10);
+        }
+        assert_eq!(cache.peek(&1), Some(&10));
+        assert_eq!(keys(&cache), vec![3, 2, 1]);
+        *cache.get_mut(&1).unwrap() += 1;
+        assert_eq!(keys(&cache), vec![1, 3, 2]);
+        assert_eq!(cache.peek(&1), Some(&11));
+        assert_eq!(cache.remove(&3), Some(30));
+        assert_eq!(cache.pop_lru(), Some((2, 20)));
+        assert_eq!(keys(&cache), vec![1]);
+    }
+
+    #[test]
+    fn test_weighted_capacity() {
+        let mut cache: LruCache<&str, String> = LruCache::with_weigher(10, |_, v: &String| v.len());
+        let evicted = record_evictions(&mut cache);
+        cache.put("a", "aaaa".to_string());
+        cache.put("b", "bbbb".to_string());
+        assert_eq!(cache.cost(), 8);
+        cache.put("c", "ccc".to_string());
+        // Needs 11, so the oldest entry goes.
+        assert_eq!(cache.cost(), 7);
+        assert_eq!(evicted.borrow().len(), 1);
+        assert_eq!(evicted.borrow()[0].0, "a");
+
+        // Too heavy to ever fit: rejected and reported as evicted, the rest stays.
+        cache.put("huge", "x".repeat(11));
+        assert_eq!(evicted.borrow().last().unwrap().0, "huge");
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b").map(String::as_str), Some("bbbb"));
+    }
+
+    #[test]
+    fn test_clear_and_zero_capacity() {
+        let mut cache = LruCache::new(0);
+        cache.put(1, 1);
+        assert!(cache.is_empty());
+
+        let mut cache = LruCache::new(5);
+        cache.put(1, 1);
+        cache.clear();
+        assert_eq!((cache.len(), cache.cost()), (0, 0));
+        cache.put(2, 2);
+        assert_eq!(keys(&cache), vec![2]);
+    }
+
+    /// Same semantics, implemented the obvious O(n) way.
+    struct Model {
+        /// Most recently used first: (key, value, cost).
+        entries: Vec<(u32, u32, usize)>,
+        capacity: usize,
+        evicted: Vec<(u32, u32)>,
+    }
+
+    impl Model {
+        fn cost(&self) -> usize {
+            self.entries.iter().map(|e| e.2).sum()
+        }
+
+        fn get(&mut self, key: u32) -> Option<u32> {
+            let pos = self.entries.iter().position(|e| e.0 == key)?;
+            let entry = self.entries.remove(pos);
+            self.entries.insert(0, entry);
+            Some(entry.1)
+        }
+
+        fn remove(&mut self, key: u32) -> Option<u32> {
+            let pos = self.entries.iter().position(|e| e.0 == key)?;
+            Some(self.entries.remove(pos).1)
+        }
+
+        fn put(&mut self, key: u32, value: u32, cost: usize) -> Option<u32> {
+            let old = self.remove(key);
+            if cost > self.capacity {
+                self.evicted.push((key, value));
+                return old;
+            }
+            self.entries.insert(0, (key, value, cost));
+            while self.cost() > self.capacity {
+                let (k, v, _) = self.entries.pop().unwrap();
+                self.evicted.push((k, v));
+            }
+            old
+        }
+    }
+
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: u32) -> u32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % u64::from(n)) as u32
+        }
+    }
+
+    fn stress(capacity: usize, weigh: fn(u32) -> usize, seed: u64) {
+        let mut cache = LruCache::with_weigher(capacity, move |_, v: &u32| weigh(*v));
+        let evicted = record_evictions(&mut cache);
+        let mut model = Model { entries: Vec::new(), capacity, evicted: Vec::new() };
+        let mut rng = Rng(seed);
+
+        for step in 0..20_000 {
+            let key = rng.below(64);
+            match rng.below(10) {
+                0..=3 => assert_eq!(cache.get(&key).copied(), model.get(key), "step {step}"),
+                4 => assert_eq!(cache.remove(&key), model.remove(key), "step {step}"),
+                5 => assert_eq!(cache.pop_lru().map(|e| e.1), model.entries.pop().map(|e| e.1)),
+                _ => {
+                    let value = rng.below(1000);
+                    assert_eq!(cache.put(key, value), model.put(key, value, weigh(value)), "step {step}");
+                }
+            }
+            let order: Vec<(u32, u32)> = cache.iter().map(|(k, v)| (*k, *v)).collect();
+            let expected: Vec<(u32, u32)> = model.entries.iter().map(|e| (e.0, e.1)).collect();
+            assert_eq!(order, expected, "step {step}");
+            assert_eq!(cache.cost(), model.cost());
+            assert_eq!(*evicted.borrow(), model.evicted);
+        }
+        // The slot vector never grows past the most entries held at once.
+        assert!(cache.slots.len() <= 64);
+    }
+
+    #[test]
+    fn test_stress_against_model_by_entries() {
+        stress(16, |_| 1, 0x1234_5678);
+    }
+
+    #[test]
+    fn test_stress_against_model_weighted() {
+        stress(3000, |v| v as usize, 0x9e37_79b9);
+        stress(50, |v| (v % 20) as usize, 42);
+    }
+}
@@ -1,3 +1,9 @@
+mod graphemes;
+mod lru_cache;
+mod page_index;
+mod persistent_list;
+mod tree;
+
 use std::collections::HashMap;
 use std::rc::{Rc, Weak};
 use std::cell::RefCell;
@@ -60,6 +66,21 @@ fn main() {
 
     let s3 = String::from("🇨🇭");
     println!("s3: len = {}, number of chars = {}", s3.len(), s3.chars().count());
+    // ... but only one grapheme cluster, which is what a reader counts (see graphemes.rs).
+    println!("s3: graphemes = {}, width = {}", graphemes::grapheme_count(&s3), graphemes::display_width(&s3));
+    let greeting = "Grüezi 🇨🇭 e\u{301}";
+    println!("reversed: {}", graphemes::reverse(greeting));
+    println!("[{}] [{}]", graphemes::pad(&s3, 6, graphemes::Align::Center), graphemes::truncate_with(greeting, 9, "…"));
+    println!(
+        "[{}] [{}] grapheme 7: {:?}",
+        graphemes::pad("e\u{301}", 3, graphemes::Align::Left),
+        graphemes::pad("e\u{301}", 3, graphemes::Align::Right),
+        graphemes::grapheme_at(greeting, 7),
+    );
+    match graphemes::slice(&s3, 0..4) {
+        Ok(part) => println!("s3 graphemes 0..4: {part}"),
+        Err(e) => println!("s3 graphemes 0..4: {e}"),
+    }
 
     // String::new returns a new empty string, use String::with_capacity when you know how much data you want to push to the string.
     // String::len returns the size of the String in bytes (which can be different from its length in characters).
@@ -224,6 +245,61 @@ fn main() {
 
     println!("{page_counts:#?}");
 
+    // The same data in a PageIndex (see page_index.rs): case-insensitive titles,
+    // merging, statistics and a text format to save it in.
+    let mut index: page_index::PageIndex = page_counts
+        .iter()
+        .map(|(title, pages)| (title.as_str(), u32::try_from(*pages).unwrap_or(0)))
+        .collect();
+    index.merge(
+        [("pride", 10), ("Les Misérables", 1463)].into_iter().collect(),
+        page_index::MergePolicy::Sum,
+    );
+    index.merge([("Alice", 1)].into_iter().collect(), page_index::MergePolicy::Max);
+    index.merge([("grimm's ", 1)].into_iter().collect(), page_index::MergePolicy::KeepLeft);
+    if index.contains("alice") {
+        println!("removed Alice ({:?} pages)", index.remove("Alice"));
+    }
+    let titles: Vec<&str> = index.iter().map(|(title, _)| title).collect();
+    println!("titles: {titles:?}");
+    println!(
+        "{} books, {} pages, average {:?}, median {:?}, PRIDE has {:?} pages",
+        index.len(),
+        index.total_pages(),
+        index.average_pages(),
+        index.median_pages(),
+        index.get("PRIDE"),
+    );
+    println!("two longest: {:?}", index.top_n(2));
+    let mut saved = Vec::new();
+    index.save(&mut saved).expect("writing to a Vec cannot fail");
+    let loaded = page_index::PageIndex::load(saved.as_slice()).expect("just saved");
+    println!("saved:\n{}reloaded equal: {}", String::from_utf8_lossy(&saved), loaded == index);
+
+    // HashMap plus a recency list gives a bounded cache (see lru_cache.rs).
+    let mut cache = lru_cache::LruCache::new(2);
+    cache.set_on_evict(|title: String, pages: u32| println!("evicted {title} ({pages} pages)"));
+    for (title, pages) in index.top_n(3) {
+        cache.put(title.to_string(), pages);
+    }
+    if let Some(pages) = cache.get("Les Misérables") {
+        println!("cached: Les Misérables has {pages} pages");
+    }
+    if let Some(pages) = cache.get_mut("Les Misérables") {
+        *pages += 1;
+    }
+    let recent: Vec<(&String, &u32)> = cache.iter().collect();
+    println!("most recent first: {recent:?}, peek Pride: {:?}", cache.peek("Pride"));
+    println!(
+        "{} of {} cached (cost {}), has Pride: {}",
+        cache.len(),
+        cache.capacity(),
+        cache.cost(),
+        cache.contains("Pride")
+    );
+    cache.clear();
+    println!("cleared: {}", cache.is_empty());
+
 
 
 
@@ -277,6 +353,14 @@ fn main() {
     let list: List<i32> = List::Cons(1, Box::new(List::Cons(2, Box::new(List::Nil))));
     println!("{list:?}");
 
+    // With Rc instead of Box, several lists can share one tail (see persistent_list.rs).
+    let shared: persistent_list::List<i32> = [2, 3].into_iter().collect();
+    let one = shared.cons(1);
+    let ten = shared.cons(10);
+    println!("{one} and {ten} share {shared}, reversed: {}", one.reverse());
+    println!("doubled evens: {}", one.filter(|x| x % 2 == 0).map(|x| x * 2));
+    println!("head {:?}, tail {:?}, len {}", ten.head(), ten.tail(), ten.len());
+
     //  If the Box was not used here and we attempted to embed a List directly into the List, the compiler would not compute a fixed size of the struct in memory, it would look infinite.
 
     // Box solves this problem as it has the same size as a regular pointer and just points at the next element of the List in the heap.
@@ -371,4 +455,24 @@ fn main() {
     }));
     root.borrow_mut().children.push(child);
     println!("graph: {root:#?}");
+
+    // The same structure behind an API that keeps the parent links consistent (see tree.rs).
+    let root = tree::Tree::new(42);
+    let child = root.push_child(43);
+    let grandchild = child.push_child(45);
+    let sibling = root.push_child(44);
+    println!("{root}");
+    grandchild.reparent(&sibling).expect("44 is not below 45");
+    println!("after reparent:\n{root}");
+    println!("depth of 45: {}, path: {:?}", grandchild.depth(), grandchild.path_to_root());
+    println!("root of 45 is 42: {}", grandchild.root().ptr_eq(&root));
+    let post: Vec<i64> = root.post_order().map(|n| *n.value()).collect();
+    let level: Vec<i64> = root.level_order().map(|n| *n.value()).collect();
+    println!("post-order {post:?}, level-order {level:?}, first odd value {:?}", root.find(|v| v % 2 == 1));
+    if let Err(e) = root.reparent(&grandchild) {
+        println!("cannot reparent the root: {e}");
+    }
+    root.remove_child(&child).expect("43 is a child of the root");
+    *child.value_mut() += 100;
+    println!("detached {child:?}, is root: {}, tree now has {} nodes", child.is_root(), root.pre_order().count());
 }
@@ -0,0 +1,371 @@
+// Reusable version of the 19.4 `page_counts` map.
+// Titles are looked up through `TitleKey`, so "pride", "Pride" and " PRIDE "
+// are the same book; the title as first inserted is kept for display.
+//
+// Text format, one book per line, `#` comments and blank lines ignored:
+//
+//     # page-index v1
+//     207	Adventures of Huckleberry Finn
+//     751	Grimm's Fairy Tales
+//
+// Page count, a tab, then the title with `\`, tab and newline escaped.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+/// Normalized title: lowercase, surrounding whitespace removed and inner runs
+/// of whitespace collapsed to a single space.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TitleKey(String);
+
+impl TitleKey {
+    pub fn new(title: &str) -> Self {
+        TitleKey(title.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase())
+    }
+}
+
+impl From<&str> for TitleKey {
+    fn from(title: &str) -> Self {
+        TitleKey::new(title)
+    }
+}
+
+/// What `merge` does with a title that is in both indexes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    Max,
+    Sum,
+    KeepLeft,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    title: String,
+    pages: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageIndex {
+    books: HashMap<TitleKey, Entry>,
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    /// `line` starts at 1.
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "read failed: {e}"),
+            LoadError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+impl PageIndex {
+    pub fn new() -> Self {
+        PageIndex::default()
+    }
+
+    /// Sets the page count and returns the previous one. The stored title is
+    /// only replaced if the book was not known yet.
+    pub fn insert(&mut self, title: &str, pages: u32) -> Option<u32> {
+        match self.books.get_mut(&TitleKey::new(title)) {
+            Some(entry) => Some(std::mem::replace(&mut entry.pages, pages)),
+            None => {
+                self.books.insert(TitleKey::new(title), Entry { title: title.trim().to_string(), pages });
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, title: &str) -> Option<u32> {
+        self.books.get(&TitleKey::new(title)).map(|entry| entry.pages)
+    }
+
+    pub fn contains(&self, title: &str) -> bool {
+        self.books.contains_key(&TitleKey::new(title))
+    }
+
+    pub fn remove(&mut self, title: &str) -> Option<u32> {
+        self.books.remove(&TitleKey::new(title)).map(|entry| entry.pages)
+    }
+
+    pub fn len(&self) -> usize {
+        self.books.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty()
+    }
+
+    /// `(title, pages)` in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.books.values().map(|entry| (entry.title.as_str(), entry.pages))
+    }
+
+    /// Adds every book of `other`. Titles present in both are resolved by `policy`;
+    /// the title spelling from `self` is kept.
+    pub fn merge(&mut self, other: PageIndex, policy: MergePolicy) {
+        for (key, theirs) in other.books {
+            match self.books.get_mut(&key) {
+                Some(ours) => {
+                    ours.pages = match policy {
+                        MergePolicy::Max => ours.pages.max(theirs.pages),
+                        MergePolicy::Sum => ours.pages.saturating_add(theirs.pages),
+                        MergePolicy::KeepLeft => ours.pages,
+                    }
+                }
+                None => {
+                    self.books.insert(key, theirs);
+                }
+            }
+        }
+    }
+
+    pub fn total_pages(&self) -> u64 {
+        self.books.values().map(|entry| u64::from(entry.pages)).sum()
+    }
+
+    /// `None` for an empty index.
+    pub fn average_pages(&self) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.total_pages() as f64 / self.len() as f64)
+    }
+
+    /// Mean of the two middle values for an even number of books. `None` for an empty index.
+    pub fn median_pages(&self) -> Option<f64> {
+        let mut pages: Vec<u32> = self.books.values().map(|entry| entry.pages).collect();
+        pages.sort_unstable();
+        let mid = pages.len() / 2;
+        match pages.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(f64::from(pages[mid])),
+            _ => Some((f64::from(pages[mid - 1]) + f64::from(pages[mid])) / 2.0),
+        }
+    }
+
+    /// The `n` longest books, longest first. Ties are ordered by title.
+    pub fn top_n(&self, n: usize) -> Vec<(&str, u32)> {
+        let mut books: Vec<&Entry> = self.books.values().collect();
+        books.sort_by(|a, b| b.pages.cmp(&a.pages).then_with(|| a.title.cmp(&b.title)));
+        books.into_iter().take(n).map(|entry| (entry.title.as_str(), entry.pages)).collect()
+    }
+
+    /// Writes the index in the text format described at the top of this file,
+    /// sorted by title so the output is stable.
+    pub fn save(&self, mut out: impl Write) -> io::Result<()> {
+        let mut entries: Vec<(&TitleKey, &Entry)> = self.books.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        writeln!(out, "# page-index v1")?;
+        for (_, entry) in entries {
+            writeln!(out, "{}\t{}", entry.pages, escape(&entry.title))?;
+        }
+        Ok(())
+    }
+
+    pub fn load(input: impl BufRead) -> Result<PageIndex, LoadError> {
+        let mut index = PageIndex::new();
+        for (i, line) in input.lines().enumerate() {
+            let line = line?;
+            let parse_error = |message: String| LoadError::Parse { line: i + 1, message };
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (pages, title) = line
+                .split_once('\t')
+                .ok_or_else(|| parse_error("expected `<pages><TAB><title>`".to_string()))?;
+            let pages: u32 = pages
+                .trim()
+                .parse()
+                .map_err(|_| parse_error(format!("invalid page count {pages:?}")))?;
+            // An empty title is accepted, since `insert` accepts one too and
+            // everything `save` writes has to load back.
+            let title = unescape(title).map_err(parse_error)?;
+            if index.insert(&title, pages).is_some() {
+                return Err(parse_error(format!("duplicate title {title:?}")));
+            }
+        }
+        Ok(index)
+    }
+}
+
+impl<S: AsRef<str>> Extend<(S, u32)> for PageIndex {
+    /// Later pairs overwrite earlier ones with the same title.
+    fn extend<I: IntoIterator<Item = (S, u32)>>(&mut self, iter: I) {
+        for (title, pages) in iter {
+            self.insert(title.as_ref(), pages);
+        }
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<(S, u32)> for PageIndex {
+    fn from_iter<I: IntoIterator<Item = (S, u32)>>(iter: I) -> Self {
+        let mut index = PageIndex::new();
+        index.extend(iter);
+        index
+    }
+}
+
+fn escape(title: &str) -> String {
+    let mut out = String::with_capacity(title.len());
+    for c in title.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(title: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(title.len());
+    let mut chars = title.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => out.push('\\'),
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(other) => return Err(format!("unknown escape \\{other}")),
+            None => return Err("title ends with a lone backslash".to_string()),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library() -> PageIndex {
+        [("Adventures of Huckleberry Finn", 207), ("Grimm's Fairy Tales", 751), ("Pride and Prejudice", 303)]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_case_insensitive_lookup() {
+        let mut index = library();
+        assert_eq!(index.get("pride and prejudice"), Some(303));
+        assert_eq!(index.get("  PRIDE   and\tPrejudice "), Some(303));
+        assert!(!index.contains("Les Misérables"));
+
+        assert_eq!(index.insert("PRIDE AND PREJUDICE", 304), Some(303));
+        assert_eq!(index.len(), 3);
+        // The first spelling is kept.
+        assert!(index.iter().any(|(title, pages)| title == "Pride and Prejudice" && pages == 304));
+        assert_eq!(TitleKey::from(" A  b "), TitleKey::new("a B"));
+    }
+
+    #[test]
+    fn test_merge_policies() {
+        let other: PageIndex = [("grimm's fairy tales", 800), ("Alice in Wonderland", 96)].into_iter().collect();
+
+        let mut max = library();
+        max.merge(other.clone(), MergePolicy::Max);
+        assert_eq!(max.get("Grimm's Fairy Tales"), Some(800));
+        assert_eq!(max.get("alice in wonderland"), Some(96));
+        assert_eq!(max.len(), 4);
+
+        let mut sum = library();
+        sum.merge(other.clone(), MergePolicy::Sum);
+        assert_eq!(sum.get("Grimm's Fairy Tales"), Some(1551));
+
+        let mut left = library();
+        left.merge(other, MergePolicy::KeepLeft);
+        assert_eq!(left.get("Grimm's Fairy Tales"), Some(751));
+        assert!(left.iter().any(|(title, _)| title == "Grimm's Fairy Tales"));
+        assert_eq!(left.get("Alice in Wonderland"), Some(96));
+    }
+
+    #[test]
+    fn test_statistics() {
+        let index = library();
+        assert_eq!(index.total_pages(), 1261);
+        assert!((index.average_pages().unwrap() - 420.333).abs() < 1e-3);
+        assert_eq!(index.median_pages(), Some(303.0));
+
+        let mut even = index.clone();
+        even.insert("Alice", 97);
+        assert_eq!(even.median_pages(), Some(255.0));
+
+        let empty = PageIndex::new();
+        assert_eq!((empty.average_pages(), empty.median_pages()), (None, None));
+    }
+
+    #[test]
+    fn test_top_n() {
+        let mut index = library();
+        index.insert("Another 303", 303);
+        assert_eq!(
+            index.top_n(3),
+            vec![("Grimm's Fairy Tales", 751), ("Another 303", 303), ("Pride and Prejudice", 303)]
+        );
+        assert_eq!(index.top_n(10).len(), 4);
+        assert!(index.top_n(0).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let mut index = library();
+        index.insert("Tabs\tand\nnewlines \\ too", 1);
+
+        let mut saved = Vec::new();
+        index.save(&mut saved).unwrap();
+        let text = String::from_utf8(saved.clone()).unwrap();
+        assert!(text.starts_with("# page-index v1\n207\tAdventures of Huckleberry Finn\n"));
+        assert!(text.contains("1\tTabs\\tand\\nnewlines \\\\ too\n"));
+
+        assert_eq!(PageIndex::load(saved.as_slice()).unwrap(), index);
+    }
+
+    #[test]
+    fn test_round_trip_with_blank_title() {
+        let mut index = library();
+        index.insert(" \t ", 5);
+        assert_eq!(index.get(""), Some(5));
+
+        let mut saved = Vec::new();
+        index.save(&mut saved).unwrap();
+        let loaded = PageIndex::load(saved.as_slice()).unwrap();
+        assert_eq!(loaded, index);
+        assert_eq!(PageIndex::load("1\t  ".as_bytes()).unwrap().get(" "), Some(1));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let error = |text: &str| match PageIndex::load(text.as_bytes()) {
+            Err(LoadError::Parse { line, message }) => (line, message),
+            other => panic!("expected a parse error, got {other:?}"),
+        };
+
+        assert_eq!(error("\n# comment\n12 Dune\n"), (3, "expected `<pages><TAB><title>`".to_string()));
+        assert_eq!(error("-1\tDune").1, "invalid page count \"-1\"");
+        assert_eq!(error("1\tDune\n2\tDUNE").0, 2);
+        assert_eq!(error("1\tbad \\x").1, "unknown escape \\x");
+    }
+}
@@ -0,0 +1,296 @@
+// Persistent (immutable) cons list.
+// Same shape as the 19.5.1 `List<T>`, but with Rc instead of Box so that
+// lists can share their tails: `cons` never copies, it just points at the
+// existing list. Nothing is ever mutated after it is built.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+struct Node<T> {
+    elem: T,
+    next: Option<Rc<Node<T>>>,
+}
+
+pub struct List<T> {
+    head: Option<Rc<Node<T>>>,
+    len: usize,
+}
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None, len: 0 }
+    }
+
+    /// A new list with `elem` in front of this one. Both lists share the tail.
+    pub fn cons(&self, elem: T) -> List<T> {
+        List {
+            head: Some(Rc::new(Node { elem, next: self.head.clone() })),
+            len: self.len + 1,
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.elem)
+    }
+
+    /// Everything but the first element, shared with this list. `None` if empty.
+    pub fn tail(&self) -> Option<List<T>> {
+        self.head.as_ref().map(|node| List { head: node.next.clone(), len: self.len - 1 })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: self.head.as_deref() }
+    }
+
+    /// True if both lists start at the same node, i.e. one was cloned from the other.
+    pub fn ptr_eq(&self, other: &List<T>) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> List<U> {
+        self.iter().map(f).collect()
+    }
+}
+
+impl<T: Clone> List<T> {
+    pub fn reverse(&self) -> List<T> {
+        self.iter().fold(List::new(), |acc, elem| acc.cons(elem.clone()))
+    }
+
+    /// Keeps the elements where `pred` is true. The longest suffix where every
+    /// element is kept is shared with this list instead of being copied.
+    pub fn filter(&self, mut pred: impl FnMut(&T) -> bool) -> List<T> {
+        let mut kept = Vec::new();
+        let mut shared_from: Option<&Rc<Node<T>>> = None;
+        let mut shared_len = 0;
+
+        let mut cursor = self.head.as_ref();
+        while let Some(node) = cursor {
+            if pred(&node.elem) {
+                if shared_from.is_none() {
+                    shared_from = Some(node);
+                }
+                shared_len += 1;
+            } else {
+                // Everything since the last rejected element has to be copied after all.
+                let mut copy = shared_from.take().map(|rc| &**rc);
+                while let Some(n) = copy {
+                    if std::ptr::eq(n, &**node) {
+                        break;
+                    }
+                    kept.push(n.elem.clone());
+                    copy = n.next.as_deref();
+                }
+                shared_len = 0;
+            }
+            cursor = node.next.as_ref();
+        }
+
+        let tail = List { head: shared_from.cloned(), len: shared_len };
+        kept.into_iter().rev().fold(tail, |acc, elem| acc.cons(elem))
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        List::new()
+    }
+}
+
+/// Cheap: only bumps the reference count of the first node.
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List { head: self.head.clone(), len: self.len }
+    }
+}
+
+/// The default drop would recurse once per node and overflow the stack on long
+/// lists. Instead, unlink nodes one at a time until we reach one that is still
+/// shared with another list.
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut cursor = self.head.take();
+        while let Some(node) = cursor {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => cursor = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.elem
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a List<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Keeps the iterator's order: the first item becomes the head.
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        items.into_iter().rev().fold(List::new(), |acc, elem| acc.cons(elem))
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{elem}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Counts how many times it has been dropped.
+    #[derive(Clone)]
+    struct DropCounter(Rc<Cell<usize>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_cons_head_tail() {
+        let empty: List<i32> = List::new();
+        assert_eq!(empty.head(), None);
+        assert!(empty.tail().is_none());
+
+        let list = empty.cons(3).cons(2).cons(1);
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.tail().unwrap().head(), Some(&2));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        // The original is untouched.
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_sharing() {
+        let tail: List<i32> = (1..=3).collect();
+        let a = tail.cons(10);
+        let b = tail.cons(20);
+
+        assert!(a.tail().unwrap().ptr_eq(&tail));
+        assert!(b.tail().unwrap().ptr_eq(&tail));
+        // `tail` plus the nodes of `a` and `b` point at its first node.
+        let first = tail.head.as_ref().unwrap();
+        assert_eq!(Rc::strong_count(first), 3);
+        drop(a);
+        assert_eq!(Rc::strong_count(first), 2);
+        assert_eq!(b, [20, 1, 2, 3].into_iter().collect());
+    }
+
+    #[test]
+    fn test_reverse_map_filter() {
+        let list: List<i32> = (1..=6).collect();
+        assert_eq!(list.reverse(), (1..=6).rev().collect());
+        assert_eq!(list.map(|x| x * 10), [10, 20, 30, 40, 50, 60].into_iter().collect());
+        assert_eq!(list.filter(|x| x % 2 == 0), [2, 4, 6].into_iter().collect());
+        assert_eq!(list.filter(|_| false), List::new());
+        assert_eq!(list.filter(|x| *x > 0).len(), 6);
+    }
+
+    #[test]
+    fn test_filter_shares_longest_kept_suffix() {
+        let list: List<i32> = [1, 2, 3, 4, 5].into_iter().collect();
+        let filtered = list.filter(|x| *x != 2);
+        assert_eq!(filtered, [1, 3, 4, 5].into_iter().collect());
+        // [3, 4, 5] is reused as is.
+        let shared = list.tail().unwrap().tail().unwrap();
+        assert!(filtered.tail().unwrap().ptr_eq(&shared));
+
+        // Keeping everything shares the whole list.
+        assert!(list.filter(|_| true).ptr_eq(&list));
+    }
+
+    #[test]
+    fn test_display_and_debug() {
+        let list: List<&str> = ["a", "b"].into_iter().collect();
+        assert_eq!(list.to_string(), "(a b)");
+        assert_eq!(format!("{list:?}"), r#"["a", "b"]"#);
+        assert_eq!(List::<i32>::new().to_string(), "()");
+    }
+
+    #[test]
+    fn test_drop_frees_each_element_once() {
+        let drops = Rc::new(Cell::new(0));
+        let counter = || DropCounter(Rc::clone(&drops));
+
+        let tail = List::new().cons(counter()).cons(counter());
+        let a = tail.cons(counter());
+        let b = tail.cons(counter());
+        assert_eq!(drops.get(), 0);
+
+        drop(tail);
+        drop(a);
+        // Only a's own head is gone; the shared tail lives on in b.
+        assert_eq!(drops.get(), 1);
+        drop(b);
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn test_long_list_drop_does_not_overflow() {
+        let list: List<u32> = (0..1_000_000).collect();
+        assert_eq!(list.len(), 1_000_000);
+        let shared = list.tail().unwrap();
+        drop(list);
+        assert_eq!(shared.head(), Some(&1));
+        drop(shared);
+    }
+}
@@ -0,0 +1,385 @@
+// Tree built from the 19.6 `Node`: children are owned through `Rc`, the parent
+// is only a `Weak` link, so there are no reference cycles and dropping the root
+// frees the whole tree.
+//
+// A `Tree<T>` is a handle to one node. Cloning the handle does not copy the
+// subtree, it points at the same node (like `Rc::clone`).
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+struct Node<T> {
+    value: T,
+    parent: Option<Weak<RefCell<Node<T>>>>,
+    children: Vec<Rc<RefCell<Node<T>>>>,
+}
+
+pub struct Tree<T>(Rc<RefCell<Node<T>>>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeError {
+    /// The new child is the parent itself or one of its ancestors.
+    Cycle,
+    NotAChild,
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TreeError::Cycle => write!(f, "a node cannot become a child of itself or of its descendants"),
+            TreeError::NotAChild => write!(f, "node is not a child of this node"),
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+impl<T> Tree<T> {
+    pub fn new(value: T) -> Self {
+        Tree(Rc::new(RefCell::new(Node { value, parent: None, children: Vec::new() })))
+    }
+
+    pub fn value(&self) -> Ref<'_, T> {
+        Ref::map(self.0.borrow(), |node| &node.value)
+    }
+
+    pub fn value_mut(&self) -> RefMut<'_, T> {
+        RefMut::map(self.0.borrow_mut(), |node| &mut node.value)
+    }
+
+    /// `None` for the root.
+    pub fn parent(&self) -> Option<Tree<T>> {
+        self.0.borrow().parent.as_ref().and_then(Weak::upgrade).map(Tree)
+    }
+
+    pub fn children(&self) -> Vec<Tree<T>> {
+        self.0.borrow().children.iter().cloned().map(Tree).collect()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.parent().is_none()
+    }
+
+    /// True if both handles point at the same node.
+    pub fn ptr_eq(&self, other: &Tree<T>) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Creates a new node holding `value` as the last child of this one.
+    pub fn push_child(&self, value: T) -> Tree<T> {
+        let child = Tree::new(value);
+        self.attach(&child);
+        child
+    }
+
+    /// Makes `child` the last child of this node, detaching it from its
+    /// previous parent first. The child keeps its own subtree.
+    pub fn add_child(&self, child: &Tree<T>) -> Result<(), TreeError> {
+        if self.path_to_root().iter().any(|ancestor| ancestor.ptr_eq(child)) {
+            return Err(TreeError::Cycle);
+        }
+        child.detach();
+        self.attach(child);
+        Ok(())
+    }
+
+    /// Detaches `child` from this node. The child becomes the root of its own tree
+    /// and stays alive as long as there are handles to it.
+    pub fn remove_child(&self, child: &Tree<T>) -> Result<(), TreeError> {
+        match child.parent() {
+            Some(parent) if parent.ptr_eq(self) => {
+                child.detach();
+                Ok(())
+            }
+            _ => Err(TreeError::NotAChild),
+        }
+    }
+
+    /// Moves this node (with its subtree) under `new_parent`.
+    pub fn reparent(&self, new_parent: &Tree<T>) -> Result<(), TreeError> {
+        new_parent.add_child(self)
+    }
+
+    /// Removes this node from its parent's children. Does nothing for a root.
+    pub fn detach(&self) {
+        if let Some(parent) = self.parent() {
+            parent.0.borrow_mut().children.retain(|c| !Rc::ptr_eq(c, &self.0));
+        }
+        self.0.borrow_mut().parent = None;
+    }
+
+    fn attach(&self, child: &Tree<T>) {
+        child.0.borrow_mut().parent = Some(Rc::downgrade(&self.0));
+        self.0.borrow_mut().children.push(Rc::clone(&child.0));
+    }
+
+    /// This node, its parent, its grandparent and so on up to the root.
+    pub fn path_to_root(&self) -> Vec<Tree<T>> {
+        let mut path = vec![self.clone()];
+        while let Some(parent) = path.last().unwrap().parent() {
+            path.push(parent);
+        }
+        path
+    }
+
+    pub fn root(&self) -> Tree<T> {
+        self.path_to_root().pop().unwrap()
+    }
+
+    /// Number of edges between this node and the root, so the root has depth 0.
+    pub fn depth(&self) -> usize {
+        self.path_to_root().len() - 1
+    }
+
+    /// Parent before children, children left to right.
+    pub fn pre_order(&self) -> PreOrder<T> {
+        PreOrder { stack: vec![self.clone()] }
+    }
+
+    /// Children before parent, children left to right.
+    pub fn post_order(&self) -> PostOrder<T> {
+        PostOrder { stack: vec![(self.clone(), false)] }
+    }
+
+    /// Breadth first: all nodes at depth 1, then all at depth 2, ...
+    pub fn level_order(&self) -> LevelOrder<T> {
+        LevelOrder { queue: VecDeque::from([self.clone()]) }
+    }
+
+    /// First node in pre-order whose value matches `pred`.
+    pub fn find(&self, mut pred: impl FnMut(&T) -> bool) -> Option<Tree<T>> {
+        self.pre_order().find(|node| pred(&node.value()))
+    }
+}
+
+impl<T: fmt::Display> Tree<T> {
+    /// Draws the subtree like `tree --charset=ascii`:
+    ///
+    /// ```text
+    /// 1
+    /// |-- 2
+    /// |   `-- 4
+    /// `-- 3
+    /// ```
+    pub fn render(&self) -> String {
+        let mut out = format!("{}\n", self.value());
+        self.render_children("", &mut out);
+        out
+    }
+
+    fn render_children(&self, prefix: &str, out: &mut String) {
+        let children = self.children();
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let (branch, indent) = if last { ("`-- ", "    ") } else { ("|-- ", "|   ") };
+            out.push_str(&format!("{prefix}{branch}{}\n", child.value()));
+            child.render_children(&format!("{prefix}{indent}"), out);
+        }
+    }
+}
+
+impl<T> Clone for Tree<T> {
+    fn clone(&self) -> Self {
+        Tree(Rc::clone(&self.0))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Tree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Tree").field(&*self.value()).finish()
+    }
+}
+
+/// `render` without the final newline, so `println!("{tree}")` adds no blank line.
+impl<T: fmt::Display> fmt::Display for Tree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let out = self.render();
+        f.write_str(out.strip_suffix('\n').unwrap_or(&out))
+    }
+}
+
+pub struct PreOrder<T> {
+    stack: Vec<Tree<T>>,
+}
+
+impl<T> Iterator for PreOrder<T> {
+    type Item = Tree<T>;
+
+    fn next(&mut self) -> Option<Tree<T>> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children().into_iter().rev());
+        Some(node)
+    }
+}
+
+pub struct PostOrder<T> {
+    /// The flag is set once the node's children have been pushed.
+    stack: Vec<(Tree<T>, bool)>,
+}
+
+impl<T> Iterator for PostOrder<T> {
+    type Item = Tree<T>;
+
+    fn next(&mut self) -> Option<Tree<T>> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(node);
+            }
+            let children = node.children();
+            self.stack.push((node, true));
+            self.stack.extend(children.into_iter().rev().map(|child| (child, false)));
+        }
+    }
+}
+
+pub struct LevelOrder<T> {
+    queue: VecDeque<Tree<T>>,
+}
+
+impl<T> Iterator for LevelOrder<T> {
+    type Item = Tree<T>;
+
+    fn next(&mut self) -> Option<Tree<T>> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.children());
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1
+    /// |-- 2
+    /// |   |-- 4
+    /// |   `-- 5
+    /// `-- 3
+    ///     `-- 6
+    fn sample() -> (Tree<i64>, Vec<Tree<i64>>) {
+        let root = Tree::new(1);
+        let two = root.push_child(2);
+        let three = root.push_child(3);
+        let four = two.push_child(4);
+        let five = two.push_child(5);
+        let six = three.push_child(6);
+        let nodes = vec![root.clone(), two, three, four, five, six];
+        (root, nodes)
+    }
+
+    fn values(nodes: impl Iterator<Item = Tree<i64>>) -> Vec<i64> {
+        nodes.map(|node| *node.value()).collect()
+    }
+
+    #[test]
+    fn test_traversals() {
+        let (root, _) = sample();
+        assert_eq!(values(root.pre_order()), vec![1, 2, 4, 5, 3, 6]);
+        assert_eq!(values(root.post_order()), vec![4, 5, 2, 6, 3, 1]);
+        assert_eq!(values(root.level_order()), vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_parent_links_depth_and_path() {
+        let (root, nodes) = sample();
+        let five = &nodes[4];
+        assert!(five.parent().unwrap().ptr_eq(&nodes[1]));
+        assert_eq!(five.depth(), 2);
+        assert_eq!(root.depth(), 0);
+        assert_eq!(values(five.path_to_root().into_iter()), vec![5, 2, 1]);
+        assert!(five.root().ptr_eq(&root));
+    }
+
+    #[test]
+    fn test_find() {
+        let (root, _) = sample();
+        assert_eq!(*root.find(|v| v % 3 == 0).unwrap().value(), 3);
+        assert!(root.find(|v| *v > 10).is_none());
+    }
+
+    #[test]
+    fn test_remove_and_reparent() {
+        let (root, nodes) = sample();
+        let (two, three, six) = (&nodes[1], &nodes[2], &nodes[5]);
+
+        assert_eq!(root.remove_child(six), Err(TreeError::NotAChild));
+        three.remove_child(six).unwrap();
+        assert!(six.is_root());
+        assert!(three.children().is_empty());
+
+        two.reparent(three).unwrap();
+        assert!(two.parent().unwrap().ptr_eq(three));
+        assert_eq!(values(root.pre_order()), vec![1, 3, 2, 4, 5]);
+        // Only one parent keeps a strong reference to `two`.
+        assert_eq!(Rc::strong_count(&two.0), 2);
+    }
+
+    #[test]
+    fn test_cycles_are_rejected() {
+        let (root, nodes) = sample();
+        let four = &nodes[3];
+        assert_eq!(four.add_child(&root), Err(TreeError::Cycle));
+        assert_eq!(four.add_child(four), Err(TreeError::Cycle));
+        assert_eq!(root.reparent(four), Err(TreeError::Cycle));
+        // Nothing moved.
+        assert_eq!(values(root.pre_order()), vec![1, 2, 4, 5, 3, 6]);
+    }
+
+    #[test]
+    fn test_render() {
+        let (root, _) = sample();
+        assert_eq!(
+            root.render(),
+            "1\n\
+             |-- 2\n\
+             |   |-- 4\n\
+             |   `-- 5\n\
+             `-- 3\n    \
+             `-- 6\n"
+        );
+        assert_eq!(root.to_string(), root.render().trim_end());
+        assert_eq!(Tree::new(7).to_string(), "7");
+    }
+
+    #[test]
+    fn test_reference_counts() {
+        let (root, nodes) = sample();
+        // Each child: one strong reference from its parent, one from `nodes`.
+        assert_eq!(Rc::strong_count(&nodes[3].0), 2);
+        // Each node with children: one weak reference per child's parent link.
+        assert_eq!(Rc::weak_count(&nodes[1].0), 2);
+        assert_eq!(Rc::weak_count(&nodes[3].0), 0);
+        // The root is held by `root` and by `nodes`, never by its children.
+        assert_eq!(Rc::strong_count(&root.0), 2);
+        assert_eq!(Rc::weak_count(&root.0), 2);
+    }
+
+    #[test]
+    fn test_dropping_root_frees_every_node() {
+        let (root, nodes) = sample();
+        let weak: Vec<Weak<RefCell<Node<i64>>>> = nodes.iter().map(|n| Rc::downgrade(&n.0)).collect();
+        drop(nodes);
+        assert!(weak.iter().all(|w| w.upgrade().is_some()));
+
+        drop(root);
+        assert!(weak.iter().all(|w| w.upgrade().is_none()));
+        assert!(weak.iter().all(|w| w.strong_count() == 0));
+    }
+
+    #[test]
+    fn test_detached_subtree_outlives_old_root() {
+        let (root, nodes) = sample();
+        let two = nodes[1].clone();
+        let weak_root = Rc::downgrade(&root.0);
+        root.remove_child(&two).unwrap();
+        drop(nodes);
+        drop(root);
+
+        assert!(weak_root.upgrade().is_none());
+        assert!(two.is_root());
+        assert_eq!(values(two.pre_order()), vec![2, 4, 5]);
+    }
+}
@@ -1,4 +1,24 @@
+mod plugins;
+mod registry;
+
+use registry::Registry;
+
 fn main() {
+    // `prog <command> [args]` runs a registered command (see registry.rs and plugins/).
+    let mut registry = Registry::new();
+    plugins::register_all(&mut registry).expect("plugin commands are valid");
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    if !argv.is_empty() {
+        match registry.dispatch(&argv) {
+            Ok(output) => println!("{}", output.trim_end()),
+            Err(e) => {
+                eprintln!("error: {e}");
+                std::process::exit(2);
+            }
+        }
+        return;
+    }
+
     // 20 Modules
     mod foo {
         pub fn do_something() {
@@ -36,6 +56,8 @@ fn main() {
     // Parent and sibling items are always visible.
     // In other words, if an item is visible in module foo, it’s visible in all the descendants of foo.
 
+    // The private functions are never called: main cannot reach them.
+    #[allow(dead_code)]
     mod outer {
         fn private() {
             println!("outer::private");
@@ -58,4 +80,7 @@ fn main() {
     }
 
     outer::public();
+
+    // Without arguments, list what the plugins registered.
+    print!("{}", registry.help());
 }
@@ -0,0 +1,49 @@
+// Plugins. `text` and `numbers` are private modules: nothing outside
+// `plugins` can call their helpers, only the commands they register are reachable.
+
+mod numbers;
+mod text;
+
+use crate::registry::{Registry, RegistryError};
+
+pub fn register_all(registry: &mut Registry) -> Result<(), RegistryError> {
+    text::register(registry)?;
+    numbers::register(registry)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(line: &str) -> Result<String, String> {
+        let mut registry = Registry::new();
+        register_all(&mut registry).unwrap();
+        let argv: Vec<String> = line.split_whitespace().map(String::from).collect();
+        registry.dispatch(&argv).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_text_commands() {
+        assert_eq!(run("greet Ferris"), Ok("Hello, Ferris!".to_string()));
+        assert_eq!(run("greet Ferris Hoi"), Ok("Hoi, Ferris!".to_string()));
+        assert_eq!(run("shout modules are neat"), Ok("MODULES ARE NEAT!".to_string()));
+        assert_eq!(run("shout"), Ok("!".to_string()));
+    }
+
+    #[test]
+    fn test_number_commands() {
+        assert_eq!(run("sum 1 2 3.5"), Ok("6.5".to_string()));
+        assert_eq!(run("sum"), Ok("0".to_string()));
+        assert_eq!(run("stats 4 1 3"), Ok("count 3, min 1, max 4, mean 2.6666666666666665".to_string()));
+        assert_eq!(run("stats"), Err("stats: need at least one number".to_string()));
+        assert_eq!(run("divide 7 2"), Ok("3.5".to_string()));
+        assert_eq!(run("divide 1 0"), Err("divide: division by zero".to_string()));
+        assert_eq!(run("sum 1 two"), Err("sum: `two` is not a number".to_string()));
+    }
+
+    #[test]
+    fn test_typo_suggestion() {
+        assert_eq!(run("gret x"), Err("unknown command `gret`, did you mean `greet`?".to_string()));
+    }
+}
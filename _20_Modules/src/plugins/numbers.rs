@@ -0,0 +1,58 @@
+// Number commands.
+
+use crate::registry::{ArgSpec, Args, Command, Registry, RegistryError};
+
+pub fn register(registry: &mut Registry) -> Result<(), RegistryError> {
+    registry.register(Command {
+        name: "sum",
+        help: "adds up the numbers",
+        args: vec![ArgSpec::rest("numbers")],
+        run: sum,
+    })?;
+    registry.register(Command {
+        name: "stats",
+        help: "count, min, max and mean of the numbers",
+        args: vec![ArgSpec::rest("numbers")],
+        run: stats,
+    })?;
+    registry.register(Command {
+        name: "divide",
+        help: "divides a by b",
+        args: vec![ArgSpec::required("a"), ArgSpec::required("b")],
+        run: divide,
+    })
+}
+
+fn parse_number(text: &str) -> Result<f64, String> {
+    text.parse().map_err(|_| format!("`{text}` is not a number"))
+}
+
+fn parse_numbers(values: &[String]) -> Result<Vec<f64>, String> {
+    values.iter().map(|v| parse_number(v)).collect()
+}
+
+fn sum(args: &Args) -> Result<String, String> {
+    let numbers = parse_numbers(args.rest())?;
+    // Not `sum()`: it starts from -0.0, which would print as "-0" for no numbers.
+    Ok(numbers.iter().fold(0.0, |total, n| total + n).to_string())
+}
+
+fn stats(args: &Args) -> Result<String, String> {
+    let numbers = parse_numbers(args.rest())?;
+    if numbers.is_empty() {
+        return Err("need at least one number".to_string());
+    }
+    let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mean = numbers.iter().sum::<f64>() / numbers.len() as f64;
+    Ok(format!("count {}, min {min}, max {max}, mean {mean}", numbers.len()))
+}
+
+fn divide(args: &Args) -> Result<String, String> {
+    let a = parse_number(args.get("a").ok_or("missing a")?)?;
+    let b = parse_number(args.get("b").ok_or("missing b")?)?;
+    if b == 0.0 {
+        return Err("division by zero".to_string());
+    }
+    Ok((a / b).to_string())
+}
@@ -0,0 +1,27 @@
+// Text commands.
+
+use crate::registry::{ArgSpec, Args, Command, Registry, RegistryError};
+
+pub fn register(registry: &mut Registry) -> Result<(), RegistryError> {
+    registry.register(Command {
+        name: "greet",
+        help: "greets someone, with \"Hello\" unless told otherwise",
+        args: vec![ArgSpec::required("name"), ArgSpec::optional("greeting")],
+        run: greet,
+    })?;
+    registry.register(Command {
+        name: "shout",
+        help: "repeats the words in upper case",
+        args: vec![ArgSpec::rest("words")],
+        run: shout,
+    })
+}
+
+fn greet(args: &Args) -> Result<String, String> {
+    let name = args.get("name").ok_or("missing name")?;
+    Ok(format!("{}, {name}!", args.get("greeting").unwrap_or("Hello")))
+}
+
+fn shout(args: &Args) -> Result<String, String> {
+    Ok(format!("{}!", args.rest().join(" ").to_uppercase()))
+}
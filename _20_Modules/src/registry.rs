@@ -0,0 +1,394 @@
+// Command registry.
+// Each plugin module hands its commands to `Registry::register`; main only
+// sees the registry, never the modules' helper functions.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub type Handler = fn(&Args) -> Result<String, String>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    Required,
+    Optional,
+    /// Zero or more remaining values. Only allowed as the last argument.
+    Rest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+}
+
+impl ArgSpec {
+    pub fn required(name: &'static str) -> Self {
+        ArgSpec { name, kind: ArgKind::Required }
+    }
+
+    pub fn optional(name: &'static str) -> Self {
+        ArgSpec { name, kind: ArgKind::Optional }
+    }
+
+    pub fn rest(name: &'static str) -> Self {
+        ArgSpec { name, kind: ArgKind::Rest }
+    }
+}
+
+impl fmt::Display for ArgSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ArgKind::Required => write!(f, "<{}>", self.name),
+            ArgKind::Optional => write!(f, "[{}]", self.name),
+            ArgKind::Rest => write!(f, "[{}...]", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub args: Vec<ArgSpec>,
+    pub run: Handler,
+}
+
+impl Command {
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.to_string();
+        for arg in &self.args {
+            usage.push_str(&format!(" {arg}"));
+        }
+        usage
+    }
+}
+
+/// The command line values, matched up with the command's `ArgSpec`s.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    values: BTreeMap<&'static str, String>,
+    rest: Vec<String>,
+}
+
+impl Args {
+    /// `None` for an optional argument that was not given.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Values collected by the `Rest` argument.
+    pub fn rest(&self) -> &[String] {
+        &self.rest
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    Duplicate { name: &'static str },
+    /// The name of a built-in command, such as `help`.
+    Reserved { name: &'static str },
+    /// A required argument after an optional one, or anything after `Rest`.
+    BadArgOrder { name: &'static str },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::Duplicate { name } => write!(f, "command `{name}` is registered twice"),
+            RegistryError::Reserved { name } => write!(f, "command name `{name}` is reserved"),
+            RegistryError::BadArgOrder { name } => {
+                write!(f, "command `{name}`: required arguments must come first and a rest argument last")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DispatchError {
+    NoCommand,
+    Unknown { name: String, suggestions: Vec<&'static str> },
+    Usage { usage: String, message: String },
+    Failed { command: &'static str, message: String },
+}
+
+impl fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DispatchError::NoCommand => write!(f, "no command given, try `help`"),
+            DispatchError::Unknown { name, suggestions } => {
+                write!(f, "unknown command `{name}`")?;
+                match suggestions.as_slice() {
+                    [] => write!(f, ", try `help`"),
+                    [only] => write!(f, ", did you mean `{only}`?"),
+                    many => write!(f, ", did you mean one of: {}?", many.join(", ")),
+                }
+            }
+            DispatchError::Usage { usage, message } => write!(f, "{message}\nusage: {usage}"),
+            DispatchError::Failed { command, message } => write!(f, "{command}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for DispatchError {}
+
+#[derive(Debug, Default)]
+pub struct Registry {
+    commands: BTreeMap<&'static str, Command>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    pub fn register(&mut self, command: Command) -> Result<(), RegistryError> {
+        let name = command.name;
+        if name == "help" {
+            return Err(RegistryError::Reserved { name });
+        }
+        if self.commands.contains_key(name) {
+            return Err(RegistryError::Duplicate { name });
+        }
+        let mut seen_optional = false;
+        for (i, arg) in command.args.iter().enumerate() {
+            let misplaced = match arg.kind {
+                ArgKind::Required => seen_optional,
+                ArgKind::Optional => false,
+                ArgKind::Rest => i + 1 != command.args.len(),
+            };
+            if misplaced {
+                return Err(RegistryError::BadArgOrder { name });
+            }
+            seen_optional |= arg.kind != ArgKind::Required;
+        }
+        self.commands.insert(name, command);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        self.commands.get(name)
+    }
+
+    /// Sorted by name.
+    pub fn commands(&self) -> impl Iterator<Item = &Command> {
+        self.commands.values()
+    }
+
+    /// One line per command: usage and help text, aligned.
+    pub fn help(&self) -> String {
+        let usages: Vec<String> = self.commands().map(Command::usage).collect();
+        let width = usages.iter().map(String::len).max().unwrap_or(0).max("help [command]".len());
+        let mut out = String::from("commands:\n");
+        out.push_str(&format!("  {:width$}  show this list or one command's usage\n", "help [command]"));
+        for (command, usage) in self.commands().zip(&usages) {
+            let line = format!("  {usage:width$}  {}", command.help);
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Registered names close to `name`, closest first. Nothing for an empty name.
+    pub fn suggest(&self, name: &str) -> Vec<&'static str> {
+        if name.is_empty() {
+            return Vec::new();
+        }
+        let max_distance = (name.chars().count() / 3).max(1);
+        let mut close: Vec<(usize, &'static str)> = self
+            .commands
+            .keys()
+            .map(|&candidate| (edit_distance(name, candidate), candidate))
+            .filter(|&(distance, candidate)| distance <= max_distance || candidate.starts_with(name))
+            .collect();
+        close.sort();
+        close.into_iter().map(|(_, candidate)| candidate).collect()
+    }
+
+    /// Runs `<command> [args]` and returns its output. `help` is built in.
+    pub fn dispatch(&self, argv: &[String]) -> Result<String, DispatchError> {
+        let (name, rest) = argv.split_first().ok_or(DispatchError::NoCommand)?;
+        if name == "help" {
+            return match rest.first() {
+                None => Ok(self.help()),
+                Some(topic) => match self.get(topic) {
+                    Some(command) => Ok(format!("{}\n  {}\n", command.usage(), command.help)),
+                    None => Err(self.unknown(topic)),
+                },
+            };
+        }
+
+        let command = self.get(name).ok_or_else(|| self.unknown(name))?;
+        let args = bind_args(command, rest)?;
+        (command.run)(&args).map_err(|message| DispatchError::Failed { command: command.name, message })
+    }
+
+    fn unknown(&self, name: &str) -> DispatchError {
+        DispatchError::Unknown { name: name.to_string(), suggestions: self.suggest(name) }
+    }
+}
+
+fn bind_args(command: &Command, values: &[String]) -> Result<Args, DispatchError> {
+    let usage_error = |message: String| DispatchError::Usage { usage: command.usage(), message };
+    let mut args = Args::default();
+    let mut values = values.iter();
+
+    for spec in &command.args {
+        match spec.kind {
+            ArgKind::Required => {
+                let value = values.next().ok_or_else(|| usage_error(format!("missing argument <{}>", spec.name)))?;
+                args.values.insert(spec.name, value.clone());
+            }
+            ArgKind::Optional => {
+                if let Some(value) = values.next() {
+                    args.values.insert(spec.name, value.clone());
+                }
+            }
+            ArgKind::Rest => args.rest.extend(values.by_ref().cloned()),
+        }
+    }
+
+    let extra: Vec<&str> = values.map(String::as_str).collect();
+    if !extra.is_empty() {
+        return Err(usage_error(format!("unexpected argument(s): {}", extra.join(" "))));
+    }
+    Ok(args)
+}
+
+/// Levenshtein distance, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn echo(args: &Args) -> Result<String, String> {
+        let mut out = format!("{} {}", args.get("first").unwrap_or("-"), args.get("second").unwrap_or("-"));
+        for value in args.rest() {
+            out.push_str(&format!(" +{value}"));
+        }
+        Ok(out)
+    }
+
+    fn fail(_: &Args) -> Result<String, String> {
+        Err("always fails".to_string())
+    }
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+        registry
+            .register(Command {
+                name: "echo",
+                help: "prints its arguments",
+                args: vec![ArgSpec::required("first"), ArgSpec::optional("second"), ArgSpec::rest("more")],
+                run: echo,
+            })
+            .unwrap();
+        registry.register(Command { name: "fail", help: "fails", args: vec![], run: fail }).unwrap();
+        registry.register(Command { name: "format", help: "", args: vec![], run: fail }).unwrap();
+        registry
+    }
+
+    fn argv(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_dispatch_binds_arguments() {
+        let registry = registry();
+        assert_eq!(registry.dispatch(&argv("echo a")), Ok("a -".to_string()));
+        assert_eq!(registry.dispatch(&argv("echo a b c d")), Ok("a b +c +d".to_string()));
+        assert_eq!(
+            registry.dispatch(&argv("echo")),
+            Err(DispatchError::Usage {
+                usage: "echo <first> [second] [more...]".to_string(),
+                message: "missing argument <first>".to_string(),
+            })
+        );
+        assert_eq!(
+            registry.dispatch(&argv("fail x")).unwrap_err().to_string(),
+            "unexpected argument(s): x\nusage: fail"
+        );
+        assert_eq!(
+            registry.dispatch(&argv("fail")),
+            Err(DispatchError::Failed { command: "fail", message: "always fails".to_string() })
+        );
+        assert_eq!(registry.dispatch(&[]), Err(DispatchError::NoCommand));
+    }
+
+    #[test]
+    fn test_unknown_command_suggestions() {
+        let registry = registry();
+        assert_eq!(registry.suggest("ecko"), vec!["echo"]);
+        assert_eq!(registry.suggest("fo"), vec!["format"]);
+        assert_eq!(registry.suggest("fal"), vec!["fail"]);
+        assert!(registry.suggest("zzzzzz").is_empty());
+        assert!(registry.suggest("").is_empty());
+        let error = registry.dispatch(&[String::new()]).unwrap_err();
+        assert_eq!(error.to_string(), "unknown command ``, try `help`");
+
+        let error = registry.dispatch(&argv("fai")).unwrap_err();
+        assert_eq!(error.to_string(), "unknown command `fai`, did you mean `fail`?");
+        let error = registry.dispatch(&argv("help formt")).unwrap_err();
+        assert_eq!(error.to_string(), "unknown command `formt`, did you mean `format`?");
+        let error = registry.dispatch(&argv("f")).unwrap_err();
+        assert_eq!(error.to_string(), "unknown command `f`, did you mean one of: fail, format?");
+    }
+
+    #[test]
+    fn test_help() {
+        let registry = registry();
+        let help = registry.dispatch(&argv("help")).unwrap();
+        assert_eq!(
+            help,
+            "commands:\n  \
+             help [command]                   show this list or one command's usage\n  \
+             echo <first> [second] [more...]  prints its arguments\n  \
+             fail                             fails\n  \
+             format\n"
+        );
+        assert_eq!(registry.dispatch(&argv("help fail")), Ok("fail\n  fails\n".to_string()));
+    }
+
+    #[test]
+    fn test_register_rejects_bad_commands() {
+        let mut registry = registry();
+        let command = |name, args| Command { name, help: "", args, run: fail };
+        assert_eq!(registry.register(command("echo", vec![])), Err(RegistryError::Duplicate { name: "echo" }));
+        assert_eq!(registry.register(command("help", vec![])), Err(RegistryError::Reserved { name: "help" }));
+        assert_eq!(
+            registry.register(command("help", vec![])).unwrap_err().to_string(),
+            "command name `help` is reserved"
+        );
+        assert_eq!(
+            registry.register(command("a", vec![ArgSpec::optional("x"), ArgSpec::required("y")])),
+            Err(RegistryError::BadArgOrder { name: "a" })
+        );
+        assert_eq!(
+            registry.register(command("b", vec![ArgSpec::rest("x"), ArgSpec::optional("y")])),
+            Err(RegistryError::BadArgOrder { name: "b" })
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("greet", "greet"), 0);
+        assert_eq!(edit_distance("gret", "greet"), 1);
+    }
+}
@@ -0,0 +1,463 @@
+// Batch validation of large files, one number per line.
+//
+// The input is read in blocks of whole lines. Worker threads validate a block
+// each and format its part of the report; the calling thread writes the parts
+// back in input order. Only a few blocks are in flight at once, so memory use
+// does not depend on the size of the input.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::luhn;
+use crate::validation;
+
+const BLOCK_SIZE: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `line,number,valid,error` with a header row.
+    Csv,
+    /// One array of `{"line", "number", "valid", "error"}` objects; `error` only when invalid.
+    Json,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    pub format: Format,
+    pub threads: usize,
+    /// Leave valid numbers out of the report. They are still counted.
+    pub invalid_only: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            format: Format::Csv,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            invalid_only: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    /// Non-blank lines.
+    pub numbers: u64,
+    pub valid: u64,
+    pub invalid: u64,
+}
+
+/// Whole lines from the input; `first_line` is the 1-based number of the first one.
+struct Block {
+    seq: u64,
+    first_line: u64,
+    bytes: Vec<u8>,
+}
+
+struct Report {
+    seq: u64,
+    text: String,
+    summary: Summary,
+}
+
+/// Validates every non-blank line of `input` and writes the report to `output`.
+pub fn run(input: impl Read + Send, mut output: impl Write, options: &Options) -> io::Result<Summary> {
+    let threads = options.threads.max(1);
+    let (block_tx, block_rx) = mpsc::sync_channel::<Block>(threads * 2);
+    let block_rx = Arc::new(Mutex::new(block_rx));
+    let (report_tx, report_rx) = mpsc::sync_channel::<Report>(threads * 2);
+
+    thread::scope(|scope| {
+        let reader = scope.spawn(move || read_blocks(input, block_tx));
+        for _ in 0..threads {
+            let block_rx = Arc::clone(&block_rx);
+            let report_tx = report_tx.clone();
+            scope.spawn(move || loop {
+                // The lock is released before validating, so workers only queue up here.
+                let block = match block_rx.lock().unwrap().recv() {
+                    Ok(block) => block,
+                    Err(_) => break,
+                };
+                if report_tx.send(check_block(&block, options)).is_err() {
+                    break;
+                }
+            });
+        }
+        // Only the workers may keep the receivers alive, or the reader would
+        // wait forever on a full channel once the workers have stopped.
+        drop(block_rx);
+        drop(report_tx);
+
+        let summary = write_reports(report_rx, &mut output, options.format);
+        // On a write error the report receiver is gone, so the workers stop on
+        // their next send. That drops the block receiver and stops the reader.
+        let read = reader.join().expect("reader thread panicked");
+        let summary = summary?;
+        read?;
+        Ok(summary)
+    })
+}
+
+fn read_blocks(mut input: impl Read, blocks: mpsc::SyncSender<Block>) -> io::Result<()> {
+    let mut carry: Vec<u8> = Vec::new();
+    let mut seq = 0;
+    let mut next_line = 1;
+    loop {
+        let mut bytes = std::mem::take(&mut carry);
+        let start = bytes.len();
+        bytes.resize(start + BLOCK_SIZE, 0);
+        // Retry in place, so an interrupted read keeps the partial line in `bytes`.
+        let n = loop {
+            match input.read(&mut bytes[start..]) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        };
+        bytes.truncate(start + n);
+        let at_end = n == 0;
+
+        // Keep a partial last line for the next block.
+        if !at_end {
+            match bytes.iter().rposition(|&b| b == b'\n') {
+                Some(last_newline) => carry = bytes.split_off(last_newline + 1),
+                None => {
+                    carry = bytes;
+                    continue;
+                }
+            }
+        }
+        if !bytes.is_empty() {
+            let lines = bytes.iter().filter(|&&b| b == b'\n').count() as u64 + u64::from(!bytes.ends_with(b"\n"));
+            if blocks.send(Block { seq, first_line: next_line, bytes }).is_err() {
+                return Ok(());
+            }
+            seq += 1;
+            next_line += lines;
+        }
+        if at_end {
+            return Ok(());
+        }
+    }
+}
+
+fn check_block(block: &Block, options: &Options) -> Report {
+    let mut text = String::new();
+    let mut summary = Summary::default();
+    let lines = block.bytes.strip_suffix(b"\n").unwrap_or(&block.bytes).split(|&b| b == b'\n');
+    for (line_number, line) in (block.first_line..).zip(lines) {
+        let line = String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line));
+        if line.trim().is_empty() {
+            continue;
+        }
+        summary.numbers += 1;
+        // The fast check first; the diagnosis only for the (rare) failures.
+        let error = if luhn(&line) {
+            summary.valid += 1;
+            None
+        } else {
+            summary.invalid += 1;
+            Some(validation::validate(&line).err().map_or_else(|| "invalid".to_string(), |e| e.to_string()))
+        };
+        if error.is_none() && options.invalid_only {
+            continue;
+        }
+        let number = line.trim();
+        match options.format {
+            Format::Csv => {
+                let error = error.as_deref().unwrap_or("");
+                // Writing to a String cannot fail.
+                let _ = writeln!(text, "{line_number},{},{},{}", csv_field(number), error.is_empty(), csv_field(error));
+            }
+            Format::Json => {
+                if !text.is_empty() {
+                    text.push_str(",\n");
+                }
+                let _ = write!(text, "  {{\"line\": {line_number}, \"number\": ");
+                push_json_string(&mut text, number);
+                let _ = write!(text, ", \"valid\": {}", error.is_none());
+                if let Some(error) = &error {
+                    text.push_str(", \"error\": ");
+                    push_json_string(&mut text, error);
+                }
+                text.push('}');
+            }
+        }
+    }
+    Report { seq: block.seq, text, summary }
+}
+
+/// Writes the reports in block order, whatever order they arrive in.
+fn write_reports(reports: mpsc::Receiver<Report>, output: &mut impl Write, format: Format) -> io::Result<Summary> {
+    let mut summary = Summary::default();
+    let mut pending: BTreeMap<u64, Report> = BTreeMap::new();
+    let mut next_seq = 0;
+    let mut wrote_record = false;
+
+    match format {
+        Format::Csv => output.write_all(b"line,number,valid,error\n")?,
+        Format::Json => output.write_all(b"[")?,
+    }
+    for report in reports {
+        pending.insert(report.seq, report);
+        while let Some(report) = pending.remove(&next_seq) {
+            next_seq += 1;
+            summary.numbers += report.summary.numbers;
+            summary.valid += report.summary.valid;
+            summary.invalid += report.summary.invalid;
+            if report.text.is_empty() {
+                continue;
+            }
+            if format == Format::Json {
+                output.write_all(if wrote_record { b",\n" } else { b"\n" })?;
+            }
+            output.write_all(report.text.as_bytes())?;
+            wrote_record = true;
+        }
+    }
+    if format == Format::Json {
+        output.write_all(if wrote_record { b"\n]\n" } else { b"]\n" })?;
+    }
+    output.flush()?;
+    Ok(summary)
+}
+
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+fn push_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::time::Instant;
+
+    fn report(input: &str, options: &Options) -> (String, Summary) {
+        let mut output = Vec::new();
+        let summary = run(input.as_bytes(), &mut output, options).unwrap();
+        (String::from_utf8(output).unwrap(), summary)
+    }
+
+    const INPUT: &str = "4263 9826 4026 9299\n4223-9826-4026-9299\n\n4539\t3195\t0343\t6467\r\nfoo,\"bar\"\n7";
+
+    #[test]
+    fn test_csv_report() {
+        let options = Options { format: Format::Csv, threads: 2, invalid_only: false };
+        let (csv, summary) = report(INPUT, &options);
+        assert_eq!(
+            csv,
+            "line,number,valid,error\n\
+             1,4263 9826 4026 9299,true,\n\
+             2,4223-9826-4026-9299,false,\"check digit is 9, expected 8\"\n\
+             4,4539\t3195\t0343\t6467,true,\n\
+             5,\"foo,\"\"bar\"\"\",false,invalid character 'f' at position 0\n\
+             6,7,false,\"need at least 2 digits, found 1\"\n"
+        );
+        assert_eq!(summary, Summary { numbers: 5, valid: 2, invalid: 3 });
+    }
+
+    #[test]
+    fn test_json_report() {
+        let options = Options { format: Format::Json, threads: 1, invalid_only: true };
+        let (json, summary) = report(INPUT, &options);
+        assert_eq!(
+            json,
+            "[\n  \
+             {\"line\": 2, \"number\": \"4223-9826-4026-9299\", \"valid\": false, \"error\": \"check digit is 9, expected 8\"},\n  \
+             {\"line\": 5, \"number\": \"foo,\\\"bar\\\"\", \"valid\": false, \"error\": \"invalid character 'f' at position 0\"},\n  \
+             {\"line\": 6, \"number\": \"7\", \"valid\": false, \"error\": \"need at least 2 digits, found 1\"}\n]\n"
+        );
+        assert_eq!(summary.invalid, 3);
+
+        let (empty, summary) = report("", &options);
+        assert_eq!((empty.as_str(), summary), ("[]\n", Summary::default()));
+    }
+
+    #[test]
+    fn test_order_and_line_numbers_across_blocks() {
+        // Enough lines for several blocks, so the workers finish out of order.
+        let numbers = ["4263982640269299", "4223982640269299", "79927398713"];
+        let input: String = (0..200_000).map(|i| format!("{}\n", numbers[i % 3])).collect();
+        let options = Options { format: Format::Csv, threads: 4, invalid_only: false };
+        let (csv, summary) = report(&input, &options);
+
+        assert_eq!(summary, Summary { numbers: 200_000, valid: 133_333, invalid: 66_667 });
+        for (i, row) in csv.lines().skip(1).enumerate() {
+            assert!(row.starts_with(&format!("{},{},", i + 1, numbers[i % 3])), "{row}");
+        }
+    }
+
+    /// Hands out `parts` one read at a time, `None` standing for an interrupted read.
+    struct Interrupting<'a> {
+        parts: std::collections::VecDeque<Option<&'a str>>,
+    }
+
+    impl Read for Interrupting<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.parts.pop_front() {
+                Some(Some(part)) => {
+                    buf[..part.len()].copy_from_slice(part.as_bytes());
+                    Ok(part.len())
+                }
+                Some(None) => Err(io::ErrorKind::Interrupted.into()),
+                None => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_interrupted_read_keeps_partial_line() {
+        let parts = [Some("4263 9826"), None, Some(" 4026 9299\n42"), None, None, Some("23 9826 4026 9299")];
+        let input = Interrupting { parts: parts.into_iter().collect() };
+        let mut output = Vec::new();
+        let options = Options { format: Format::Csv, threads: 2, invalid_only: false };
+        let summary = run(input, &mut output, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "line,number,valid,error\n\
+             1,4263 9826 4026 9299,true,\n\
+             2,4223 9826 4026 9299,false,\"check digit is 9, expected 8\"\n"
+        );
+        assert_eq!(summary, Summary { numbers: 2, valid: 1, invalid: 1 });
+    }
+
+    /// Endless input, like a pipe that is never closed.
+    struct Endless;
+
+    impl Read for Endless {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            for chunk in buf.chunks_mut(17) {
+                chunk.copy_from_slice(&b"4263982640269299\n"[..chunk.len()]);
+            }
+            Ok(buf.len())
+        }
+    }
+
+    /// Accepts `limit` bytes, then fails like a pipe whose reader has exited.
+    struct ClosesAfter {
+        limit: usize,
+    }
+
+    impl Write for ClosesAfter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.limit == 0 {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            let n = buf.len().min(self.limit);
+            self.limit -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_stops_when_output_closes() {
+        for threads in [1, 2, 4] {
+            let options = Options { format: Format::Csv, threads, invalid_only: false };
+            let error = run(Endless, ClosesAfter { limit: 100 }, &options).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+        }
+    }
+
+    /// The `luhn` from before separators other than spaces were handled.
+    fn char_iterator_luhn(cc_number: &str) -> bool {
+        let length = cc_number.len();
+        let mut total_blank_cnt = 0;
+        let mut sum_luhn = 0;
+        for (i, ch) in cc_number.chars().rev().enumerate() {
+            if ch == ' ' {
+                total_blank_cnt += 1;
+                continue;
+            }
+            match ch.to_digit(10) {
+                Some(d) => {
+                    sum_luhn += if (i - total_blank_cnt) % 2 == 1 {
+                        let doubled = d * 2;
+                        doubled / 10 + doubled % 10
+                    } else {
+                        d
+                    }
+                }
+                None => return false,
+            }
+        }
+        length - total_blank_cnt >= 2 && sum_luhn % 10 == 0
+    }
+
+    /// `cargo test --release bench_batch -- --ignored --nocapture`.
+    /// Set LUHN_BENCH_LINES to change the default of 10 million lines.
+    #[test]
+    #[ignore]
+    fn bench_batch_against_char_iterator() {
+        let lines: usize = std::env::var("LUHN_BENCH_LINES").ok().and_then(|v| v.parse().ok()).unwrap_or(10_000_000);
+        let path = std::env::temp_dir().join(format!("luhn-bench-{}.txt", std::process::id()));
+        {
+            let mut file = io::BufWriter::new(std::fs::File::create(&path).unwrap());
+            let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+            for _ in 0..lines {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let mut digits = format!("{:015}", state % 1_000_000_000_000_000);
+                // Nine in ten numbers are valid, as in a real list of cards.
+                let check = crate::luhn_check_digit(&digits).unwrap();
+                digits.push(char::from_digit(if state.is_multiple_of(10) { (check + 1) % 10 } else { check }, 10).unwrap());
+                writeln!(file, "{} {} {} {}", &digits[..4], &digits[4..8], &digits[8..12], &digits[12..]).unwrap();
+            }
+        }
+
+        let start = Instant::now();
+        let file = io::BufReader::new(std::fs::File::open(&path).unwrap());
+        let mut valid = 0;
+        for line in file.lines() {
+            valid += u64::from(char_iterator_luhn(&line.unwrap()));
+        }
+        let baseline = start.elapsed();
+
+        let mut thread_counts = vec![1, Options::default().threads];
+        thread_counts.dedup();
+        for threads in thread_counts {
+            for invalid_only in [false, true] {
+                let options = Options { format: Format::Csv, threads, invalid_only };
+                let start = Instant::now();
+                let summary = run(std::fs::File::open(&path).unwrap(), io::sink(), &options).unwrap();
+                let elapsed = start.elapsed();
+                assert_eq!(summary.valid, valid);
+                let report = if invalid_only { "invalid only" } else { "full" };
+                println!("batch, {threads} thread(s), {report} CSV report: {elapsed:?}");
+            }
+        }
+        println!("char iterator, 1 thread, no report: {baseline:?} for {lines} lines ({valid} valid)");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,304 @@
+// Payment card numbers: parsing, network detection and display.
+// A `CardNumber` only exists for input that passed every check, so code that
+// holds one never has to re-validate it.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{is_separator, luhn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Visa,
+    Mastercard,
+    AmericanExpress,
+    Discover,
+    Jcb,
+    DinersClub,
+    UnionPay,
+    Maestro,
+}
+
+/// `(first, last, network)`: numbers whose leading digits are between `first`
+/// and `last` belong to `network`. Both bounds have the same number of digits.
+/// More specific (longer) prefixes come first, so a Discover co-branded 622126
+/// is not taken for a UnionPay 62.
+const IIN_RANGES: &[(u32, u32, Network)] = &[
+    (622126, 622925, Network::Discover),
+    (2221, 2720, Network::Mastercard),
+    (3528, 3589, Network::Jcb),
+    (5018, 5018, Network::Maestro),
+    (5020, 5020, Network::Maestro),
+    (5038, 5038, Network::Maestro),
+    (5893, 5893, Network::Maestro),
+    (6011, 6011, Network::Discover),
+    (6304, 6304, Network::Maestro),
+    (6759, 6759, Network::Maestro),
+    (6761, 6763, Network::Maestro),
+    (300, 305, Network::DinersClub),
+    (644, 649, Network::Discover),
+    (34, 34, Network::AmericanExpress),
+    (36, 36, Network::DinersClub),
+    (37, 37, Network::AmericanExpress),
+    (38, 39, Network::DinersClub),
+    (51, 55, Network::Mastercard),
+    (62, 62, Network::UnionPay),
+    (65, 65, Network::Discover),
+    (4, 4, Network::Visa),
+];
+
+impl Network {
+    /// Detects the network from the leading digits. `digits` must only contain ASCII digits.
+    pub fn detect(digits: &str) -> Option<Network> {
+        IIN_RANGES.iter().find_map(|&(first, last, network)| {
+            let prefix_len = first.to_string().len();
+            let prefix: u32 = digits.get(..prefix_len)?.parse().ok()?;
+            (first..=last).contains(&prefix).then_some(network)
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Network::Visa => "Visa",
+            Network::Mastercard => "Mastercard",
+            Network::AmericanExpress => "American Express",
+            Network::Discover => "Discover",
+            Network::Jcb => "JCB",
+            Network::DinersClub => "Diners Club",
+            Network::UnionPay => "UnionPay",
+            Network::Maestro => "Maestro",
+        }
+    }
+
+    pub fn valid_lengths(self) -> &'static [usize] {
+        match self {
+            Network::Visa => &[13, 16, 19],
+            Network::Mastercard => &[16],
+            Network::AmericanExpress => &[15],
+            Network::Discover | Network::Jcb | Network::UnionPay => &[16, 17, 18, 19],
+            Network::DinersClub => &[14, 15, 16, 17, 18, 19],
+            Network::Maestro => &[12, 13, 14, 15, 16, 17, 18, 19],
+        }
+    }
+
+    /// How the digits are grouped when printed, e.g. 4-6-5 for American Express.
+    fn groups(self, len: usize) -> Vec<usize> {
+        match (self, len) {
+            (Network::AmericanExpress, 15) => vec![4, 6, 5],
+            (Network::DinersClub, 14) => vec![4, 6, 4],
+            // Groups of four; a single leftover digit joins the last group.
+            _ => {
+                let mut groups = vec![4; len / 4];
+                match (len % 4, groups.last_mut()) {
+                    (0, _) => {}
+                    (1, Some(last)) => *last += 1,
+                    (rest, _) => groups.push(rest),
+                }
+                groups
+            }
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CardError {
+    Empty,
+    /// `position` counts chars in the original input, starting at 0.
+    InvalidCharacter { ch: char, position: usize },
+    UnknownNetwork,
+    InvalidLength { network: Network, length: usize },
+    ChecksumMismatch,
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CardError::Empty => write!(f, "no digits"),
+            CardError::InvalidCharacter { ch, position } => {
+                write!(f, "invalid character {ch:?} at position {position}")
+            }
+            CardError::UnknownNetwork => write!(f, "unknown card network"),
+            CardError::InvalidLength { network, length } => {
+                write!(f, "{network} numbers cannot have {length} digits")
+            }
+            CardError::ChecksumMismatch => write!(f, "check digit does not match"),
+        }
+    }
+}
+
+impl std::error::Error for CardError {}
+
+/// Removes the separators people use to group the digits.
+pub fn normalize(input: &str) -> Result<String, CardError> {
+    let mut digits = String::with_capacity(input.len());
+    for (position, ch) in input.chars().enumerate() {
+        match ch {
+            '0'..='9' => digits.push(ch),
+            ch if is_separator(ch) => {}
+            _ => return Err(CardError::InvalidCharacter { ch, position }),
+        }
+    }
+    if digits.is_empty() {
+        return Err(CardError::Empty);
+    }
+    Ok(digits)
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct CardNumber {
+    digits: String,
+    network: Network,
+}
+
+impl CardNumber {
+    pub fn digits(&self) -> &str {
+        &self.digits
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn last_four(&self) -> &str {
+        &self.digits[self.digits.len() - 4..]
+    }
+
+    /// The full number, grouped the way the network prints it.
+    pub fn formatted(&self) -> String {
+        self.grouped(&self.digits)
+    }
+
+    /// Every digit but the last four replaced by `*`, e.g. `**** **** **** 9299`.
+    pub fn masked(&self) -> String {
+        let hidden = self.digits.len() - 4;
+        let masked: String = "*".repeat(hidden) + self.last_four();
+        self.grouped(&masked)
+    }
+
+    fn grouped(&self, chars: &str) -> String {
+        let mut out = String::with_capacity(chars.len() + 4);
+        let mut start = 0;
+        for len in self.network.groups(chars.len()) {
+            if start > 0 {
+                out.push(' ');
+            }
+            out.push_str(&chars[start..start + len]);
+            start += len;
+        }
+        out
+    }
+}
+
+impl FromStr for CardNumber {
+    type Err = CardError;
+
+    fn from_str(input: &str) -> Result<Self, CardError> {
+        let digits = normalize(input)?;
+        let network = Network::detect(&digits).ok_or(CardError::UnknownNetwork)?;
+        if !network.valid_lengths().contains(&digits.len()) {
+            return Err(CardError::InvalidLength { network, length: digits.len() });
+        }
+        if !luhn(&digits) {
+            return Err(CardError::ChecksumMismatch);
+        }
+        Ok(CardNumber { digits, network })
+    }
+}
+
+/// Shows the masked form, so card numbers don't end up in logs by accident.
+impl fmt::Display for CardNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.masked())
+    }
+}
+
+impl fmt::Debug for CardNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CardNumber({}, {})", self.network, self.masked())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_network() {
+        let cases = [
+            ("4263982640269299", Network::Visa),
+            ("5555555555554444", Network::Mastercard),
+            ("2223003122003222", Network::Mastercard),
+            ("378282246310005", Network::AmericanExpress),
+            ("6011111111111117", Network::Discover),
+            ("6221260000000000", Network::Discover),
+            ("6500000000000002", Network::Discover),
+            ("3530111333300000", Network::Jcb),
+            ("36227206271667", Network::DinersClub),
+            ("30569309025904", Network::DinersClub),
+            ("6200000000000005", Network::UnionPay),
+            ("6759649826438453", Network::Maestro),
+        ];
+        for (digits, network) in cases {
+            assert_eq!(Network::detect(digits), Some(network), "{digits}");
+        }
+        assert_eq!(Network::detect("1234567812345678"), None);
+        assert_eq!(Network::detect("9"), None);
+    }
+
+    #[test]
+    fn test_parse_valid_numbers() {
+        let card: CardNumber = "4263 9826 4026 9299".parse().unwrap();
+        assert_eq!(card.network(), Network::Visa);
+        assert_eq!(card.digits(), "4263982640269299");
+        assert_eq!(card.last_four(), "9299");
+
+        let amex: CardNumber = "3782-822463-10005".parse().unwrap();
+        assert_eq!(amex.network(), Network::AmericanExpress);
+        assert_eq!(amex.formatted(), "3782 822463 10005");
+
+        let tabbed: CardNumber = "4263\t9826\t4026\t9299".parse().unwrap();
+        assert_eq!(tabbed.digits(), "4263982640269299");
+
+        for number in ["5555 5555 5555 4444", "6011000990139424", "3566002020360505", "6200000000000005"] {
+            assert!(number.parse::<CardNumber>().is_ok(), "{number}");
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<CardNumber>(), Err(CardError::Empty));
+        assert_eq!(" - ".parse::<CardNumber>(), Err(CardError::Empty));
+        assert_eq!(
+            "4263 98x6".parse::<CardNumber>(),
+            Err(CardError::InvalidCharacter { ch: 'x', position: 7 })
+        );
+        assert_eq!("9999 9999 9999 9995".parse::<CardNumber>(), Err(CardError::UnknownNetwork));
+        // Passes Luhn, but Amex numbers have 15 digits.
+        assert_eq!(
+            "3400 0000 0000 0009".parse::<CardNumber>(),
+            Err(CardError::InvalidLength { network: Network::AmericanExpress, length: 16 })
+        );
+        assert_eq!("4223 9826 4026 9299".parse::<CardNumber>(), Err(CardError::ChecksumMismatch));
+    }
+
+    #[test]
+    fn test_masked_display() {
+        let card: CardNumber = "4263982640269299".parse().unwrap();
+        assert_eq!(card.masked(), "**** **** **** 9299");
+        assert_eq!(card.to_string(), "**** **** **** 9299");
+        assert_eq!(format!("{card:?}"), "CardNumber(Visa, **** **** **** 9299)");
+
+        let amex: CardNumber = "378282246310005".parse().unwrap();
+        assert_eq!(amex.masked(), "**** ****** *0005");
+        let diners: CardNumber = "36227206271667".parse().unwrap();
+        assert_eq!(diners.masked(), "**** ****** 1667");
+        let visa13: CardNumber = "4222222222222".parse().unwrap();
+        assert_eq!(visa13.masked(), "**** **** *2222");
+    }
+}
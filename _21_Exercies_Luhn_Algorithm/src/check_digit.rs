@@ -0,0 +1,480 @@
+// Check digit schemes behind one trait.
+// All of them ignore the spaces and dashes used to group digits
+// ("978-0-306-40615-7", "GB82 WEST 1234 ...").
+//
+// What they catch (see the corpus test at the bottom):
+// - Luhn: every single-digit error, adjacent transpositions except 09 <-> 90.
+// - EAN-13 / ISBN-13 / UPC-A: every single-digit error, adjacent
+//   transpositions except between digits that differ by 5.
+// - Verhoeff, Damm, ISBN-10 and IBAN: every single-digit error and every
+//   adjacent transposition.
+
+use crate::{is_separator, luhn, luhn_check_digit};
+
+pub trait CheckDigit {
+    fn name(&self) -> &'static str;
+
+    /// True if `input` is a well-formed number with a correct check digit.
+    fn validate(&self, input: &str) -> bool;
+
+    /// The check character(s) for `payload`, the number without them.
+    /// `None` if the payload is malformed for this scheme.
+    fn compute(&self, payload: &str) -> Option<String>;
+
+    /// `payload` with its check character(s) added.
+    fn complete(&self, payload: &str) -> Option<String> {
+        let check = self.compute(payload)?;
+        Some(clean(payload) + &check)
+    }
+}
+
+/// Drops the grouping separators.
+fn clean(input: &str) -> String {
+    input.chars().filter(|c| !is_separator(*c)).collect()
+}
+
+/// The digit values, or `None` if anything but an ASCII digit is present.
+fn digits(input: &str) -> Option<Vec<u32>> {
+    input.chars().map(|c| c.to_digit(10)).collect()
+}
+
+/// Check digit that makes `sum(weight * digit)` a multiple of 10, with the
+/// weights cycling from the leftmost payload digit.
+fn weighted_mod10(payload: &[u32], weights: &[u32]) -> u32 {
+    let sum: u32 = payload.iter().zip(weights.iter().cycle()).map(|(d, w)| d * w).sum();
+    (10 - sum % 10) % 10
+}
+
+pub struct Luhn;
+
+impl CheckDigit for Luhn {
+    fn name(&self) -> &'static str {
+        "Luhn"
+    }
+
+    fn validate(&self, input: &str) -> bool {
+        luhn(&clean(input))
+    }
+
+    fn compute(&self, payload: &str) -> Option<String> {
+        luhn_check_digit(&clean(payload)).map(|d| d.to_string())
+    }
+}
+
+pub struct Verhoeff;
+
+/// Multiplication table of the dihedral group D5.
+const VERHOEFF_D: [[u8; 10]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+    [1, 2, 3, 4, 0, 6, 7, 8, 9, 5],
+    [2, 3, 4, 0, 1, 7, 8, 9, 5, 6],
+    [3, 4, 0, 1, 2, 8, 9, 5, 6, 7],
+    [4, 0, 1, 2, 3, 9, 5, 6, 7, 8],
+    [5, 9, 8, 7, 6, 0, 4, 3, 2, 1],
+    [6, 5, 9, 8, 7, 1, 0, 4, 3, 2],
+    [7, 6, 5, 9, 8, 2, 1, 0, 4, 3],
+    [8, 7, 6, 5, 9, 3, 2, 1, 0, 4],
+    [9, 8, 7, 6, 5, 4, 3, 2, 1, 0],
+];
+
+/// Permutation applied to a digit depending on its position.
+const VERHOEFF_P: [[u8; 10]; 8] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+    [1, 5, 7, 6, 2, 8, 3, 0, 9, 4],
+    [5, 8, 0, 3, 7, 9, 6, 1, 4, 2],
+    [8, 9, 1, 6, 0, 4, 3, 5, 2, 7],
+    [9, 4, 5, 3, 1, 2, 6, 8, 7, 0],
+    [4, 2, 8, 6, 5, 7, 3, 9, 0, 1],
+    [2, 7, 9, 3, 8, 0, 6, 4, 1, 5],
+    [7, 0, 4, 6, 9, 1, 3, 2, 5, 8],
+];
+
+const VERHOEFF_INV: [u8; 10] = [0, 4, 3, 2, 1, 5, 6, 7, 8, 9];
+
+impl Verhoeff {
+    /// `offset` is 1 when computing, since the check digit will take position 0.
+    fn checksum(digits: &[u32], offset: usize) -> u8 {
+        digits.iter().rev().enumerate().fold(0, |c, (i, &d)| {
+            VERHOEFF_D[c as usize][VERHOEFF_P[(i + offset) % 8][d as usize] as usize]
+        })
+    }
+}
+
+impl CheckDigit for Verhoeff {
+    fn name(&self) -> &'static str {
+        "Verhoeff"
+    }
+
+    fn validate(&self, input: &str) -> bool {
+        match digits(&clean(input)) {
+            Some(d) if d.len() >= 2 => Verhoeff::checksum(&d, 0) == 0,
+            _ => false,
+        }
+    }
+
+    fn compute(&self, payload: &str) -> Option<String> {
+        let d = digits(&clean(payload))?;
+        Some(VERHOEFF_INV[Verhoeff::checksum(&d, 1) as usize].to_string())
+    }
+}
+
+pub struct Damm;
+
+/// A totally anti-symmetric quasigroup of order 10.
+const DAMM_TABLE: [[u8; 10]; 10] = [
+    [0, 3, 1, 7, 5, 9, 8, 6, 4, 2],
+    [7, 0, 9, 2, 1, 5, 4, 8, 6, 3],
+    [4, 2, 0, 6, 8, 7, 1, 3, 5, 9],
+    [1, 7, 5, 0, 9, 8, 3, 4, 2, 6],
+    [6, 1, 2, 3, 0, 4, 5, 9, 7, 8],
+    [3, 6, 7, 4, 2, 0, 9, 5, 8, 1],
+    [5, 8, 6, 9, 7, 2, 0, 1, 3, 4],
+    [8, 9, 4, 5, 3, 6, 2, 0, 1, 7],
+    [9, 4, 3, 8, 6, 1, 7, 2, 0, 5],
+    [2, 5, 8, 1, 4, 3, 6, 7, 9, 0],
+];
+
+impl Damm {
+    fn interim(digits: &[u32]) -> u8 {
+        digits.iter().fold(0, |interim, &d| DAMM_TABLE[interim as usize][d as usize])
+    }
+}
+
+impl CheckDigit for Damm {
+    fn name(&self) -> &'static str {
+        "Damm"
+    }
+
+    fn validate(&self, input: &str) -> bool {
+        match digits(&clean(input)) {
+            Some(d) if d.len() >= 2 => Damm::interim(&d) == 0,
+            _ => false,
+        }
+    }
+
+    fn compute(&self, payload: &str) -> Option<String> {
+        let d = digits(&clean(payload))?;
+        Some(Damm::interim(&d).to_string())
+    }
+}
+
+/// ISBN-10: weights 10 down to 1, sum divisible by 11. A check value of 10 is written `X`.
+pub struct Isbn10;
+
+impl Isbn10 {
+    fn check(payload: &[u32]) -> char {
+        let sum: u32 = payload.iter().zip((2..=10).rev()).map(|(d, w)| d * w).sum();
+        match (11 - sum % 11) % 11 {
+            10 => 'X',
+            d => char::from_digit(d, 10).unwrap(),
+        }
+    }
+}
+
+impl CheckDigit for Isbn10 {
+    fn name(&self) -> &'static str {
+        "ISBN-10"
+    }
+
+    fn validate(&self, input: &str) -> bool {
+        let input = clean(input).to_ascii_uppercase();
+        match (input.len(), input.split_at_checked(9)) {
+            (10, Some((payload, check))) => match digits(payload) {
+                Some(d) => check.starts_with(Isbn10::check(&d)),
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn compute(&self, payload: &str) -> Option<String> {
+        let d = digits(&clean(payload)).filter(|d| d.len() == 9)?;
+        Some(Isbn10::check(&d).to_string())
+    }
+}
+
+/// Weights 1, 3, 1, 3, ... from the left; the scheme behind GTIN codes.
+fn gtin_validate(input: &str, len: usize, weights: &[u32]) -> bool {
+    match digits(&clean(input)) {
+        Some(d) if d.len() == len => weighted_mod10(&d[..len - 1], weights) == d[len - 1],
+        _ => false,
+    }
+}
+
+fn gtin_compute(payload: &str, len: usize, weights: &[u32]) -> Option<String> {
+    let d = digits(&clean(payload)).filter(|d| d.len() == len - 1)?;
+    Some(weighted_mod10(&d, weights).to_string())
+}
+
+/// EAN-13, which ISBN-13 is a subset of.
+pub struct Ean13;
+
+pub type Isbn13 = Ean13;
+
+impl CheckDigit for Ean13 {
+    fn name(&self) -> &'static str {
+        "EAN-13"
+    }
+
+    fn validate(&self, input: &str) -> bool {
+        gtin_validate(input, 13, &[1, 3])
+    }
+
+    fn compute(&self, payload: &str) -> Option<String> {
+        gtin_compute(payload, 13, &[1, 3])
+    }
+}
+
+/// UPC-A: 12 digits, weights 3, 1, 3, 1, ... from the left.
+pub struct UpcA;
+
+impl CheckDigit for UpcA {
+    fn name(&self) -> &'static str {
+        "UPC-A"
+    }
+
+    fn validate(&self, input: &str) -> bool {
+        gtin_validate(input, 12, &[3, 1])
+    }
+
+    fn compute(&self, payload: &str) -> Option<String> {
+        gtin_compute(payload, 12, &[3, 1])
+    }
+}
+
+/// IBAN (ISO 13616): two letters of country code, two check digits, then the
+/// account number (BBAN). The payload for `compute` is the country code
+/// followed by the BBAN; `complete` puts the check digits in between.
+pub struct Iban;
+
+impl Iban {
+    /// `account` rearranged as BBAN + country + check digits, read as one big
+    /// number with A = 10 ... Z = 35, modulo 97.
+    fn mod97(country: &str, check: &str, bban: &str) -> Option<u32> {
+        let mut remainder = 0;
+        for c in bban.chars().chain(country.chars()).chain(check.chars()) {
+            let value = c.to_digit(36)?;
+            remainder = if value < 10 { remainder * 10 + value } else { remainder * 100 + value } % 97;
+        }
+        Some(remainder)
+    }
+
+    /// Splits off the country code; `None` if it is not two letters or the BBAN is
+    /// not alphanumeric or has an impossible length.
+    fn parts(payload: &str) -> Option<(String, String)> {
+        let payload = clean(payload).to_ascii_uppercase();
+        let (country, bban) = payload.split_at_checked(2)?;
+        let valid = country.chars().all(|c| c.is_ascii_uppercase())
+            && bban.chars().all(|c| c.is_ascii_alphanumeric())
+            && (1..=30).contains(&bban.len());
+        valid.then(|| (country.to_string(), bban.to_string()))
+    }
+}
+
+impl CheckDigit for Iban {
+    fn name(&self) -> &'static str {
+        "IBAN"
+    }
+
+    fn validate(&self, input: &str) -> bool {
+        let input = clean(input).to_ascii_uppercase();
+        // Everything below slices by byte, which is only safe on ASCII.
+        if !input.is_ascii() {
+            return false;
+        }
+        let Some((head, bban)) = input.split_at_checked(4) else {
+            return false;
+        };
+        let (country, check) = head.split_at(2);
+        if !check.chars().all(|c| c.is_ascii_digit()) || Iban::parts(&format!("{country}{bban}")).is_none() {
+            return false;
+        }
+        Iban::mod97(country, check, bban) == Some(1)
+    }
+
+    fn compute(&self, payload: &str) -> Option<String> {
+        let (country, bban) = Iban::parts(payload)?;
+        let remainder = Iban::mod97(&country, "00", &bban)?;
+        Some(format!("{:02}", 98 - remainder))
+    }
+
+    fn complete(&self, payload: &str) -> Option<String> {
+        let check = self.compute(payload)?;
+        let (country, bban) = Iban::parts(payload)?;
+        Some(format!("{country}{check}{bban}"))
+    }
+}
+
+/// Every scheme in this module, e.g. to find which ones accept a number.
+pub fn all() -> [&'static dyn CheckDigit; 7] {
+    [&Luhn, &Verhoeff, &Damm, &Isbn10, &Ean13, &UpcA, &Iban]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Known-good numbers for each scheme.
+    fn corpus() -> Vec<(&'static dyn CheckDigit, Vec<&'static str>)> {
+        vec![
+            (&Luhn, vec!["79927398713", "4263 9826 4026 9299", "4539-3195-0343-6467"]),
+            (&Verhoeff, vec!["2363", "12340", "112944", "7053-8209-4233"]),
+            (&Damm, vec!["5724", "112946", "7053-8209-4236"]),
+            (&Isbn10, vec!["0-306-40615-2", "080442957X", "0 19 853453 1"]),
+            (&Ean13, vec!["978-0-306-40615-7", "4006381333931", "9780198534532"]),
+            (&UpcA, vec!["036000291452", "0 12345 67890 5", "042100005264"]),
+            (&Iban, vec!["GB82 WEST 1234 5698 7654 32", "DE89370400440532013000", "NL91ABNA0417164300"]),
+        ]
+    }
+
+    /// Positions of the digits that carry information, i.e. not separators and
+    /// not an IBAN's country letters.
+    fn digit_positions(number: &str) -> Vec<usize> {
+        number.char_indices().filter(|(_, c)| c.is_ascii_digit()).map(|(i, _)| i).collect()
+    }
+
+    /// Every variant of `number` with one digit replaced by another.
+    fn single_digit_errors(number: &str) -> Vec<String> {
+        let mut variants = Vec::new();
+        for i in digit_positions(number) {
+            for replacement in '0'..='9' {
+                if number[i..].starts_with(replacement) {
+                    continue;
+                }
+                let mut variant = number.to_string();
+                variant.replace_range(i..i + 1, &replacement.to_string());
+                variants.push(variant);
+            }
+        }
+        variants
+    }
+
+    /// Every variant with two neighbouring, different digits swapped. Only
+    /// digits that are next to each other once the separators are removed count.
+    fn adjacent_transpositions(number: &str) -> Vec<String> {
+        let positions = digit_positions(number);
+        let mut variants = Vec::new();
+        for pair in positions.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let between = &number[a + 1..b];
+            if !between.chars().all(|c| c == ' ' || c == '-') || number[a..=a] == number[b..=b] {
+                continue;
+            }
+            let mut bytes = number.as_bytes().to_vec();
+            bytes.swap(a, b);
+            variants.push(String::from_utf8(bytes).unwrap());
+        }
+        variants
+    }
+
+    #[test]
+    fn test_known_good_numbers() {
+        for (scheme, numbers) in corpus() {
+            for number in numbers {
+                assert!(scheme.validate(number), "{} rejects {number}", scheme.name());
+            }
+        }
+    }
+
+    #[test]
+    fn test_single_digit_errors_are_always_detected() {
+        for (scheme, numbers) in corpus() {
+            for number in numbers {
+                for variant in single_digit_errors(number) {
+                    assert!(!scheme.validate(&variant), "{} misses {number} -> {variant}", scheme.name());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_transposition_detection() {
+        for (scheme, numbers) in corpus() {
+            for number in numbers {
+                for variant in adjacent_transpositions(number) {
+                    let digits = |s: &str| clean(s).chars().filter(char::is_ascii_digit).collect::<Vec<_>>();
+                    let (before, after) = (digits(number), digits(&variant));
+                    let i = before.iter().zip(&after).position(|(a, b)| a != b).unwrap();
+                    let (a, b) = (before[i].to_digit(10).unwrap(), before[i + 1].to_digit(10).unwrap());
+
+                    let expected_miss = match scheme.name() {
+                        "Luhn" => a + b == 9 && a * b == 0,
+                        "EAN-13" | "UPC-A" => a.abs_diff(b) == 5,
+                        _ => false,
+                    };
+                    assert_eq!(
+                        scheme.validate(&variant),
+                        expected_miss,
+                        "{}: {number} -> {variant}",
+                        scheme.name()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_luhn_misses_09_transposition() {
+        assert!(Luhn.validate("5090"));
+        assert!(Luhn.validate("5900"));
+    }
+
+    #[test]
+    fn test_compute_matches_corpus() {
+        assert_eq!(Luhn.compute("7992739871"), Some("3".to_string()));
+        assert_eq!(Verhoeff.compute("236"), Some("3".to_string()));
+        assert_eq!(Damm.compute("572"), Some("4".to_string()));
+        assert_eq!(Isbn10.compute("080442957"), Some("X".to_string()));
+        let isbn13: Isbn13 = Ean13;
+        assert_eq!(isbn13.compute("978-0-306-40615"), Some("7".to_string()));
+        assert_eq!(UpcA.compute("03600029145"), Some("2".to_string()));
+        assert_eq!(Iban.compute("GB WEST 1234 5698 7654 32"), Some("82".to_string()));
+        assert_eq!(Iban.complete("DE370400440532013000"), Some("DE89370400440532013000".to_string()));
+        assert_eq!(Luhn.compute("7992\t739871"), Some("3".to_string()));
+        assert!(Iban.validate("GB82\tWEST\t1234\t5698\t7654\t32"));
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        for scheme in all() {
+            assert!(!scheme.validate(""), "{}", scheme.name());
+            assert!(!scheme.validate("abc"), "{}", scheme.name());
+            for non_ascii in ["AÄ12345678", "ÄA12345678", "GB8Ä WEST 1234", "12345678Ä", "４００６３８１３３３９３１", "日本"] {
+                assert!(!scheme.validate(non_ascii), "{} {non_ascii}", scheme.name());
+                assert_eq!(scheme.compute(non_ascii), None, "{} {non_ascii}", scheme.name());
+            }
+        }
+        assert_eq!(Isbn10.compute("12345678"), None);
+        assert_eq!(Ean13.compute("12345678901x"), None);
+        assert_eq!(Iban.compute("1234"), None);
+        assert!(!Iban.validate("GBxx WEST 1234 5698 7654 32"));
+        // X is only allowed as the ISBN-10 check character.
+        assert!(!Isbn10.validate("X804429570"));
+    }
+
+    #[test]
+    fn test_complete_round_trip() {
+        // Eleven digits each; "080442957" leads to the ISBN-10 check character X.
+        let payloads = [
+            "00000000000",
+            "99999999999",
+            "12345678901",
+            "10987654321",
+            "31415926535",
+            "27182818284",
+            "08044295700",
+            "03600029145",
+            "40063813339",
+            "55555555550",
+        ];
+        for payload in payloads {
+            for scheme in [&Luhn as &dyn CheckDigit, &Verhoeff, &Damm, &UpcA] {
+                let full = scheme.complete(payload).unwrap();
+                assert!(scheme.validate(&full), "{}: {full}", scheme.name());
+            }
+            assert!(Ean13.validate(&Ean13.complete(&format!("0{payload}")).unwrap()));
+            assert!(Isbn10.validate(&Isbn10.complete(&payload[..9]).unwrap()));
+            assert!(Iban.validate(&Iban.complete(&format!("CH{payload}")).unwrap()));
+        }
+    }
+}
@@ -1,35 +1,72 @@
+mod batch;
+mod card;
+mod check_digit;
+mod validation;
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::process;
+use std::time::Instant;
+
+use batch::{Format, Options};
+use card::CardNumber;
+use check_digit::CheckDigit;
+
+/// Spaces, dashes and tabs between the digits are ignored.
 pub fn luhn(cc_number: &str) -> bool {
-    let length = cc_number.len();
-    let mut totalBlankCnt = 0;
-    let mut sumLuhn = 0;
-    for (i, ch) in cc_number.chars().rev().enumerate() {
-        if ch == ' ' {
-            totalBlankCnt += 1;
+    let mut digit_cnt = 0;
+    let mut sum_luhn = 0;
+    for ch in cc_number.chars().rev() {
+        if is_separator(ch) {
             continue
         }
         let digit = ch.to_digit(10);
         match digit {
             Some(d) => {
-                sumLuhn += if (i-totalBlankCnt) %2 == 1 {
+                // Count digits rather than chars, so separators never shift the doubling.
+                sum_luhn += if digit_cnt %2 == 1 {
                     let doubled = d*2;
                     doubled/10 + doubled%10
                 } else {
                     d
-                }
+                };
+                digit_cnt += 1;
             }
             None => return false
         }
     }
 
-    if length - totalBlankCnt < 2 {
+    if digit_cnt < 2 {
         return false
     }
 
-    if sumLuhn%10 == 0 {
-        return true
-    }
+    sum_luhn%10 == 0
+}
 
-    return false
+/// The chars people put between groups of digits.
+pub fn is_separator(ch: char) -> bool {
+    matches!(ch, ' ' | '-' | '\t')
+}
+
+/// The digit to append to `partial` so that the result passes `luhn`.
+/// `None` if `partial` is empty or contains anything but ASCII digits; a lone
+/// check digit is too short for `luhn`.
+pub fn luhn_check_digit(partial: &str) -> Option<u32> {
+    if partial.is_empty() {
+        return None;
+    }
+    let mut sum = 0;
+    for (i, ch) in partial.chars().rev().enumerate() {
+        let d = ch.to_digit(10)?;
+        // The check digit will take position 0, so doubling starts right here.
+        sum += if i % 2 == 0 {
+            let doubled = d * 2;
+            doubled / 10 + doubled % 10
+        } else {
+            d
+        };
+    }
+    Some((10 - sum % 10) % 10)
 }
 
 #[test]
@@ -69,5 +106,157 @@ fn test_invalid_cc_number() {
     assert!(!luhn("8273 1232 7352 0569"));
 }
 
+#[test]
+fn test_other_separators() {
+    assert!(luhn("4263-9826-4026-9299"));
+    assert!(luhn("4263\t9826\t4026\t9299"));
+    assert!(luhn("3782-822463-10005"));
+    assert!(!luhn("4223-9826-4026-9299"));
+    // Mixed separators, including several in a row.
+    assert!(luhn("7992 - 7398\t713"));
+    assert!(!luhn("- -"));
+    assert!(!luhn("4263_9826_4026_9299"));
+}
+
+#[test]
+fn test_luhn_check_digit() {
+    assert_eq!(luhn_check_digit("426398264026929"), Some(9));
+    assert_eq!(luhn_check_digit("7992739871"), Some(3));
+    assert_eq!(luhn_check_digit("0"), Some(0));
+    assert_eq!(luhn_check_digit(""), None);
+    assert_eq!(luhn_check_digit("12 34"), None);
+    for partial in ["4", "45393195034364", "37828224631000", "601111111111111"] {
+        let digit = luhn_check_digit(partial).unwrap();
+        assert!(luhn(&format!("{partial}{digit}")), "{partial}{digit}");
+    }
+}
+
+const USAGE: &str = "usage: luhn [--format csv|json] [--threads N] [--invalid-only] [--output FILE] FILE|-";
+
+/// Command line arguments; `input` is `None` for stdin.
+struct Cli {
+    options: Options,
+    input: Option<String>,
+    output: Option<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<Cli, String> {
+    let mut options = Options::default();
+    let mut input = None;
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| format!("{flag} needs a value"));
+        match arg.as_str() {
+            "--format" => {
+                options.format = match value(arg)?.as_str() {
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format {other:?}, expected csv or json")),
+                }
+            }
+            "--threads" => {
+                options.threads = value(arg)?
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or("--threads needs a positive number")?
+            }
+            "--invalid-only" => options.invalid_only = true,
+            "--output" => output = Some(value(arg)?.clone()),
+            "-" if input.is_none() => input = Some("-".to_string()),
+            flag if flag.starts_with('-') => return Err(format!("unknown option {flag}")),
+            path if input.is_none() => input = Some(path.to_string()),
+            extra => return Err(format!("unexpected argument {extra:?}")),
+        }
+    }
+    let input = input.ok_or("no input given, use - for stdin")?;
+    Ok(Cli { options, input: Some(input).filter(|path| path != "-"), output })
+}
+
+fn run_batch(cli: &Cli) -> io::Result<batch::Summary> {
+    let output: Box<dyn io::Write> = match &cli.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let output = BufWriter::new(output);
+    match &cli.input {
+        Some(path) => batch::run(File::open(path)?, output, &cli.options),
+        None => batch::run(io::stdin(), output, &cli.options),
+    }
+}
+
 fn main() {
-}
\ No newline at end of file
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        println!("{USAGE}");
+        demo();
+        return;
+    }
+    let cli = match parse_args(&args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("error: {e}\n{USAGE}");
+            process::exit(2);
+        }
+    };
+    let start = Instant::now();
+    match run_batch(&cli) {
+        Ok(summary) => eprintln!(
+            "{} numbers, {} valid, {} invalid in {:.2?}",
+            summary.numbers,
+            summary.valid,
+            summary.invalid,
+            start.elapsed()
+        ),
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+fn demo() {
+    let partial = "426398264026929";
+    if let Some(digit) = luhn_check_digit(partial) {
+        println!("check digit for {partial}: {digit}");
+    }
+    for input in ["4263-9826-4026-9299", "3782 822463 10005", "4223 9826 4026 9299", "1234 x"] {
+        match input.parse::<CardNumber>() {
+            Ok(card) => println!(
+                "{input}: {} {} ({} digits, normalized {}, last four {})",
+                card.network(),
+                card,
+                card.digits().len(),
+                card.formatted(),
+                card.last_four(),
+            ),
+            Err(e) => println!("{input}: {e}"),
+        }
+    }
+    println!("{:?} accepts {:?} digits", card::Network::detect("5555"), card::Network::Mastercard.valid_lengths());
+    println!("{:?}", card::normalize("4111-1111 1111-1111"));
+
+    for input in ["79927398713", "978-0-306-40615-7", "GB82 WEST 1234 5698 7654 32", "5724"] {
+        let accepted: Vec<&str> = check_digit::all()
+            .iter()
+            .filter(|scheme| scheme.validate(input))
+            .map(|scheme| scheme.name())
+            .collect();
+        println!("{input}: valid {accepted:?}");
+    }
+    for input in ["foo", "7", "4223 9826 4026 9299"] {
+        if let Err(e) = validation::validate(input) {
+            println!("{input:?}: {e}");
+            for suggestion in validation::suggest_corrections(input).iter().take(3) {
+                println!("  did you mean {suggestion}?");
+            }
+        }
+    }
+    let isbn13: check_digit::Isbn13 = check_digit::Ean13;
+    println!(
+        "ISBN-13 978030640615 + {:?}, IBAN {:?}",
+        isbn13.compute("978030640615"),
+        check_digit::Iban.complete("DE370400440532013000"),
+    );
+}
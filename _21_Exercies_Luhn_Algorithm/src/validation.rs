@@ -0,0 +1,193 @@
+// Luhn validation that explains itself.
+// `luhn` says yes or no; `validate` says what is wrong, and for a checksum
+// mismatch `suggest_corrections` lists the numbers the user probably meant.
+
+use std::fmt;
+
+use crate::{is_separator, luhn_check_digit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LuhnError {
+    /// `position` counts chars in the input, starting at 0.
+    InvalidCharacter { ch: char, position: usize },
+    /// Luhn needs a payload and a check digit.
+    TooFewDigits { found: usize },
+    /// `found` is the last digit of the input, `expected` the one that would make it valid.
+    ChecksumMismatch { expected: u32, found: u32 },
+}
+
+impl fmt::Display for LuhnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LuhnError::InvalidCharacter { ch, position } => {
+                write!(f, "invalid character {ch:?} at position {position}")
+            }
+            LuhnError::TooFewDigits { found } => write!(f, "need at least 2 digits, found {found}"),
+            LuhnError::ChecksumMismatch { expected, found } => {
+                write!(f, "check digit is {found}, expected {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LuhnError {}
+
+/// The digits of `cc_number`, skipping separators like `luhn` does.
+fn digits(cc_number: &str) -> Result<String, LuhnError> {
+    let mut digits = String::with_capacity(cc_number.len());
+    for (position, ch) in cc_number.chars().enumerate() {
+        match ch {
+            '0'..='9' => digits.push(ch),
+            ch if is_separator(ch) => {}
+            _ => return Err(LuhnError::InvalidCharacter { ch, position }),
+        }
+    }
+    if digits.len() < 2 {
+        return Err(LuhnError::TooFewDigits { found: digits.len() });
+    }
+    Ok(digits)
+}
+
+/// Same rules as `luhn`, with the reason for a rejection.
+pub fn validate(cc_number: &str) -> Result<(), LuhnError> {
+    let digits = digits(cc_number)?;
+    let (payload, check) = digits.split_at(digits.len() - 1);
+    let expected = luhn_check_digit(payload).expect("only digits left");
+    let found = check.parse().expect("only digits left");
+    if expected != found {
+        return Err(LuhnError::ChecksumMismatch { expected, found });
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fix {
+    /// The digit at char `position` was mistyped.
+    Digit { position: usize, was: char, now: char },
+    /// The digits at char `first` and `second` were typed in the wrong order.
+    /// Separators may sit between them.
+    Swap { first: usize, second: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub fix: Fix,
+    /// The input with the fix applied, formatting kept.
+    pub corrected: String,
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.fix {
+            Fix::Digit { position, was, now } => {
+                write!(f, "{} (change {was} to {now} at position {position})", self.corrected)
+            }
+            Fix::Swap { first, second } => {
+                write!(f, "{} (swap positions {first} and {second})", self.corrected)
+            }
+        }
+    }
+}
+
+/// Valid numbers that differ from `cc_number` by one mistyped digit or one
+/// swap of neighbouring digits. Swaps come first since they are the more
+/// likely slip; each list is ordered by position. Empty if `cc_number` is
+/// already valid or fails for a reason other than the checksum.
+pub fn suggest_corrections(cc_number: &str) -> Vec<Suggestion> {
+    if !matches!(validate(cc_number), Err(LuhnError::ChecksumMismatch { .. })) {
+        return Vec::new();
+    }
+    let chars: Vec<char> = cc_number.chars().collect();
+    let positions: Vec<usize> = (0..chars.len()).filter(|&i| chars[i].is_ascii_digit()).collect();
+    let valid = |candidate: &[char]| validate(&candidate.iter().collect::<String>()).is_ok();
+
+    let mut suggestions = Vec::new();
+    for pair in positions.windows(2) {
+        let (first, second) = (pair[0], pair[1]);
+        if chars[first] == chars[second] {
+            continue;
+        }
+        let mut candidate = chars.clone();
+        candidate.swap(first, second);
+        if valid(&candidate) {
+            suggestions.push(Suggestion { fix: Fix::Swap { first, second }, corrected: candidate.iter().collect() });
+        }
+    }
+    for &position in &positions {
+        for now in '0'..='9' {
+            if now == chars[position] {
+                continue;
+            }
+            let mut candidate = chars.clone();
+            candidate[position] = now;
+            if valid(&candidate) {
+                let fix = Fix::Digit { position, was: chars[position], now };
+                suggestions.push(Suggestion { fix, corrected: candidate.iter().collect() });
+            }
+        }
+    }
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::luhn;
+
+    #[test]
+    fn test_validate_agrees_with_luhn() {
+        for input in ["", " ", "0", " 0 0 ", "foo", "4263 9826 4026 9299", "4223 9826 4026 9299", "7992 7398 713", "4263-9826-4026-9299", "4539\t3195"] {
+            assert_eq!(validate(input).is_ok(), luhn(input), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(validate("foo"), Err(LuhnError::InvalidCharacter { ch: 'f', position: 0 }));
+        assert_eq!(validate("4263 98x6"), Err(LuhnError::InvalidCharacter { ch: 'x', position: 7 }));
+        assert_eq!(validate("  "), Err(LuhnError::TooFewDigits { found: 0 }));
+        assert_eq!(validate(" 7 "), Err(LuhnError::TooFewDigits { found: 1 }));
+        assert_eq!(
+            validate("4263 9826 4026 9290"),
+            Err(LuhnError::ChecksumMismatch { expected: 9, found: 0 })
+        );
+        assert_eq!(
+            validate("4263 9826 4026 9290").unwrap_err().to_string(),
+            "check digit is 0, expected 9"
+        );
+    }
+
+    #[test]
+    fn test_suggests_mistyped_digit() {
+        // 4263 9826 4026 9299 with the third digit mistyped.
+        let suggestions = suggest_corrections("4223 9826 4026 9299");
+        let fix = Fix::Digit { position: 2, was: '2', now: '6' };
+        assert!(suggestions.contains(&Suggestion { fix, corrected: "4263 9826 4026 9299".to_string() }));
+        // Luhn catches every single-digit error, so each digit position offers exactly one fix.
+        let digit_fixes = suggestions.iter().filter(|s| matches!(s.fix, Fix::Digit { .. })).count();
+        assert_eq!(digit_fixes, 16);
+        assert!(suggestions.iter().all(|s| luhn(&s.corrected)));
+    }
+
+    #[test]
+    fn test_suggests_transpositions_first() {
+        // 4539 3195 0343 6467 with 6 and 7 swapped at the end.
+        let suggestions = suggest_corrections("4539 3195 0343 6476");
+        let swap = Suggestion { fix: Fix::Swap { first: 17, second: 18 }, corrected: "4539 3195 0343 6467".to_string() };
+        assert!(suggestions.contains(&swap));
+        let first_digit_fix = suggestions.iter().position(|s| matches!(s.fix, Fix::Digit { .. })).unwrap();
+        assert!(suggestions[first_digit_fix..].iter().all(|s| matches!(s.fix, Fix::Digit { .. })));
+
+        // Swaps across a space count as neighbours.
+        let suggestions = suggest_corrections("4539 3195 0346 3467");
+        let swap = suggestions.iter().find(|s| s.fix == Fix::Swap { first: 13, second: 15 }).unwrap();
+        assert_eq!(swap.to_string(), "4539 3195 0343 6467 (swap positions 13 and 15)");
+    }
+
+    #[test]
+    fn test_no_suggestions_unless_checksum_fails() {
+        assert!(suggest_corrections("4263 9826 4026 9299").is_empty());
+        assert!(suggest_corrections("foo").is_empty());
+        assert!(suggest_corrections("1").is_empty());
+    }
+}
@@ -1,3 +1,10 @@
+mod normalize;
+mod prefix_trie;
+mod router;
+
+use prefix_trie::PrefixTrie;
+use router::{Method, Params, Router};
+
 pub fn prefix_matches(prefix: &str, request_path: &str) -> bool {
     let mut request_segments = request_path.split('/');
 
@@ -11,38 +18,106 @@ pub fn prefix_matches(prefix: &str, request_path: &str) -> bool {
         }
     }
 
-    return true
+    true
+}
+
+/// `prefix_matches` answered by a trie holding just `prefix`.
+#[cfg(test)]
+fn trie_matches(prefix: &str, request_path: &str) -> bool {
+    let mut trie = PrefixTrie::new();
+    trie.insert(prefix, ());
+    trie.best_match(request_path).is_some()
 }
 
+#[cfg(test)]
+type Matcher = fn(&str, &str) -> bool;
+
+/// The `test_matches_*` cases run against each of these.
+#[cfg(test)]
+const IMPLEMENTATIONS: [(&str, Matcher); 2] = [("prefix_matches", prefix_matches), ("PrefixTrie", trie_matches)];
+
 #[test]
 fn test_matches_without_wildcard() {
-    assert!(prefix_matches("/v1/publishers", "/v1/publishers"));
-    assert!(prefix_matches("/v1/publishers", "/v1/publishers/abc-123"));
-    assert!(prefix_matches("/v1/publishers", "/v1/publishers/abc/books"));
+    for (name, prefix_matches) in IMPLEMENTATIONS {
+        assert!(prefix_matches("/v1/publishers", "/v1/publishers"), "{name}");
+        assert!(prefix_matches("/v1/publishers", "/v1/publishers/abc-123"), "{name}");
+        assert!(prefix_matches("/v1/publishers", "/v1/publishers/abc/books"), "{name}");
 
-    assert!(!prefix_matches("/v1/publishers", "/v1"));
-    assert!(!prefix_matches("/v1/publishers", "/v1/publishersBooks"));
-    assert!(!prefix_matches("/v1/publishers", "/v1/parent/publishers"));
+        assert!(!prefix_matches("/v1/publishers", "/v1"), "{name}");
+        assert!(!prefix_matches("/v1/publishers", "/v1/publishersBooks"), "{name}");
+        assert!(!prefix_matches("/v1/publishers", "/v1/parent/publishers"), "{name}");
+    }
 }
 
 #[test]
 fn test_matches_with_wildcard() {
-    assert!(prefix_matches(
-        "/v1/publishers/*/books",
-        "/v1/publishers/foo/books"
-    ));
-    assert!(prefix_matches(
-        "/v1/publishers/*/books",
-        "/v1/publishers/bar/books"
-    ));
-    assert!(prefix_matches(
-        "/v1/publishers/*/books",
-        "/v1/publishers/foo/books/book1"
-    ));
-
-    assert!(!prefix_matches("/v1/publishers/*/books", "/v1/publishers"));
-    assert!(!prefix_matches(
-        "/v1/publishers/*/books",
-        "/v1/publishers/foo/booksByAuthor"
-    ));
-}
\ No newline at end of file
+    for (name, prefix_matches) in IMPLEMENTATIONS {
+        assert!(prefix_matches(
+            "/v1/publishers/*/books",
+            "/v1/publishers/foo/books"
+        ), "{name}");
+        assert!(prefix_matches(
+            "/v1/publishers/*/books",
+            "/v1/publishers/bar/books"
+        ), "{name}");
+        assert!(prefix_matches(
+            "/v1/publishers/*/books",
+            "/v1/publishers/foo/books/book1"
+        ), "{name}");
+
+        assert!(!prefix_matches("/v1/publishers/*/books", "/v1/publishers"), "{name}");
+        assert!(!prefix_matches(
+            "/v1/publishers/*/books",
+            "/v1/publishers/foo/booksByAuthor"
+        ), "{name}");
+    }
+}
+
+fn main() {
+    println!("{}", prefix_matches("/v1/publishers/*/books", "/v1/publishers/foo/books/book1"));
+
+    let mut router: Router<fn(&Params) -> String> = Router::new();
+    println!("empty: {}", router.is_empty());
+    router.get("/v1/publishers", |p| format!("all publishers, params empty: {}", p.is_empty())).unwrap();
+    router.post("/v1/publishers/:id/books/:book", |p| format!("{:?}", p.iter().collect::<Vec<_>>())).unwrap();
+    router.get("/v1/publishers/:id/books", |p| format!("books of {}", p.get("id").unwrap())).unwrap();
+    router.get("/static/**", |p| format!("file {}", p.tail().unwrap())).unwrap();
+    if let Err(e) = router.get("/v1/publishers/:publisher/books", |_| String::new()) {
+        println!("{e}");
+    }
+    println!("{router:?}");
+    for (method, path) in [
+        (Method::Get, "/v1/publishers/abc-123/books"),
+        (Method::Head, "/static/css/site.css"),
+        (Method::Post, "/v1/publishers"),
+        (Method::Post, "/v1/publishers/abc/books/42"),
+        (Method::Get, "/v2"),
+    ] {
+        match router.route(method, path) {
+            Ok(m) => println!("{method} {path} -> {} via {}", (m.handler)(&m.params), m.pattern.as_str()),
+            Err(e) => println!("{method} {path} -> {e}"),
+        }
+    }
+    println!("{:?} {}", "DELETE".parse::<Method>(), router.len());
+
+    let trie: PrefixTrie<&str> =
+        [("/v1", "v1 api"), ("/v1/publishers", "publishers"), ("/v1/publishers/*/books", "books")].into_iter().collect();
+    let path = "/v1/publishers/foo/books/book1";
+    let all: Vec<&str> = trie.matches(path).iter().map(|m| m.prefix).collect();
+    println!("{} prefixes, empty: {}, {path} matches {all:?}", trie.len(), trie.is_empty());
+    if let Some(best) = trie.best_match(path) {
+        println!("best: {} -> {}", best.prefix, best.value);
+    }
+
+    let options = normalize::Options { ignore_case: true, trailing_slash: normalize::TrailingSlash::Keep };
+    for path in ["/V1//Publishers/./foo/%62ooks/", "/v1/publishers/../../etc/passwd", "/v1/%2e%2e%2fadmin"] {
+        match normalize::normalize(path, &options) {
+            Ok(normalized) => {
+                let best = trie.best_match(&normalized.to_string()).map(|m| m.prefix);
+                println!("{path} -> {normalized} ({} segments), best prefix {best:?}", normalized.segments().count());
+            }
+            Err(e) => println!("{path} -> {e}"),
+        }
+    }
+    println!("{:?}", normalize::prefix_matches("/v1/publishers", "/v1//publishers/", &normalize::Options::default()));
+}
@@ -0,0 +1,258 @@
+// Path normalization before matching.
+//
+// `prefix_matches` compares raw segments, so "/v1//publishers/", "/v1/./publishers"
+// and "/v1/%70ublishers" all miss "/v1/publishers". `normalize` turns a request
+// path into the segments a server would actually serve:
+//   - empty segments from duplicate slashes are dropped,
+//   - each segment is percent-decoded once, as UTF-8,
+//   - "." and ".." are resolved, also when they were written as %2E,
+//   - letters are lowercased if asked to.
+// Anything that could make two layers disagree about the path is rejected
+// instead of guessed at: ".." above the root, an encoded '/', backslashes,
+// control characters, bad escapes and invalid UTF-8.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrailingSlash {
+    /// "/a/" is the same path as "/a".
+    #[default]
+    Ignore,
+    /// "/a/" ends with an empty segment, so "/a/" matches the prefix "/a" but not the other way round.
+    Keep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Options {
+    pub ignore_case: bool,
+    pub trailing_slash: TrailingSlash,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathError {
+    NotAbsolute,
+    /// A '%' at byte `position` not followed by two hex digits.
+    InvalidPercentEncoding { position: usize },
+    /// The decoded bytes of a segment are not UTF-8, e.g. the overlong "%C0%AE".
+    InvalidUtf8,
+    /// A separator hidden in a segment ('/' as %2F, '\\') or a control character.
+    ForbiddenCharacter { ch: char },
+    /// ".." would leave the root.
+    EscapesRoot,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::NotAbsolute => write!(f, "path must start with '/'"),
+            PathError::InvalidPercentEncoding { position } => {
+                write!(f, "invalid percent-encoding at byte {position}")
+            }
+            PathError::InvalidUtf8 => write!(f, "decoded path is not valid UTF-8"),
+            PathError::ForbiddenCharacter { ch } => write!(f, "forbidden character {ch:?} in path"),
+            PathError::EscapesRoot => write!(f, "path escapes the root"),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedPath {
+    /// Decoded; with `TrailingSlash::Keep` a trailing slash is a last, empty segment.
+    segments: Vec<String>,
+}
+
+impl NormalizedPath {
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().map(String::as_str)
+    }
+
+    /// Whether every segment of `prefix` equals the segment at the same place
+    /// here, or is `*`. A trailing slash on `prefix` asks for one more segment
+    /// of any kind. Both must have been normalized with the same options.
+    pub fn starts_with(&self, prefix: &NormalizedPath) -> bool {
+        prefix.segments.len() <= self.segments.len()
+            && prefix.segments.iter().zip(&self.segments).all(|(p, s)| p == s || p == "*" || p.is_empty())
+    }
+}
+
+/// Re-encodes '%', so that normalizing the output again gives the same path.
+impl fmt::Display for NormalizedPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.segments.is_empty() {
+            return write!(f, "/");
+        }
+        for segment in &self.segments {
+            write!(f, "/{}", segment.replace('%', "%25"))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn normalize(path: &str, options: &Options) -> Result<NormalizedPath, PathError> {
+    let Some(rest) = path.strip_prefix('/') else {
+        return Err(PathError::NotAbsolute);
+    };
+    let mut segments: Vec<String> = Vec::new();
+    let mut trailing_slash = false;
+    let mut offset = 1;
+    for raw in rest.split('/') {
+        let segment = decode(raw, offset)?;
+        offset += raw.len() + 1;
+        // A path that ends in "/", "/." or "/.." names a directory.
+        trailing_slash = matches!(segment.as_str(), "" | "." | "..");
+        match segment.as_str() {
+            "" | "." => {}
+            ".." => {
+                segments.pop().ok_or(PathError::EscapesRoot)?;
+            }
+            _ if options.ignore_case => segments.push(segment.to_lowercase()),
+            _ => segments.push(segment),
+        }
+    }
+    if trailing_slash && options.trailing_slash == TrailingSlash::Keep && !segments.is_empty() {
+        segments.push(String::new());
+    }
+    Ok(NormalizedPath { segments })
+}
+
+/// Percent-decodes one segment. `offset` is where it starts in the whole path,
+/// for error positions.
+fn decode(raw: &str, offset: usize) -> Result<String, PathError> {
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let hex = |i: usize| bytes.get(i).and_then(|&b| char::from(b).to_digit(16));
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            match (hex(i + 1), hex(i + 2)) {
+                (Some(high), Some(low)) => decoded.push((high * 16 + low) as u8),
+                _ => return Err(PathError::InvalidPercentEncoding { position: offset + i }),
+            }
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    let segment = String::from_utf8(decoded).map_err(|_| PathError::InvalidUtf8)?;
+    if let Some(ch) = segment.chars().find(|&ch| ch == '/' || ch == '\\' || ch.is_control()) {
+        return Err(PathError::ForbiddenCharacter { ch });
+    }
+    Ok(segment)
+}
+
+/// `prefix_matches` on normalized paths. `prefix` goes through the same
+/// normalization, so it may use `*` segments and escapes too.
+pub fn prefix_matches(prefix: &str, request_path: &str, options: &Options) -> Result<bool, PathError> {
+    let prefix = normalize(prefix, options)?;
+    Ok(normalize(request_path, options)?.starts_with(&prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn norm(path: &str) -> Result<String, PathError> {
+        normalize(path, &Options::default()).map(|p| p.to_string())
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(norm("/"), Ok("/".to_string()));
+        assert_eq!(norm("//"), Ok("/".to_string()));
+        assert_eq!(norm("/v1//publishers/"), Ok("/v1/publishers".to_string()));
+        assert_eq!(norm("/v1/./publishers/abc/../"), Ok("/v1/publishers".to_string()));
+        assert_eq!(norm("/v1/%70ublishers/a%20b"), Ok("/v1/publishers/a b".to_string()));
+        assert_eq!(norm("/caf%C3%A9/%E2%9C%93"), Ok("/café/✓".to_string()));
+        assert_eq!(norm("/a/%2e/b/%2E%2E"), Ok("/a".to_string()));
+        // Three dots are just a name.
+        assert_eq!(norm("/.../a"), Ok("/.../a".to_string()));
+        assert_eq!(norm("v1/publishers"), Err(PathError::NotAbsolute));
+        assert_eq!(norm(""), Err(PathError::NotAbsolute));
+    }
+
+    #[test]
+    fn test_options() {
+        let keep = Options { trailing_slash: TrailingSlash::Keep, ..Options::default() };
+        let keep_norm = |path| normalize(path, &keep).unwrap().to_string();
+        assert_eq!(keep_norm("/v1/publishers/"), "/v1/publishers/");
+        assert_eq!(keep_norm("/v1/publishers//"), "/v1/publishers/");
+        assert_eq!(keep_norm("/v1/publishers/abc/.."), "/v1/publishers/");
+        assert_eq!(keep_norm("/v1/publishers"), "/v1/publishers");
+        assert_eq!(keep_norm("/"), "/");
+
+        let ignore_case = Options { ignore_case: true, ..Options::default() };
+        assert_eq!(normalize("/V1/Publishers/%C3%89", &ignore_case).unwrap().to_string(), "/v1/publishers/é");
+        let segments: Vec<String> = normalize("/A/b", &ignore_case).unwrap().segments().map(str::to_string).collect();
+        assert_eq!(segments, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_prefix_matches() {
+        let default = Options::default();
+        assert_eq!(prefix_matches("/v1/publishers", "/v1//publishers/", &default), Ok(true));
+        assert_eq!(prefix_matches("/v1/publishers/*/books", "/v1/publishers/%66oo/./books/1", &default), Ok(true));
+        assert_eq!(prefix_matches("/v1/publishers", "/v1/publishersBooks", &default), Ok(false));
+        assert_eq!(prefix_matches("/v1/publishers", "/V1/PUBLISHERS", &default), Ok(false));
+        let ignore_case = Options { ignore_case: true, ..default };
+        assert_eq!(prefix_matches("/v1/publishers", "/V1/PUBLISHERS", &ignore_case), Ok(true));
+
+        let keep = Options { trailing_slash: TrailingSlash::Keep, ..default };
+        assert_eq!(prefix_matches("/v1/publishers", "/v1/publishers/", &keep), Ok(true));
+        assert_eq!(prefix_matches("/v1/publishers/", "/v1/publishers", &keep), Ok(false));
+        assert_eq!(prefix_matches("/v1/publishers/", "/v1/publishers/abc", &keep), Ok(true));
+        assert_eq!(prefix_matches("/v1/publishers/", "/v1/publishers", &default), Ok(true));
+    }
+
+    #[test]
+    fn test_rejects_traversal() {
+        for path in ["/..", "/../etc/passwd", "/a/../../etc/passwd", "/a/./../..", "/%2e%2e/etc", "/%2E%2e/", "/a/b/../../%2e."] {
+            assert_eq!(norm(path), Err(PathError::EscapesRoot), "{path}");
+        }
+        // Going back down after ".." is fine as long as the root is never left.
+        assert_eq!(norm("/public/../admin"), Ok("/admin".to_string()));
+        assert_eq!(prefix_matches("/admin", "/public/../admin/users", &Options::default()), Ok(true));
+    }
+
+    #[test]
+    fn test_rejects_hidden_separators() {
+        let cases = [
+            ("/..%2f..%2fetc/passwd", PathError::ForbiddenCharacter { ch: '/' }),
+            ("/a%2Fb", PathError::ForbiddenCharacter { ch: '/' }),
+            ("/..\\..\\windows", PathError::ForbiddenCharacter { ch: '\\' }),
+            ("/..%5c..%5cwindows", PathError::ForbiddenCharacter { ch: '\\' }),
+            ("/etc/passwd%00.png", PathError::ForbiddenCharacter { ch: '\0' }),
+            ("/a%0d%0aSet-Cookie:x", PathError::ForbiddenCharacter { ch: '\r' }),
+        ];
+        for (path, error) in cases {
+            assert_eq!(norm(path), Err(error), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_bad_encoding() {
+        assert_eq!(norm("/a/%"), Err(PathError::InvalidPercentEncoding { position: 3 }));
+        assert_eq!(norm("/a/%4"), Err(PathError::InvalidPercentEncoding { position: 3 }));
+        assert_eq!(norm("/abc/%zz"), Err(PathError::InvalidPercentEncoding { position: 5 }));
+        assert_eq!(norm("/%+1"), Err(PathError::InvalidPercentEncoding { position: 1 }));
+        // Overlong encodings of '.' and '/' are not UTF-8.
+        assert_eq!(norm("/%c0%ae%c0%ae/etc"), Err(PathError::InvalidUtf8));
+        assert_eq!(norm("/%c0%af"), Err(PathError::InvalidUtf8));
+        assert_eq!(norm("/%e2%82"), Err(PathError::InvalidUtf8));
+    }
+
+    #[test]
+    fn test_decodes_only_once() {
+        // "%252e%252e" is the literal name "%2e%2e", not "..".
+        let path = normalize("/%252e%252e/etc", &Options::default()).unwrap();
+        assert_eq!(path.segments().collect::<Vec<_>>(), ["%2e%2e", "etc"]);
+        assert_eq!(path.to_string(), "/%252e%252e/etc");
+        for input in ["/%252e%252e/etc", "/a%25/b", "/v1//x/../y/", "/%41%2541"] {
+            let once = normalize(input, &Options::default()).unwrap();
+            let twice = normalize(&once.to_string(), &Options::default()).unwrap();
+            assert_eq!(once, twice, "{input}");
+        }
+    }
+}
@@ -0,0 +1,269 @@
+// Many `prefix_matches` prefixes compiled into one segment trie.
+//
+// Each edge is one '/'-separated segment, either a literal or `*`. Looking up
+// a path walks its segments once, following the literal edge and the `*` edge
+// at each level, so the cost depends on the path depth and not on how many
+// prefixes were inserted. A prefix matches exactly when
+// `prefix_matches(prefix, path)` would say so.
+
+use std::collections::HashMap;
+
+struct Node<V> {
+    literals: HashMap<String, Node<V>>,
+    wildcard: Option<Box<Node<V>>>,
+    /// Set if an inserted prefix ends here.
+    entry: Option<(String, V)>,
+}
+
+impl<V> Default for Node<V> {
+    fn default() -> Self {
+        Node { literals: HashMap::new(), wildcard: None, entry: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'t, V> {
+    pub prefix: &'t str,
+    pub value: &'t V,
+}
+
+pub struct PrefixTrie<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+impl<V> Default for PrefixTrie<V> {
+    fn default() -> Self {
+        PrefixTrie { root: Node::default(), len: 0 }
+    }
+}
+
+impl<V> PrefixTrie<V> {
+    pub fn new() -> Self {
+        PrefixTrie::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds `prefix`, returning the value it replaced if it was already there.
+    pub fn insert(&mut self, prefix: &str, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for segment in prefix.split('/') {
+            node = if segment == "*" {
+                node.wildcard.get_or_insert_with(Box::default)
+            } else {
+                node.literals.entry(segment.to_string()).or_default()
+            };
+        }
+        let old = node.entry.replace((prefix.to_string(), value)).map(|(_, value)| value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Every prefix that matches `path`, shorter prefixes first. Prefixes of
+    /// the same length come in the order `best_match` prefers them.
+    pub fn matches(&self, path: &str) -> Vec<Match<'_, V>> {
+        let mut found = Vec::new();
+        self.walk(path, |depth, m| found.push((depth, m)));
+        // Stable, so the literal-first order of the walk survives within a depth.
+        found.sort_by_key(|&(depth, _)| depth);
+        found.into_iter().map(|(_, m)| m).collect()
+    }
+
+    /// The matching prefix with the most segments. Between prefixes of the
+    /// same length, the one with a literal at the first segment where they
+    /// differ wins over the one with `*`.
+    pub fn best_match(&self, path: &str) -> Option<Match<'_, V>> {
+        let mut best: Option<(usize, Match<'_, V>)> = None;
+        self.walk(path, |depth, m| {
+            if best.as_ref().is_none_or(|&(best_depth, _)| depth > best_depth) {
+                best = Some((depth, m));
+            }
+        });
+        best.map(|(_, m)| m)
+    }
+
+    /// Calls `visit` with the segment count and entry of each matching prefix,
+    /// trying literal edges before `*` at every level.
+    fn walk<'t>(&'t self, path: &str, mut visit: impl FnMut(usize, Match<'t, V>)) {
+        let segments: Vec<&str> = path.split('/').collect();
+        let mut stack = vec![(&self.root, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            if let Some((prefix, value)) = &node.entry {
+                visit(depth, Match { prefix, value });
+            }
+            let Some(&segment) = segments.get(depth) else {
+                continue;
+            };
+            // Pushed in reverse, so the literal edge is explored first.
+            if let Some(wildcard) = &node.wildcard {
+                stack.push((wildcard, depth + 1));
+            }
+            if let Some(literal) = node.literals.get(segment) {
+                stack.push((literal, depth + 1));
+            }
+        }
+    }
+}
+
+impl<S: AsRef<str>, V> FromIterator<(S, V)> for PrefixTrie<V> {
+    fn from_iter<I: IntoIterator<Item = (S, V)>>(iter: I) -> Self {
+        let mut trie = PrefixTrie::new();
+        for (prefix, value) in iter {
+            trie.insert(prefix.as_ref(), value);
+        }
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prefix_matches;
+    use std::time::Instant;
+
+    fn prefixes(trie: &PrefixTrie<usize>, path: &str) -> Vec<String> {
+        trie.matches(path).iter().map(|m| m.prefix.to_string()).collect()
+    }
+
+    #[test]
+    fn test_all_and_best_matches() {
+        let trie: PrefixTrie<usize> = [
+            "/v1",
+            "/v1/publishers",
+            "/v1/publishers/*/books",
+            "/v1/publishers/abc/books",
+            "/v1/publishers/abc/*",
+            "/v2",
+        ]
+        .iter()
+        .copied()
+        .zip(0..)
+        .collect();
+        assert_eq!(trie.len(), 6);
+
+        assert_eq!(
+            prefixes(&trie, "/v1/publishers/abc/books/7"),
+            [
+                "/v1",
+                "/v1/publishers",
+                "/v1/publishers/abc/books",
+                "/v1/publishers/abc/*",
+                "/v1/publishers/*/books",
+            ]
+        );
+        let best = trie.best_match("/v1/publishers/abc/books/7").unwrap();
+        assert_eq!((best.prefix, *best.value), ("/v1/publishers/abc/books", 3));
+        assert_eq!(trie.best_match("/v1/publishers/xyz/books").unwrap().prefix, "/v1/publishers/*/books");
+        assert_eq!(trie.best_match("/v1/publishers/abc/authors").unwrap().prefix, "/v1/publishers/abc/*");
+        assert_eq!(trie.best_match("/v1/publishersBooks").unwrap().prefix, "/v1");
+        assert!(trie.best_match("/v3").is_none());
+        assert!(trie.matches("").is_empty());
+    }
+
+    #[test]
+    fn test_insert_replaces() {
+        let mut trie = PrefixTrie::new();
+        assert_eq!(trie.insert("/a/*", 1), None);
+        assert_eq!(trie.insert("/a/*", 2), Some(1));
+        assert_eq!(trie.len(), 1);
+        assert_eq!(*trie.best_match("/a/b").unwrap().value, 2);
+        assert!(PrefixTrie::<()>::new().is_empty());
+    }
+
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as usize
+        }
+    }
+
+    /// A path or prefix of up to five segments from a small alphabet, so that
+    /// random prefixes and paths overlap often.
+    fn random_path(rng: &mut XorShift, wildcards: bool) -> String {
+        let words = ["", "v1", "v2", "publishers", "books", "abc", "*"];
+        let choices = if wildcards { words.len() } else { words.len() - 1 };
+        let mut path = String::new();
+        for _ in 0..rng.next(5) + 1 {
+            path.push('/');
+            path.push_str(words[rng.next(choices)]);
+        }
+        path
+    }
+
+    #[test]
+    fn test_agrees_with_prefix_matches() {
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        for _ in 0..200 {
+            let patterns: Vec<String> = (0..rng.next(30)).map(|_| random_path(&mut rng, true)).collect();
+            let trie: PrefixTrie<usize> = patterns.iter().zip(0..).collect();
+            for _ in 0..20 {
+                let path = random_path(&mut rng, false);
+                let mut expected: Vec<&str> =
+                    patterns.iter().filter(|p| prefix_matches(p, &path)).map(|p| p.as_str()).collect();
+                expected.sort();
+                expected.dedup();
+                let mut found: Vec<&str> = trie.matches(&path).iter().map(|m| m.prefix).collect();
+                found.sort();
+                assert_eq!(found, expected, "{path} against {patterns:?}");
+
+                let longest = expected.iter().map(|p| p.split('/').count()).max();
+                let best = trie.best_match(&path).map(|m| m.prefix.split('/').count());
+                assert_eq!(best, longest, "{path}");
+            }
+        }
+    }
+
+    /// `cargo test --release bench_trie -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_trie_against_prefix_matches() {
+        let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+        let patterns: Vec<String> = (0..5_000)
+            .map(|i| {
+                let wildcard = if rng.next(4) == 0 { "*".to_string() } else { format!("p{}", rng.next(500)) };
+                format!("/v{}/{}/{wildcard}/r{i}", rng.next(3), ["publishers", "authors", "books"][rng.next(3)])
+            })
+            .collect();
+        let paths: Vec<String> = (0..10_000)
+            .map(|_| {
+                let pattern = &patterns[rng.next(patterns.len())];
+                format!("{}/tail/{}", pattern.replace('*', "x"), rng.next(100))
+            })
+            .collect();
+
+        let start = Instant::now();
+        let trie: PrefixTrie<usize> = patterns.iter().zip(0..).collect();
+        let compile = start.elapsed();
+
+        let start = Instant::now();
+        let linear: usize = paths.iter().map(|path| patterns.iter().filter(|p| prefix_matches(p, path)).count()).sum();
+        let linear_time = start.elapsed();
+
+        let start = Instant::now();
+        let all: usize = paths.iter().map(|path| trie.matches(path).len()).sum();
+        let all_time = start.elapsed();
+
+        let start = Instant::now();
+        let best = paths.iter().filter(|path| trie.best_match(path).is_some()).count();
+        let best_time = start.elapsed();
+
+        assert_eq!(all, linear);
+        assert_eq!(best, paths.len());
+        println!("{} prefixes, {} paths", patterns.len(), paths.len());
+        println!("prefix_matches over every prefix: {linear_time:?}");
+        println!("trie compile: {compile:?}, all matches: {all_time:?}, best match: {best_time:?}");
+    }
+}
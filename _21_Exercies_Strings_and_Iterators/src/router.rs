@@ -0,0 +1,479 @@
+// An HTTP-style router on top of the segment matching in `prefix_matches`.
+//
+// Patterns are split on '/' like `prefix_matches` does, and each segment is
+// one of:
+//   literal   matches itself
+//   :name     matches any one non-empty segment and captures it as `name`
+//   *         matches any one segment, even an empty one, without capturing it
+//   **        only last; matches the rest of the path, possibly nothing
+// A pattern matches whole paths, so `prefix_matches(p, path)` is the same as
+// matching `path` against `p/**`.
+//
+// When several routes match, the one whose first differing segment is the
+// most specific wins: literal, then :param, then *, then **. Two routes with
+// the same shape for the same method are rejected when they are added, so the
+// winner never depends on the order of registration.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Method {
+    Get,
+    Head,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Options,
+}
+
+impl Method {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Options => "OPTIONS",
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Method {
+    type Err = RouterError;
+
+    /// Method names are case-sensitive, as in HTTP.
+    fn from_str(s: &str) -> Result<Self, RouterError> {
+        let methods = [
+            Method::Get,
+            Method::Head,
+            Method::Post,
+            Method::Put,
+            Method::Patch,
+            Method::Delete,
+            Method::Options,
+        ];
+        methods
+            .into_iter()
+            .find(|method| method.as_str() == s)
+            .ok_or_else(|| RouterError::UnknownMethod(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouterError {
+    UnknownMethod(String),
+    InvalidPattern { pattern: String, reason: &'static str },
+    /// `pattern` matches exactly the same paths as `existing`.
+    Conflict { method: Method, pattern: String, existing: String },
+}
+
+impl fmt::Display for RouterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RouterError::UnknownMethod(method) => write!(f, "unknown method {method:?}"),
+            RouterError::InvalidPattern { pattern, reason } => {
+                write!(f, "invalid pattern {pattern:?}: {reason}")
+            }
+            RouterError::Conflict { method, pattern, existing } => {
+                write!(f, "{method} {pattern} conflicts with {method} {existing}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RouterError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteError {
+    NotFound,
+    /// Some route matches the path, but not for this method.
+    MethodNotAllowed { allowed: Vec<Method> },
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RouteError::NotFound => write!(f, "not found"),
+            RouteError::MethodNotAllowed { allowed } => {
+                let allowed: Vec<&str> = allowed.iter().map(|method| method.as_str()).collect();
+                write!(f, "method not allowed, allowed: {}", allowed.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Any,
+    CatchAll,
+}
+
+impl Segment {
+    /// Lower is more specific.
+    fn rank(&self) -> u8 {
+        match self {
+            Segment::Literal(_) => 0,
+            Segment::Param(_) => 1,
+            Segment::Any => 2,
+            Segment::CatchAll => 3,
+        }
+    }
+
+    /// Whether both segments match the same path segments. Param names don't matter.
+    fn same_shape(&self, other: &Segment) -> bool {
+        match (self, other) {
+            (Segment::Literal(a), Segment::Literal(b)) => a == b,
+            _ => self.rank() == other.rank() && !matches!(self, Segment::Literal(_)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl Pattern {
+    pub fn parse(pattern: &str) -> Result<Pattern, RouterError> {
+        let invalid = |reason| RouterError::InvalidPattern { pattern: pattern.to_string(), reason };
+        if !pattern.starts_with('/') {
+            return Err(invalid("must start with '/'"));
+        }
+        let parts: Vec<&str> = pattern.split('/').collect();
+        let mut segments = Vec::with_capacity(parts.len());
+        for (i, part) in parts.iter().enumerate() {
+            let segment = match *part {
+                "**" if i + 1 < parts.len() => return Err(invalid("'**' must be the last segment")),
+                "**" => Segment::CatchAll,
+                "*" => Segment::Any,
+                ":" => return Err(invalid("parameter without a name")),
+                _ => match part.strip_prefix(':') {
+                    Some(name) if segments.contains(&Segment::Param(name.to_string())) => {
+                        return Err(invalid("parameter name used twice"))
+                    }
+                    Some(name) => Segment::Param(name.to_string()),
+                    None => Segment::Literal(part.to_string()),
+                },
+            };
+            segments.push(segment);
+        }
+        Ok(Pattern { source: pattern.to_string(), segments })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// The captured params if `path` matches.
+    pub fn matches(&self, path: &str) -> Option<Params> {
+        let mut params = Params::default();
+        let mut rest = Some(path);
+        for segment in &self.segments {
+            if *segment == Segment::CatchAll {
+                // `rest` is what follows the last matched '/'; the path "/a" against "/a/**" leaves nothing.
+                params.0.push(("**".to_string(), rest.unwrap_or("").to_string()));
+                return Some(params);
+            }
+            let (path_segment, tail) = match rest?.split_once('/') {
+                Some((head, tail)) => (head, Some(tail)),
+                None => (rest?, None),
+            };
+            match segment {
+                Segment::Literal(literal) if literal != path_segment => return None,
+                Segment::Param(_) if path_segment.is_empty() => return None,
+                Segment::Param(name) => params.0.push((name.clone(), path_segment.to_string())),
+                _ => {}
+            }
+            rest = tail;
+        }
+        rest.is_none().then_some(params)
+    }
+
+    fn same_shape(&self, other: &Pattern) -> bool {
+        self.segments.len() == other.segments.len()
+            && self.segments.iter().zip(&other.segments).all(|(a, b)| a.same_shape(b))
+    }
+
+    fn specificity(&self) -> impl Iterator<Item = u8> + '_ {
+        self.segments.iter().map(Segment::rank)
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// Values captured from the path, in pattern order. A `**` tail is stored under `"**"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params(Vec<(String, String)>);
+
+impl Params {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// What `**` matched, without the leading '/'.
+    pub fn tail(&self) -> Option<&str> {
+        self.get("**")
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub struct Match<'r, H> {
+    pub handler: &'r H,
+    pub pattern: &'r Pattern,
+    pub params: Params,
+}
+
+struct Route<H> {
+    method: Method,
+    pattern: Pattern,
+    handler: H,
+}
+
+pub struct Router<H> {
+    routes: Vec<Route<H>>,
+}
+
+impl<H> Default for Router<H> {
+    fn default() -> Self {
+        Router { routes: Vec::new() }
+    }
+}
+
+impl<H> Router<H> {
+    pub fn new() -> Self {
+        Router::default()
+    }
+
+    pub fn add(&mut self, method: Method, pattern: &str, handler: H) -> Result<(), RouterError> {
+        let pattern = Pattern::parse(pattern)?;
+        if let Some(existing) = self.routes.iter().find(|r| r.method == method && r.pattern.same_shape(&pattern)) {
+            return Err(RouterError::Conflict {
+                method,
+                pattern: pattern.source,
+                existing: existing.pattern.source.clone(),
+            });
+        }
+        self.routes.push(Route { method, pattern, handler });
+        Ok(())
+    }
+
+    pub fn get(&mut self, pattern: &str, handler: H) -> Result<(), RouterError> {
+        self.add(Method::Get, pattern, handler)
+    }
+
+    pub fn post(&mut self, pattern: &str, handler: H) -> Result<(), RouterError> {
+        self.add(Method::Post, pattern, handler)
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// The most specific route for `method` that matches `path`.
+    /// A HEAD request falls back to the GET route.
+    pub fn route(&self, method: Method, path: &str) -> Result<Match<'_, H>, RouteError> {
+        let mut matching: Vec<(&Route<H>, Params)> = self
+            .routes
+            .iter()
+            .filter_map(|route| route.pattern.matches(path).map(|params| (route, params)))
+            .collect();
+        if matching.is_empty() {
+            return Err(RouteError::NotFound);
+        }
+        // Most specific first; `add` made sure no two routes for a method tie.
+        matching.sort_by(|(a, _), (b, _)| a.pattern.specificity().cmp(b.pattern.specificity()));
+
+        let found = matching.iter().position(|(route, _)| route.method == method).or_else(|| {
+            let head = method == Method::Head;
+            matching.iter().position(|(route, _)| head && route.method == Method::Get)
+        });
+        match found {
+            Some(index) => {
+                let (route, params) = matching.swap_remove(index);
+                Ok(Match { handler: &route.handler, pattern: &route.pattern, params })
+            }
+            None => {
+                let mut allowed: Vec<Method> = matching.iter().map(|(route, _)| route.method).collect();
+                if allowed.contains(&Method::Get) {
+                    allowed.push(Method::Head);
+                }
+                allowed.sort();
+                allowed.dedup();
+                Err(RouteError::MethodNotAllowed { allowed })
+            }
+        }
+    }
+}
+
+impl<H> fmt::Debug for Router<H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let routes: Vec<String> = self.routes.iter().map(|r| format!("{} {}", r.method, r.pattern)).collect();
+        f.debug_struct("Router").field("routes", &routes).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prefix_matches;
+
+    fn router() -> Router<&'static str> {
+        let mut router = Router::new();
+        router.get("/v1/publishers", "list publishers").unwrap();
+        router.post("/v1/publishers", "create publisher").unwrap();
+        router.get("/v1/publishers/:id", "get publisher").unwrap();
+        router.get("/v1/publishers/new", "new publisher form").unwrap();
+        router.get("/v1/publishers/:id/books", "list books").unwrap();
+        router.get("/v1/publishers/:publisher/books/:book", "get book").unwrap();
+        router.get("/v1/publishers/*/books/latest", "latest book").unwrap();
+        router.get("/static/**", "static files").unwrap();
+        router.add(Method::Delete, "/v1/publishers/:id", "delete publisher").unwrap();
+        router
+    }
+
+    #[test]
+    fn test_routes_and_params() {
+        let router = router();
+        let m = router.route(Method::Get, "/v1/publishers/abc-123/books").unwrap();
+        assert_eq!(*m.handler, "list books");
+        assert_eq!(m.pattern.as_str(), "/v1/publishers/:id/books");
+        assert_eq!(m.params.get("id"), Some("abc-123"));
+
+        let m = router.route(Method::Get, "/v1/publishers/p1/books/b2").unwrap();
+        assert_eq!(*m.handler, "get book");
+        assert_eq!(m.params.iter().collect::<Vec<_>>(), [("publisher", "p1"), ("book", "b2")]);
+
+        assert_eq!(*router.route(Method::Post, "/v1/publishers").unwrap().handler, "create publisher");
+        assert_eq!(*router.route(Method::Delete, "/v1/publishers/7").unwrap().handler, "delete publisher");
+        assert!(router.route(Method::Get, "/v1/publishers").unwrap().params.is_empty());
+    }
+
+    #[test]
+    fn test_priority() {
+        let router = router();
+        // Literal beats :id.
+        assert_eq!(*router.route(Method::Get, "/v1/publishers/new").unwrap().handler, "new publisher form");
+        assert_eq!(*router.route(Method::Get, "/v1/publishers/old").unwrap().handler, "get publisher");
+        // :publisher beats * at the first segment where the two differ.
+        assert_eq!(*router.route(Method::Get, "/v1/publishers/x/books/latest").unwrap().handler, "get book");
+
+        let mut router = Router::new();
+        router.get("/files/**", "tail").unwrap();
+        router.get("/files/:name", "param").unwrap();
+        router.get("/files/readme", "literal").unwrap();
+        router.get("/**", "fallback").unwrap();
+        assert_eq!(*router.route(Method::Get, "/files/readme").unwrap().handler, "literal");
+        assert_eq!(*router.route(Method::Get, "/files/a").unwrap().handler, "param");
+        assert_eq!(*router.route(Method::Get, "/files/a/b").unwrap().handler, "tail");
+        assert_eq!(*router.route(Method::Get, "/other").unwrap().handler, "fallback");
+
+        // :param before * before **.
+        router.get("/files/*", "any").unwrap();
+        assert_eq!(*router.route(Method::Get, "/files/a").unwrap().handler, "param");
+        assert_eq!(*router.route(Method::Get, "/files/").unwrap().handler, "any");
+        router.get("/dir/*", "any").unwrap();
+        router.get("/dir/**", "tail").unwrap();
+        assert_eq!(*router.route(Method::Get, "/dir/a").unwrap().handler, "any");
+    }
+
+    #[test]
+    fn test_params_are_never_empty() {
+        let router = router();
+        assert_eq!(router.route(Method::Get, "/v1/publishers/").unwrap_err(), RouteError::NotFound);
+        assert_eq!(router.route(Method::Get, "/v1/publishers//books").unwrap_err(), RouteError::NotFound);
+        let m = router.route(Method::Get, "/v1/publishers//books/latest").unwrap();
+        assert_eq!(*m.handler, "latest book");
+    }
+
+    #[test]
+    fn test_catch_all() {
+        let router = router();
+        let m = router.route(Method::Get, "/static/css/site.css").unwrap();
+        assert_eq!((*m.handler, m.params.tail()), ("static files", Some("css/site.css")));
+        assert_eq!(router.route(Method::Get, "/static").unwrap().params.tail(), Some(""));
+        assert_eq!(router.route(Method::Get, "/static/").unwrap().params.tail(), Some(""));
+        assert_eq!(router.route(Method::Get, "/staticfiles").unwrap_err(), RouteError::NotFound);
+    }
+
+    #[test]
+    fn test_method_errors() {
+        let router = router();
+        assert_eq!(
+            router.route(Method::Put, "/v1/publishers/7").unwrap_err(),
+            RouteError::MethodNotAllowed { allowed: vec![Method::Get, Method::Head, Method::Delete] }
+        );
+        assert_eq!(router.route(Method::Get, "/v2").unwrap_err(), RouteError::NotFound);
+        assert_eq!(*router.route(Method::Head, "/v1/publishers").unwrap().handler, "list publishers");
+        assert_eq!("PATCH".parse(), Ok(Method::Patch));
+        assert_eq!("get".parse::<Method>(), Err(RouterError::UnknownMethod("get".to_string())));
+    }
+
+    #[test]
+    fn test_invalid_patterns_and_conflicts() {
+        let mut router = router();
+        assert!(matches!(router.get("v1", ""), Err(RouterError::InvalidPattern { .. })));
+        assert!(matches!(router.get("/a/**/b", ""), Err(RouterError::InvalidPattern { .. })));
+        assert!(matches!(router.get("/a/:", ""), Err(RouterError::InvalidPattern { .. })));
+        assert!(matches!(router.get("/a/:x/:x", ""), Err(RouterError::InvalidPattern { .. })));
+        assert_eq!(
+            router.get("/v1/publishers/:name", "").unwrap_err().to_string(),
+            "GET /v1/publishers/:name conflicts with GET /v1/publishers/:id"
+        );
+        // * also matches the empty segment, so it doesn't clash with :id.
+        assert!(router.get("/v1/publishers/*", "").is_ok());
+        assert!(router.get("/v1/publishers/*", "").is_err());
+        // The same pattern for another method is fine.
+        assert!(router.add(Method::Put, "/v1/publishers/:name", "").is_ok());
+    }
+
+    #[test]
+    fn test_catch_all_is_prefix_matches() {
+        let cases = [
+            ("/v1/publishers", "/v1/publishers"),
+            ("/v1/publishers", "/v1/publishers/abc-123"),
+            ("/v1/publishers", "/v1/publishers/abc/books"),
+            ("/v1/publishers", "/v1"),
+            ("/v1/publishers", "/v1/publishersBooks"),
+            ("/v1/publishers", "/v1/parent/publishers"),
+            ("/v1/publishers/*/books", "/v1/publishers/foo/books"),
+            ("/v1/publishers/*/books", "/v1/publishers/foo/books/book1"),
+            ("/v1/publishers/*/books", "/v1/publishers"),
+            ("/v1/publishers/*/books", "/v1/publishers/foo/booksByAuthor"),
+        ];
+        for (prefix, path) in cases {
+            let pattern = Pattern::parse(&format!("{prefix}/**")).unwrap();
+            assert_eq!(pattern.matches(path).is_some(), prefix_matches(prefix, path), "{prefix} {path}");
+        }
+    }
+}
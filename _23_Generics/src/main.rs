@@ -1,15 +1,46 @@
+mod point;
+mod quantity;
+
+use point::Point;
+use quantity::{
+    Gram, Grams, Kilogram, Kilograms, Kilometre, Kilometres, Metre, Metres, Mile, Miles, Minute, Pound, Pounds, Quantity,
+    Seconds, SquareKilometre,
+};
+
 fn main() {
     // 23.1 Generic Data Types
-    #[derive(Debug)]
-    struct Point<T> {
-        x: T,
-        y: T,
-    }
-
+    // `Point` lives in point.rs, together with its arithmetic.
     let integer = Point { x: 5, y: 10};
     let float = Point { x: 1.0, y: 4.0 };
     println!("{integer:?} and {float:?}");
 
+    let shifted = integer + Point::new(1, -1) * 2 - Point::origin();
+    println!("{shifted}, -{integer} = {}, dot {}", -integer, shifted.dot(integer));
+    println!("distance {} (squared {})", integer.distance(shifted), integer.distance_squared(shifted));
+    let widened: Point<f64> = integer.into();
+    println!("{widened:?}, length {}, angle {:.3} rad", widened.length(), widened.angle());
+    println!("normalized {:?}, angle to {float} {:.3} rad", widened.normalize(), widened.angle_between(float));
+    match Point::<i8>::try_from(Point::new(100i32, 300)) {
+        Ok(p) => println!("narrowed to {p}"),
+        Err(e) => println!("can't narrow: {e}"),
+    }
+    println!("{:?}", Point::<i32>::try_from(Point::new(2.0, -7.0)));
+
+    // The unit is part of the type, so `laps` can only hold seconds.
+    let laps = [Seconds::new(61.5), Seconds::new(59.25), Seconds::new(60.0)];
+    let total: Seconds = laps.iter().copied().sum();
+    let lap_length = Miles::new(0.25).to::<Metre>();
+    println!("total {total} = {:.3}, average {:?}", total.to::<Minute>(), total / laps.len() as f64);
+    println!("lap {lap_length:.1}, {:.2} laps per km", Kilometres::new(1.0).to::<Metre>() / lap_length);
+    let plot = Metres::new(20.0) * Metres::new(12.5);
+    let park = Kilometres::new(1.2) * Kilometres::new(0.5);
+    println!("plot {plot}, park {park} = {:.3}", (Miles::new(1.0) * Miles::new(1.0)).to::<SquareKilometre>());
+    println!("{} and {}", Quantity::<u32, Mile>::new(26).value(), Kilometres::new(5.0).to::<Mile>() - Miles::new(3.0));
+    let pace: Quantity<f64, Kilometre> = Metres::new(800.0).to();
+    println!("{pace} in {:.4}", Seconds::new(150.0).to::<quantity::Hour>());
+    let luggage = Kilograms::new(18.5) + Grams::new(750.0).to::<Kilogram>();
+    println!("luggage {luggage}, {:.1}, {:.0}", luggage.to::<Pound>(), Pounds::new(2.0).to::<Gram>());
+
 
 
 
@@ -33,12 +64,14 @@ fn main() {
         }
 
         fn set_x(&mut self, x:T) {
-            
+            self.0 = x;
         }
     }
 
-    let p = Point1(5, 10);
+    let mut p = Point1(5, 10);
     println!("p.x = {}", p.x());
+    p.set_x(7);
+    println!("p.x = {} after set_x, {p:?}", p.x());
 
     // Q: Why T is specified twice in impl<T> Point<T> {}? Isn’t that redundant?
     // This is because it is a generic implementation section for generic type. They are independently generic.
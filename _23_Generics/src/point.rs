@@ -0,0 +1,386 @@
+// Arithmetic on `Point<T>` for any numeric `T`.
+// The operators only need what `Num` promises, so one impl covers every
+// integer and float type. Conversions between element types that can't lose
+// information are `From`; the others are `TryFrom` and check every value.
+
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// What the generic `Point` methods need from an element type.
+pub trait Num: Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+    const ZERO: Self;
+    /// Rounds for 64-bit integers beyond 2^53; only used for distances.
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_num {
+    ($zero:literal: $($t:ty),*) => {
+        $(impl Num for $t {
+            const ZERO: Self = $zero;
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        })*
+    };
+}
+
+impl_num!(0: i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_num!(0.0: f32, f64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Num> Point<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Point { x, y }
+    }
+
+    pub fn origin() -> Self {
+        Point { x: T::ZERO, y: T::ZERO }
+    }
+
+    pub fn dot(self, other: Point<T>) -> T {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Exact for integers, unlike `distance`. For unsigned `T` the
+    /// subtraction underflows unless `other` is below and left of `self`;
+    /// use `distance` there.
+    pub fn distance_squared(self, other: Point<T>) -> T {
+        (self - other).dot(self - other)
+    }
+
+    /// Works for any `T`, unsigned included, since it measures in `f64`.
+    pub fn distance(self, other: Point<T>) -> f64 {
+        let dx = self.x.to_f64() - other.x.to_f64();
+        let dy = self.y.to_f64() - other.y.to_f64();
+        dx.hypot(dy)
+    }
+}
+
+// Only `Point<f64>` has these; `Point<i32>` can't be normalized without rounding.
+impl Point<f64> {
+    pub fn length(self) -> f64 {
+        self.x.hypot(self.y)
+    }
+
+    /// The point at distance 1 from the origin in the same direction.
+    /// `None` for the origin, which has no direction.
+    pub fn normalize(self) -> Option<Point<f64>> {
+        let length = self.length();
+        (length > 0.0 && length.is_finite()).then(|| Point { x: self.x / length, y: self.y / length })
+    }
+
+    /// Angle from the positive x axis, in radians in `(-PI, PI]`.
+    pub fn angle(self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    /// The unsigned angle between the two directions, in radians in `[0, PI]`.
+    pub fn angle_between(self, other: Point<f64>) -> f64 {
+        let cross = self.x * other.y - self.y * other.x;
+        cross.atan2(self.dot(other)).abs()
+    }
+}
+
+impl<T: Num> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point { x: self.x + other.x, y: self.y + other.y }
+    }
+}
+
+impl<T: Num> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, other: Point<T>) -> Point<T> {
+        Point { x: self.x - other.x, y: self.y - other.y }
+    }
+}
+
+/// Scaling: `Point::new(1, 2) * 3`.
+impl<T: Num> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, factor: T) -> Point<T> {
+        Point { x: self.x * factor, y: self.y * factor }
+    }
+}
+
+impl<T: Num + Neg<Output = T>> Neg for Point<T> {
+    type Output = Point<T>;
+
+    fn neg(self) -> Point<T> {
+        Point { x: -self.x, y: -self.y }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// `From<Point<$from>> for Point<$to>` for each `$to` that holds every `$from` exactly.
+/// A blanket `impl<T, U: From<T>>` would overlap with `From<T> for T`.
+macro_rules! impl_from {
+    ($($from:ty => $($to:ty),*;)*) => {
+        $($(impl From<Point<$from>> for Point<$to> {
+            fn from(p: Point<$from>) -> Self {
+                Point { x: p.x.into(), y: p.y.into() }
+            }
+        })*)*
+    };
+}
+
+impl_from! {
+    i8 => i16, i32, i64, isize, f32, f64;
+    i16 => i32, i64, isize, f32, f64;
+    i32 => i64, f64;
+    u8 => u16, u32, u64, usize, i16, i32, i64, isize, f32, f64;
+    u16 => u32, u64, usize, i32, i64, f32, f64;
+    u32 => u64, i64, f64;
+    f32 => f64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    /// A value doesn't fit in the target type.
+    OutOfRange,
+    /// A float with a fractional part, or not finite, converted to an integer type.
+    NotAnInteger,
+    /// The target type would round the value.
+    Inexact,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionError::OutOfRange => write!(f, "value out of range for the target type"),
+            ConversionError::NotAnInteger => write!(f, "value is not an integer"),
+            ConversionError::Inexact => write!(f, "value cannot be represented exactly"),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Integer to integer, where some values don't fit.
+macro_rules! impl_try_from_int {
+    ($($from:ty => $($to:ty),*;)*) => {
+        $($(impl TryFrom<Point<$from>> for Point<$to> {
+            type Error = ConversionError;
+
+            fn try_from(p: Point<$from>) -> Result<Self, ConversionError> {
+                let convert = |v: $from| <$to>::try_from(v).map_err(|_| ConversionError::OutOfRange);
+                Ok(Point { x: convert(p.x)?, y: convert(p.y)? })
+            }
+        })*)*
+    };
+}
+
+// Every integer pair that `impl_from!` doesn't cover. The pointer-sized types
+// are here even where they are wide enough on 64-bit targets, since std only
+// has `TryFrom` for them.
+impl_try_from_int! {
+    i8 => u8, u16, u32, u64, usize;
+    i16 => i8, u8, u16, u32, u64, usize;
+    i32 => i8, i16, isize, u8, u16, u32, u64, usize;
+    i64 => i8, i16, i32, isize, u8, u16, u32, u64, usize;
+    isize => i8, i16, i32, i64, u8, u16, u32, u64, usize;
+    u8 => i8;
+    u16 => u8, i8, i16, isize;
+    u32 => u8, u16, usize, i8, i16, i32, isize;
+    u64 => u8, u16, u32, usize, i8, i16, i32, i64, isize;
+    usize => u8, u16, u32, u64, i8, i16, i32, i64, isize;
+}
+
+/// Float to integer: only whole numbers in range convert.
+macro_rules! impl_try_from_float {
+    ($($from:ty => $($to:ty),*;)*) => {
+        $($(impl TryFrom<Point<$from>> for Point<$to> {
+            type Error = ConversionError;
+
+            fn try_from(p: Point<$from>) -> Result<Self, ConversionError> {
+                let convert = |v: $from| {
+                    if !v.is_finite() || v.fract() != 0.0 {
+                        return Err(ConversionError::NotAnInteger);
+                    }
+                    // MAX is 2^n - 1; as a float it is exact or rounds up to 2^n,
+                    // and either way adding one gives the exclusive bound 2^n.
+                    let end = <$to>::MAX as $from + 1.0;
+                    if v < <$to>::MIN as $from || v >= end {
+                        return Err(ConversionError::OutOfRange);
+                    }
+                    Ok(v as $to)
+                };
+                Ok(Point { x: convert(p.x)?, y: convert(p.y)? })
+            }
+        })*)*
+    };
+}
+
+impl_try_from_float! {
+    f32 => i8, i16, i32, i64, isize, u8, u16, u32, u64, usize;
+    f64 => i8, i16, i32, i64, isize, u8, u16, u32, u64, usize;
+}
+
+/// Integer to float, where large values would be rounded.
+macro_rules! impl_try_from_int_to_float {
+    ($($from:ty => $($to:ty),*;)*) => {
+        $($(impl TryFrom<Point<$from>> for Point<$to> {
+            type Error = ConversionError;
+
+            fn try_from(p: Point<$from>) -> Result<Self, ConversionError> {
+                let convert = |v: $from| {
+                    let converted = v as $to;
+                    // Through i128, because converting back to `$from` would saturate.
+                    if converted as i128 != v as i128 {
+                        return Err(ConversionError::Inexact);
+                    }
+                    Ok(converted)
+                };
+                Ok(Point { x: convert(p.x)?, y: convert(p.y)? })
+            }
+        })*)*
+    };
+}
+
+impl_try_from_int_to_float! {
+    i32 => f32;
+    i64 => f32, f64;
+    isize => f32, f64;
+    u32 => f32;
+    u64 => f32, f64;
+    usize => f32, f64;
+}
+
+impl TryFrom<Point<f64>> for Point<f32> {
+    type Error = ConversionError;
+
+    /// NaN and infinities carry over; finite values must fit without rounding.
+    fn try_from(p: Point<f64>) -> Result<Self, ConversionError> {
+        let convert = |v: f64| {
+            let converted = v as f32;
+            if !v.is_finite() {
+                Ok(converted)
+            } else if converted.is_infinite() {
+                Err(ConversionError::OutOfRange)
+            } else if converted as f64 != v {
+                Err(ConversionError::Inexact)
+            } else {
+                Ok(converted)
+            }
+        };
+        Ok(Point { x: convert(p.x)?, y: convert(p.y)? })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Point::new(1, 2);
+        let b = Point::new(4, -2);
+        assert_eq!(a + b, Point::new(5, 0));
+        assert_eq!(a - b, Point::new(-3, 4));
+        assert_eq!(a * 3, Point::new(3, 6));
+        assert_eq!(-a, Point::new(-1, -2));
+        assert_eq!(a.dot(b), 0);
+        assert_eq!(a.distance_squared(b), 25);
+        assert_eq!(a.distance(b), 5.0);
+        assert_eq!(Point::<u8>::origin(), Point::new(0, 0));
+
+        let p = Point::new(1.5, -0.5) * 2.0;
+        assert_eq!(p, Point::new(3.0, -1.0));
+        // Unsigned points measure without underflow.
+        assert_eq!(Point::new(0u32, 0).distance(Point::new(3, 4)), 5.0);
+        assert_eq!(Point::new(3u32, 4).to_string(), "(3, 4)");
+    }
+
+    #[test]
+    fn test_f64_only_methods() {
+        let p = Point::new(3.0, 4.0);
+        assert_eq!(p.length(), 5.0);
+        assert_eq!(p.normalize(), Some(Point::new(0.6, 0.8)));
+        assert_eq!(Point::<f64>::origin().normalize(), None);
+        assert_eq!(Point::new(f64::NAN, 1.0).normalize(), None);
+
+        assert!(close(Point::new(0.0, 1.0).angle(), FRAC_PI_2));
+        assert!(close(Point::new(-1.0, 0.0).angle(), PI));
+        assert!(close(Point::new(1.0, 1.0).angle_between(Point::new(0.0, 1.0)), FRAC_PI_4));
+        assert!(close(Point::new(0.0, 1.0).angle_between(Point::new(1.0, 1.0)), FRAC_PI_4));
+        assert!(close(Point::new(1.0, 0.0).angle_between(Point::new(-2.0, 0.0)), PI));
+    }
+
+    #[test]
+    fn test_lossless_from() {
+        let p: Point<f64> = Point::new(-3i32, 7).into();
+        assert_eq!(p, Point::new(-3.0, 7.0));
+        assert_eq!(Point::<i64>::from(Point::new(i32::MIN, i32::MAX)), Point::new(i32::MIN as i64, i32::MAX as i64));
+        assert_eq!(Point::<i16>::from(Point::new(255u8, 0)), Point::new(255, 0));
+        assert_eq!(Point::<f64>::from(Point::new(0.1f32, 0.0)).x, 0.1f32 as f64);
+        assert_eq!(Point::<isize>::from(Point::new(i8::MIN, -1)), Point::new(-128, -1));
+        assert_eq!(Point::<isize>::from(Point::new(i16::MIN, 1)), Point::new(-32768, 1));
+        assert_eq!(Point::<usize>::from(Point::new(u8::MAX, 0)), Point::new(255, 0));
+        assert_eq!(Point::<usize>::from(Point::new(u16::MAX, 0)), Point::new(65535, 0));
+        assert_eq!(Point::<f64>::from(Point::new(u32::MAX, 0)), Point::new(4294967295.0, 0.0));
+    }
+
+    #[test]
+    fn test_lossy_try_from() {
+        assert_eq!(Point::<i8>::try_from(Point::new(100i32, -100)), Ok(Point::new(100, -100)));
+        assert_eq!(Point::<i8>::try_from(Point::new(100i32, 200)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<u32>::try_from(Point::new(-1i64, 0)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<u8>::try_from(Point::new(-1i8, 0)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<usize>::try_from(Point::new(7i16, 0)), Ok(Point::new(7, 0)));
+        assert_eq!(Point::<isize>::try_from(Point::new(i32::MIN, 0)), Ok(Point::new(i32::MIN as isize, 0)));
+        assert_eq!(Point::<i8>::try_from(Point::new(isize::MAX, 0)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<i8>::try_from(Point::new(128u8, 0)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<i16>::try_from(Point::new(40000u16, 0)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<usize>::try_from(Point::new(u32::MAX, 0)), Ok(Point::new(u32::MAX as usize, 0)));
+        assert_eq!(Point::<u64>::try_from(Point::new(usize::MAX, 0)), Ok(Point::new(usize::MAX as u64, 0)));
+
+        assert_eq!(Point::<i32>::try_from(Point::new(2.0, -3.0)), Ok(Point::new(2, -3)));
+        assert_eq!(Point::<i32>::try_from(Point::new(2.5, 0.0)), Err(ConversionError::NotAnInteger));
+        assert_eq!(Point::<i32>::try_from(Point::new(f64::INFINITY, 0.0)), Err(ConversionError::NotAnInteger));
+        assert_eq!(Point::<i32>::try_from(Point::new(3e9, 0.0)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<u32>::try_from(Point::new(-1.0, 0.0)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<i64>::try_from(Point::new(9.3e18f64, 0.0)), Err(ConversionError::OutOfRange));
+        // 2^63 itself is one past i64::MAX, though `as` would saturate it to MAX.
+        assert_eq!(Point::<i64>::try_from(Point::new(9223372036854775808.0f64, 0.0)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<i64>::try_from(Point::new(-9223372036854775808.0f64, 0.0)), Ok(Point::new(i64::MIN, 0)));
+        assert_eq!(Point::<u32>::try_from(Point::new(4294967295.0, 0.0)), Ok(Point::new(u32::MAX, 0)));
+        assert_eq!(Point::<u8>::try_from(Point::new(255.0f32, 0.0)), Ok(Point::new(255, 0)));
+        assert_eq!(Point::<u8>::try_from(Point::new(256.0f32, 0.0)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<u32>::try_from(Point::new(-0.5f32, 0.0)), Err(ConversionError::NotAnInteger));
+        // u64::MAX rounds up to 2^64 as a float, which is out of range.
+        assert_eq!(Point::<u64>::try_from(Point::new(2f64.powi(64), 0.0)), Err(ConversionError::OutOfRange));
+        assert_eq!(Point::<usize>::try_from(Point::new(-1.0f64, 0.0)), Err(ConversionError::OutOfRange));
+
+        assert_eq!(Point::<f32>::try_from(Point::new(0.5, 1e10)), Ok(Point::new(0.5, 1e10)));
+        assert_eq!(Point::<f32>::try_from(Point::new(0.1, 0.0)), Err(ConversionError::Inexact));
+        assert_eq!(Point::<f32>::try_from(Point::new(1e300, 0.0)), Err(ConversionError::OutOfRange));
+        assert!(Point::<f32>::try_from(Point::new(f64::NAN, f64::INFINITY)).is_ok());
+        assert_eq!(Point::<f64>::try_from(Point::new(1i64 << 53, 0)), Ok(Point::new(9007199254740992.0, 0.0)));
+        assert_eq!(Point::<f64>::try_from(Point::new((1i64 << 53) + 1, 0)), Err(ConversionError::Inexact));
+        assert_eq!(Point::<f32>::try_from(Point::new(16_777_217i32, 0)), Err(ConversionError::Inexact));
+        assert_eq!(Point::<f64>::try_from(Point::new(i64::MAX, 0)), Err(ConversionError::Inexact));
+        assert_eq!(Point::<f32>::try_from(Point::new(-(1isize << 24), 0)), Ok(Point::new(-16777216.0, 0.0)));
+        assert_eq!(Point::<f32>::try_from(Point::new(u32::MAX, 0)), Err(ConversionError::Inexact));
+        // u64::MAX as f32 is 2^64, which compares unequal only if the check doesn't saturate.
+        assert_eq!(Point::<f32>::try_from(Point::new(u64::MAX, 0)), Err(ConversionError::Inexact));
+        assert_eq!(Point::<f64>::try_from(Point::new(1usize << 60, 0)), Ok(Point::new(1152921504606846976.0, 0.0)));
+    }
+}
@@ -0,0 +1,336 @@
+// Numbers tagged with a unit at compile time.
+//
+// `Quantity<T, U>` stores just a `T`; the unit `U` is a type that never
+// exists at runtime (a `PhantomData`). Operators are only implemented where
+// the units agree, so adding metres to seconds or kilometres to miles is a
+// type error, and length times length gives an area. Changing the unit is
+// always an explicit `.to::<Unit>()`.
+
+use std::fmt;
+use std::iter::Sum;
+use std::marker::PhantomData;
+use std::ops::{Add, Div, Mul, Sub};
+
+/// A unit of measure. Units are empty enums: they only exist as types.
+pub trait Unit {
+    /// What the unit measures; conversions stay within one dimension.
+    type Dimension;
+    const SYMBOL: &'static str;
+    /// One of this unit in the dimension's base unit (m, s, m² or kg).
+    const IN_BASE: f64;
+}
+
+pub enum Length {}
+pub enum Time {}
+pub enum Area {}
+pub enum Mass {}
+
+macro_rules! units {
+    ($($(#[$doc:meta])* $name:ident: $dimension:ty, $symbol:literal, $in_base:expr;)*) => {
+        $($(#[$doc])* pub enum $name {}
+
+        impl Unit for $name {
+            type Dimension = $dimension;
+            const SYMBOL: &'static str = $symbol;
+            const IN_BASE: f64 = $in_base;
+        })*
+    };
+}
+
+const METRES_PER_MILE: f64 = 1609.344;
+const KILOGRAMS_PER_POUND: f64 = 0.453_592_37;
+
+units! {
+    Metre: Length, "m", 1.0;
+    Kilometre: Length, "km", 1000.0;
+    /// The international mile.
+    Mile: Length, "mi", METRES_PER_MILE;
+    Second: Time, "s", 1.0;
+    Minute: Time, "min", 60.0;
+    Hour: Time, "h", 3600.0;
+    SquareMetre: Area, "m²", 1.0;
+    SquareKilometre: Area, "km²", 1e6;
+    SquareMile: Area, "mi²", METRES_PER_MILE * METRES_PER_MILE;
+    Gram: Mass, "g", 0.001;
+    Kilogram: Mass, "kg", 1.0;
+    /// The international avoirdupois pound.
+    Pound: Mass, "lb", KILOGRAMS_PER_POUND;
+}
+
+/// The unit of `Self × Self`.
+pub trait Squared: Unit<Dimension = Length> {
+    type Output: Unit<Dimension = Area>;
+}
+
+impl Squared for Metre {
+    type Output = SquareMetre;
+}
+
+impl Squared for Kilometre {
+    type Output = SquareKilometre;
+}
+
+impl Squared for Mile {
+    type Output = SquareMile;
+}
+
+pub struct Quantity<T, U> {
+    value: T,
+    unit: PhantomData<U>,
+}
+
+pub type Metres<T = f64> = Quantity<T, Metre>;
+pub type Kilometres<T = f64> = Quantity<T, Kilometre>;
+pub type Miles<T = f64> = Quantity<T, Mile>;
+pub type Seconds<T = f64> = Quantity<T, Second>;
+pub type Grams<T = f64> = Quantity<T, Gram>;
+pub type Kilograms<T = f64> = Quantity<T, Kilogram>;
+pub type Pounds<T = f64> = Quantity<T, Pound>;
+
+impl<T, U> Quantity<T, U> {
+    pub fn new(value: T) -> Self {
+        Quantity { value, unit: PhantomData }
+    }
+
+    /// The bare number, unit dropped.
+    pub fn value(self) -> T {
+        self.value
+    }
+}
+
+impl<U: Unit> Quantity<f64, U> {
+    /// The same amount in another unit of the same dimension.
+    pub fn to<V: Unit<Dimension = U::Dimension>>(self) -> Quantity<f64, V> {
+        Quantity::new(self.value * U::IN_BASE / V::IN_BASE)
+    }
+}
+
+// Written out rather than derived: a derive would also require `U: Clone`
+// and so on, though no value of `U` is ever stored.
+impl<T: Clone, U> Clone for Quantity<T, U> {
+    fn clone(&self) -> Self {
+        Quantity::new(self.value.clone())
+    }
+}
+
+impl<T: Copy, U> Copy for Quantity<T, U> {}
+
+impl<T: PartialEq, U> PartialEq for Quantity<T, U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: PartialOrd, U> PartialOrd for Quantity<T, U> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Default, U> Default for Quantity<T, U> {
+    fn default() -> Self {
+        Quantity::new(T::default())
+    }
+}
+
+impl<T: fmt::Display, U: Unit> fmt::Display for Quantity<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)?;
+        write!(f, " {}", U::SYMBOL)
+    }
+}
+
+impl<T: fmt::Debug, U: Unit> fmt::Debug for Quantity<T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} {}", self.value, U::SYMBOL)
+    }
+}
+
+impl<T: Add<Output = T>, U> Add for Quantity<T, U> {
+    type Output = Quantity<T, U>;
+
+    fn add(self, other: Self) -> Self {
+        Quantity::new(self.value + other.value)
+    }
+}
+
+impl<T: Sub<Output = T>, U> Sub for Quantity<T, U> {
+    type Output = Quantity<T, U>;
+
+    fn sub(self, other: Self) -> Self {
+        Quantity::new(self.value - other.value)
+    }
+}
+
+/// Scaling by a plain number.
+impl<T: Mul<Output = T>, U> Mul<T> for Quantity<T, U> {
+    type Output = Quantity<T, U>;
+
+    fn mul(self, factor: T) -> Self {
+        Quantity::new(self.value * factor)
+    }
+}
+
+impl<T: Mul<Output = T>, U: Squared> Mul for Quantity<T, U> {
+    type Output = Quantity<T, U::Output>;
+
+    fn mul(self, other: Self) -> Quantity<T, U::Output> {
+        Quantity::new(self.value * other.value)
+    }
+}
+
+impl<T: Div<Output = T>, U> Div<T> for Quantity<T, U> {
+    type Output = Quantity<T, U>;
+
+    fn div(self, divisor: T) -> Self {
+        Quantity::new(self.value / divisor)
+    }
+}
+
+/// Same unit over same unit is a plain ratio.
+impl<T: Div<Output = T>, U> Div for Quantity<T, U> {
+    type Output = T;
+
+    fn div(self, other: Self) -> T {
+        self.value / other.value
+    }
+}
+
+impl<T: Add<Output = T> + Default, U> Sum for Quantity<T, U> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Quantity::default(), |total, q| total + q)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close<U>(a: Quantity<f64, U>, b: f64) -> bool {
+        (a.value() - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    /// A scratch directory, removed again even if the test panics.
+    struct ScratchDir(std::path::PathBuf);
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Compiles `expression` against this module with rustc, in `dir`, and
+    /// returns its errors, or `None` if it compiled.
+    fn compile_errors(dir: &std::path::Path, expression: &str) -> Option<String> {
+        let source = dir.join(format!("case{}.rs", CASE.fetch_add(1, std::sync::atomic::Ordering::Relaxed)));
+        let module = concat!(env!("CARGO_MANIFEST_DIR"), "/src/quantity.rs");
+        std::fs::write(
+            &source,
+            format!("#[path = {module:?}]\nmod quantity;\nuse quantity::*;\nfn main() {{\n    let _ = {expression};\n}}\n"),
+        )
+        .unwrap();
+        // The same edition as the crate, read from its manifest.
+        let manifest = include_str!("../Cargo.toml");
+        let edition = manifest.lines().find_map(|line| line.strip_prefix("edition = ")).unwrap_or("2015");
+        // Cargo runs tests with CARGO set; the toolchain's rustc sits next to it.
+        let rustc = std::env::var_os("CARGO")
+            .map(|cargo| std::path::PathBuf::from(cargo).with_file_name("rustc"))
+            .filter(|rustc| rustc.exists())
+            .unwrap_or_else(|| "rustc".into());
+        let output = std::process::Command::new(&rustc)
+            .args(["--edition", edition.trim_matches('"'), "--emit", "metadata", "-A", "warnings", "--out-dir"])
+            .arg(dir)
+            .arg(&source)
+            .output()
+            .unwrap_or_else(|e| panic!("can't run {}: {e}", rustc.display()));
+        (!output.status.success()).then(|| String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+
+    static CASE: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    /// Runs rustc once per case, so it is opt-in:
+    /// `cargo test test_unit_errors -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_unit_errors_do_not_compile() {
+        let dir = ScratchDir(std::env::temp_dir().join(format!("quantity-compile-{}", std::process::id())));
+        std::fs::create_dir_all(&dir.0).unwrap();
+        let dir = &dir.0;
+        // The harness itself works.
+        assert_eq!(compile_errors(dir, "Metres::new(1.0) + Kilometres::new(1.0).to::<Metre>()"), None);
+
+        let cases = [
+            // Different units.
+            ("Metres::new(1.0) + Seconds::new(1.0)", "E0308"),
+            ("Kilograms::new(1.0) - Grams::new(1.0)", "E0308"),
+            // Same dimension, still different units.
+            ("Kilometres::new(1.0) + Miles::new(1.0)", "E0308"),
+            // No conversion across dimensions.
+            ("Metres::new(1.0).to::<Second>()", "E0271"),
+            ("Pounds::new(1.0).to::<Metre>()", "E0271"),
+            // Convert one side first.
+            ("Metres::new(1.0) * Kilometres::new(1.0)", "E0277"),
+            // `Second` and `Kilogram` are not `Squared`.
+            ("Seconds::new(1.0) * Seconds::new(1.0)", "E0308"),
+            ("Kilograms::new(1.0) * Kilograms::new(1.0)", "E0308"),
+            // That's an area.
+            ("{ let m: Metres = Metres::new(1.0) * Metres::new(1.0); m }", "E0308"),
+        ];
+        for (expression, code) in cases {
+            match compile_errors(dir, expression) {
+                Some(errors) => assert!(errors.contains(&format!("error[{code}]")), "{expression}:\n{errors}"),
+                None => panic!("{expression} compiled"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_unit_arithmetic() {
+        let lap = Seconds::new(61.5);
+        let laps = [lap, Seconds::new(59.25), Seconds::new(60.0)];
+        let total: Seconds = laps.iter().copied().sum();
+        assert_eq!(total, Seconds::new(180.75));
+        assert_eq!(total / 3.0, Seconds::new(60.25));
+        assert_eq!(lap - Seconds::new(1.5), Seconds::new(60.0));
+        assert_eq!(lap * 2.0, Seconds::new(123.0));
+        assert_eq!(Seconds::new(90.0) / Seconds::new(60.0), 1.5);
+        assert!(Seconds::new(59.25) < lap);
+
+        let integer: Metres<i32> = Metres::new(3) + Metres::new(4);
+        assert_eq!(integer.value(), 7);
+    }
+
+    #[test]
+    fn test_area() {
+        let area: Quantity<f64, SquareMetre> = Metres::new(3.0) * Metres::new(4.0);
+        assert_eq!(area.to_string(), "12 m²");
+        let field: Quantity<i64, SquareKilometre> = Kilometres::new(2) * Kilometres::new(5);
+        assert_eq!(field.value(), 10);
+        assert!(close(Miles::new(1.0) * Miles::new(1.0), 1.0));
+        assert!(close((Miles::new(1.0) * Miles::new(1.0)).to::<SquareKilometre>(), 2.589988110336));
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert!(close(Kilometres::new(1.5).to::<Metre>(), 1500.0));
+        assert!(close(Metres::new(250.0).to::<Kilometre>(), 0.25));
+        assert!(close(Miles::new(10.0).to::<Kilometre>(), 16.09344));
+        assert!(close(Kilometres::new(16.09344).to::<Mile>(), 10.0));
+        assert!(close(Seconds::new(5400.0).to::<Hour>(), 1.5));
+        assert!(close(Quantity::<f64, Hour>::new(0.5).to::<Minute>(), 30.0));
+        assert!(close(Kilograms::new(2.5).to::<Gram>(), 2500.0));
+        assert!(close(Grams::new(750.0).to::<Kilogram>(), 0.75));
+        assert!(close(Pounds::new(1.0).to::<Gram>(), 453.59237));
+        assert!(close(Kilograms::new(100.0).to::<Pound>(), 220.462262184878));
+        // Mixing units takes an explicit conversion.
+        let run = Kilometres::new(5.0) + Miles::new(1.0).to::<Kilometre>();
+        assert!(close(run, 6.609344));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{:.1}", Kilometres::new(42.195)), "42.2 km");
+        assert_eq!(format!("{:?}", Seconds::new(1.0)), "1.0 s");
+        assert_eq!(Quantity::<u32, Minute>::new(3).to_string(), "3 min");
+    }
+}
@@ -0,0 +1,435 @@
+// Comparison helpers that grow out of 24.3's `Equals`/`NotEquals`.
+//
+//   - `Equals<Rhs>` takes a right-hand type, so lengths in different units
+//     compare directly; `NotEquals` still comes free from the blanket impl.
+//   - `ApproxEq` compares floats within an epsilon or a number of ULPs.
+//   - `Comparator` is anything that orders two values: plain closures,
+//     `by_key` adaptors, and `reversed()`/`then()` combinations of those.
+//   - `impl_ord_by!` gives a type `Eq` and `Ord` from a list of field
+//     extractors, for orders a derive can't express.
+
+use std::cmp::Ordering;
+
+pub trait Equals<Rhs: ?Sized = Self> {
+    fn equal(&self, other: &Rhs) -> bool;
+}
+
+pub trait NotEquals<Rhs: ?Sized = Self> {
+    fn not_equal(&self, other: &Rhs) -> bool;
+}
+
+impl<T: Equals<Rhs> + ?Sized, Rhs: ?Sized> NotEquals<Rhs> for T {
+    fn not_equal(&self, other: &Rhs) -> bool {
+        !self.equal(other)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// Equal if the difference is at most `absolute`, or at most `relative`
+    /// times the larger magnitude. `absolute` matters near zero, where
+    /// relative differences blow up.
+    Epsilon { absolute: f64, relative: f64 },
+    /// Equal if at most this many representable floats lie between the two.
+    Ulps(u64),
+}
+
+pub trait ApproxEq: Copy {
+    fn approx_eq(self, other: Self, tolerance: Tolerance) -> bool;
+
+    /// How many steps apart the two floats are; `None` if either is NaN.
+    /// 0.0 and -0.0 are 0 steps apart.
+    fn ulps_between(self, other: Self) -> Option<u64>;
+}
+
+macro_rules! impl_approx_eq {
+    ($($float:ty => $int:ty),*) => {
+        $(impl ApproxEq for $float {
+            fn approx_eq(self, other: Self, tolerance: Tolerance) -> bool {
+                if self == other {
+                    // Also covers equal infinities, which the checks below would reject.
+                    return true;
+                }
+                if !self.is_finite() || !other.is_finite() {
+                    // Infinity is only close to itself, and NaN to nothing.
+                    return false;
+                }
+                match tolerance {
+                    Tolerance::Epsilon { absolute, relative } => {
+                        let difference = f64::from(self - other).abs();
+                        let largest = f64::from(self.abs().max(other.abs()));
+                        difference <= absolute || difference <= relative * largest
+                    }
+                    Tolerance::Ulps(max) => self.ulps_between(other).is_some_and(|ulps| ulps <= max),
+                }
+            }
+
+            fn ulps_between(self, other: Self) -> Option<u64> {
+                if self.is_nan() || other.is_nan() {
+                    return None;
+                }
+                // Reinterpreted as integers, positive floats already sort like
+                // their values; negative ones are mirrored below zero so that
+                // -0.0 lands on 0 too.
+                let ordered = |x: $float| {
+                    let bits = x.to_bits() as $int;
+                    if bits < 0 { <$int>::MIN - bits } else { bits }
+                };
+                Some((i128::from(ordered(self)) - i128::from(ordered(other))).unsigned_abs() as u64)
+            }
+        })*
+    };
+}
+
+impl_approx_eq!(f32 => i32, f64 => i64);
+
+/// Decides the order of two values.
+pub trait Comparator<T: ?Sized> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+
+    /// Equality by this comparator; `Player`s with the same strength are equal by strength.
+    fn equal(&self, a: &T, b: &T) -> bool {
+        self.compare(a, b) == Ordering::Equal
+    }
+
+    fn reversed(self) -> Reversed<Self>
+    where
+        Self: Sized,
+    {
+        Reversed(self)
+    }
+
+    /// Breaks ties with `next`.
+    fn then<C: Comparator<T>>(self, next: C) -> Then<Self, C>
+    where
+        Self: Sized,
+    {
+        Then(self, next)
+    }
+
+    fn then_by_key<K: Ord, F: Fn(&T) -> K>(self, key: F) -> Then<Self, ByKey<F>>
+    where
+        Self: Sized,
+    {
+        Then(self, by_key(key))
+    }
+
+    /// Stable, like `slice::sort_by`.
+    fn sort(&self, items: &mut [T])
+    where
+        T: Sized,
+    {
+        items.sort_by(|a, b| self.compare(a, b));
+    }
+
+    /// The first of the largest items.
+    fn max<'a>(&self, items: &'a [T]) -> Option<&'a T>
+    where
+        T: Sized,
+    {
+        items.iter().reduce(|best, item| if self.compare(item, best) == Ordering::Greater { item } else { best })
+    }
+}
+
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering> Comparator<T> for F {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+pub struct ByKey<F>(F);
+
+/// Compares by an owned key, e.g. `by_key(|p: &Player| p.strength)`.
+pub fn by_key<T: ?Sized, K: Ord, F: Fn(&T) -> K>(key: F) -> ByKey<F> {
+    ByKey(key)
+}
+
+impl<T: ?Sized, K: Ord, F: Fn(&T) -> K> Comparator<T> for ByKey<F> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a).cmp(&(self.0)(b))
+    }
+}
+
+pub struct ByRefKey<F>(F);
+
+/// Compares by a borrowed key, e.g. `by_ref_key(|p: &Player| &p.name)`.
+/// `by_key` can't take that closure: its key may not borrow from the value.
+pub fn by_ref_key<T: ?Sized, K: Ord + ?Sized, F: for<'a> Fn(&'a T) -> &'a K>(key: F) -> ByRefKey<F> {
+    ByRefKey(key)
+}
+
+impl<T: ?Sized, K: Ord + ?Sized, F: for<'a> Fn(&'a T) -> &'a K> Comparator<T> for ByRefKey<F> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a).cmp((self.0)(b))
+    }
+}
+
+pub struct Reversed<C>(C);
+
+impl<T: ?Sized, C: Comparator<T>> Comparator<T> for Reversed<C> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self.0.compare(b, a)
+    }
+}
+
+pub struct Then<A, B>(A, B);
+
+impl<T: ?Sized, A: Comparator<T>, B: Comparator<T>> Comparator<T> for Then<A, B> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self.0.compare(a, b).then_with(|| self.1.compare(a, b))
+    }
+}
+
+/// Implements `PartialEq`, `Eq`, `PartialOrd` and `Ord` from field extractors,
+/// compared in order:
+///
+/// `impl_ord_by!(Score: |s| Reverse(s.points), |s| s.time_ms);`
+///
+/// The extractors look like closures but are expanded inline, so they may
+/// borrow (`|s| &s.name`). Fields left out don't take part in equality either,
+/// which keeps `Eq` and `Ord` consistent.
+macro_rules! impl_ord_by {
+    ($ty:ty: $(|$value:ident| $key:expr),+ $(,)?) => {
+        impl PartialEq for $ty {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == std::cmp::Ordering::Equal
+            }
+        }
+
+        impl Eq for $ty {}
+
+        impl PartialOrd for $ty {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $ty {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                std::cmp::Ordering::Equal
+                    $(.then_with(|| {
+                        let left = { let $value = self; $key };
+                        let right = { let $value = other; $key };
+                        left.cmp(&right)
+                    }))+
+            }
+        }
+    };
+}
+
+pub(crate) use impl_ord_by;
+
+/// A length in some unit, as a whole number of micrometres.
+pub trait Length {
+    fn micrometres(&self) -> i64;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Millimeter(pub i32);
+
+/// The `Centimeter` of 24.3, now comparable with the other units.
+#[derive(Debug, Clone, Copy)]
+pub struct Centimeter(pub i16);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Meter(pub i32);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Inch(pub i32);
+
+impl Length for Millimeter {
+    fn micrometres(&self) -> i64 {
+        i64::from(self.0) * 1_000
+    }
+}
+
+impl Length for Centimeter {
+    fn micrometres(&self) -> i64 {
+        i64::from(self.0) * 10_000
+    }
+}
+
+impl Length for Meter {
+    fn micrometres(&self) -> i64 {
+        i64::from(self.0) * 1_000_000
+    }
+}
+
+impl Length for Inch {
+    fn micrometres(&self) -> i64 {
+        i64::from(self.0) * 25_400
+    }
+}
+
+/// `PartialEq`, `PartialOrd` and `Equals` between every pair of the listed
+/// units, plus `Eq` and `Ord` within each. Micrometres are exact for all of
+/// them, so the comparisons never round.
+macro_rules! length_comparisons {
+    ($($unit:ty),*) => {
+        length_comparisons!(@each [$($unit),*] $($unit),*);
+    };
+    // `$all` is one token tree, so it can be repeated once per unit.
+    (@each $all:tt $($unit:ty),*) => {
+        $(length_comparisons!(@with $unit, $all);
+
+        impl Eq for $unit {}
+
+        impl Ord for $unit {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.micrometres().cmp(&other.micrometres())
+            }
+        })*
+    };
+    (@with $left:ty, [$($right:ty),*]) => {
+        $(impl PartialEq<$right> for $left {
+            fn eq(&self, other: &$right) -> bool {
+                self.micrometres() == other.micrometres()
+            }
+        }
+
+        // Clippy wants `Some(self.cmp(other))` when `$left` and `$right` are
+        // the same type; this is the same order, spelled for every pair.
+        #[allow(clippy::non_canonical_partial_ord_impl)]
+        impl PartialOrd<$right> for $left {
+            fn partial_cmp(&self, other: &$right) -> Option<Ordering> {
+                Some(self.micrometres().cmp(&other.micrometres()))
+            }
+        }
+
+        impl Equals<$right> for $left {
+            fn equal(&self, other: &$right) -> bool {
+                self == other
+            }
+        })*
+    };
+}
+
+length_comparisons!(Millimeter, Centimeter, Meter, Inch);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Reverse;
+
+    #[derive(Debug, Clone)]
+    struct Player {
+        name: String,
+        strength: u8,
+        hit_points: u8,
+    }
+
+    fn player(name: &str, strength: u8, hit_points: u8) -> Player {
+        Player { name: name.to_string(), strength, hit_points }
+    }
+
+    fn names(players: &[Player]) -> Vec<&str> {
+        players.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_approx_eq_epsilon() {
+        let tight = Tolerance::Epsilon { absolute: 1e-12, relative: 1e-9 };
+        assert!((0.1 + 0.2).approx_eq(0.3, tight));
+        assert_ne!(0.1 + 0.2, 0.3);
+        assert!(1e20.approx_eq(1e20 + 1e5, tight));
+        assert!(!1.0.approx_eq(1.001, tight));
+        // Near zero only the absolute tolerance helps.
+        assert!(1e-15.approx_eq(-1e-15, tight));
+        assert!(!1e-15.approx_eq(-1e-15, Tolerance::Epsilon { absolute: 0.0, relative: 1e-9 }));
+        assert!(0.1f32.approx_eq(0.100_000_01, Tolerance::Epsilon { absolute: 0.0, relative: 1e-6 }));
+
+        assert!(f64::INFINITY.approx_eq(f64::INFINITY, tight));
+        assert!(!f64::INFINITY.approx_eq(f64::MAX, tight));
+        assert!(!f64::NAN.approx_eq(f64::NAN, tight));
+    }
+
+    #[test]
+    fn test_approx_eq_ulps() {
+        let next = f64::from_bits(1.0f64.to_bits() + 1);
+        assert_eq!(1.0.ulps_between(next), Some(1));
+        assert_eq!(0.0.ulps_between(-0.0), Some(0));
+        assert_eq!((-f64::MIN_POSITIVE / 2.0).ulps_between(f64::MIN_POSITIVE / 2.0), Some(1 << 52));
+        assert_eq!(1.0.ulps_between(f64::NAN), None);
+        assert_eq!(f32::MAX.ulps_between(f32::INFINITY), Some(1));
+        assert_eq!(f64::MIN.ulps_between(f64::MAX), Some(2 * (0x7fef_ffff_ffff_ffff)));
+
+        assert!((0.1 + 0.2).approx_eq(0.3, Tolerance::Ulps(1)));
+        assert!(!(0.1 + 0.2).approx_eq(0.3, Tolerance::Ulps(0)));
+        assert!(!1.0.approx_eq(-1.0, Tolerance::Ulps(u64::MAX / 4)));
+        assert!(!f32::NAN.approx_eq(f32::NAN, Tolerance::Ulps(u64::MAX)));
+        assert!(!f32::MAX.approx_eq(f32::INFINITY, Tolerance::Ulps(1)));
+    }
+
+    #[test]
+    fn test_key_comparators() {
+        let mut players = vec![player("Cleo", 7, 30), player("Abe", 9, 20), player("Bea", 7, 50)];
+        let by_strength = by_key(|p: &Player| p.strength);
+        assert!(by_strength.equal(&players[0], &players[2]));
+        assert!(!by_strength.equal(&players[0], &players[1]));
+
+        by_strength.sort(&mut players);
+        assert_eq!(names(&players), ["Cleo", "Bea", "Abe"]);
+        by_key(|p: &Player| p.strength).reversed().then(by_ref_key(|p: &Player| &p.name)).sort(&mut players);
+        assert_eq!(names(&players), ["Abe", "Bea", "Cleo"]);
+        by_key(|p: &Player| p.strength).then_by_key(|p: &Player| Reverse(p.hit_points)).sort(&mut players);
+        assert_eq!(names(&players), ["Bea", "Cleo", "Abe"]);
+
+        assert_eq!(by_key(|p: &Player| p.hit_points).max(&players).unwrap().name, "Bea");
+        assert_eq!(by_strength.max(&players).unwrap().name, "Abe");
+        assert!(by_strength.max(&[]).is_none());
+        // The first of equal maximums.
+        assert_eq!(by_key(|p: &Player| p.strength / 10).max(&players).unwrap().name, "Bea");
+    }
+
+    #[test]
+    fn test_closure_comparators() {
+        let by_length = |a: &&str, b: &&str| a.len().cmp(&b.len());
+        let mut words = ["ccc", "a", "bb", "dd"];
+        by_length.sort(&mut words);
+        assert_eq!(words, ["a", "bb", "dd", "ccc"]);
+        let longest_first = (|a: &&str, b: &&str| a.len().cmp(&b.len())).reversed().then(|a: &&str, b: &&str| a.cmp(b));
+        longest_first.sort(&mut words);
+        assert_eq!(words, ["ccc", "bb", "dd", "a"]);
+        assert!(by_ref_key(|s: &String| s.as_str()).equal(&"x".to_string(), &"x".to_string()));
+    }
+
+    #[derive(Debug)]
+    struct Score {
+        player: String,
+        points: u32,
+        time_ms: u32,
+    }
+
+    impl_ord_by!(Score: |s| Reverse(s.points), |s| s.time_ms, |s| &s.player);
+
+    #[test]
+    fn test_impl_ord_by() {
+        let score = |player: &str, points, time_ms| Score { player: player.to_string(), points, time_ms };
+        let mut scores = [score("b", 10, 900), score("a", 12, 1000), score("c", 10, 800), score("a", 10, 800)];
+        scores.sort();
+        let order: Vec<(&str, u32)> = scores.iter().map(|s| (s.player.as_str(), s.time_ms)).collect();
+        assert_eq!(order, [("a", 1000), ("a", 800), ("c", 800), ("b", 900)]);
+        assert_eq!(score("x", 1, 2), score("x", 1, 2));
+        assert_ne!(score("x", 1, 2), score("y", 1, 2));
+        assert!(score("x", 2, 2) < score("x", 1, 2));
+        assert_eq!(scores.iter().max().unwrap().player, "b");
+    }
+
+    #[test]
+    fn test_lengths_across_units() {
+        assert_eq!(Centimeter(10), Millimeter(100));
+        assert_eq!(Meter(1), Centimeter(100));
+        assert_eq!(Inch(1).micrometres(), Millimeter(25).micrometres() + 400);
+        assert_eq!(Centimeter(254), Inch(100));
+        assert!(Inch(1) > Centimeter(2));
+        assert!(Inch(1) < Centimeter(3));
+        assert!(Meter(-1) < Millimeter(0));
+        assert!(Centimeter(10).equal(&Millimeter(100)));
+        assert!(Centimeter(10).not_equal(&Millimeter(101)));
+        assert!(Millimeter(101).not_equal(&Centimeter(10)));
+
+        let mut lengths = [Centimeter(30), Centimeter(-5), Centimeter(12)];
+        lengths.sort();
+        assert_eq!(lengths.map(|c| c.0), [-5, 12, 30]);
+    }
+}
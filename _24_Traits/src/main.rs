@@ -1,39 +1,25 @@
+mod compare;
+mod pets;
+
 use std::fmt::Display;
 
+use compare::{ApproxEq, Comparator, Equals as _, NotEquals as _, Tolerance};
+use pets::{Cat, Dog, Pet, PetRecord, PetRegistry};
+
 fn main() {
     // 24.0 Traits
     // Rust lets you abstract over types with traits. They're similar to interfaces.
 
-    trait Pet {
-        fn name(&self) -> String;
-    }
-
-    struct Dog {
-        name: String,
-    }
-
-    struct Cat;
-
-    impl Pet for Dog {
-        fn name(&self) -> String {
-            self.name.clone()
-        }
-    }
-
-    impl Pet for Cat {
-        fn name(&self) -> String {
-            String::from("The cat")
-        }
-    }
+    // `Pet`, `Dog` and `Cat` live in pets.rs, where `Pet` grows a few more methods.
 
     fn greet<P: Pet>(pet: &P) {
         println!("Who's a cutie? {} is!", pet.name());
     }
 
-    let fido = Dog{ name: "Fido".into() };
+    let fido = Dog{ name: "Fido".into(), tricks: Vec::new() };
     greet(&fido);
 
-    let captain_floof = Cat;
+    let captain_floof = Cat { name: "The cat".into() };
     greet(&captain_floof);
 
 
@@ -94,6 +80,34 @@ fn main() {
     println!("{}", std::mem::size_of::<&dyn Pet1>());
     println!("{}", std::mem::size_of::<Box<dyn Pet1>>());
 
+    // The same idea at scale: pets.rs builds `Box<dyn Pet>` from config records by species.
+    let mut registry = PetRegistry::with_builtins();
+    // Factories are closures, so a species can be a preconfigured variant of another.
+    let puppy_tricks = vec!["chew shoes".to_string()];
+    registry
+        .register("puppy", move |name| Box::new(Dog { name: name.to_string(), tricks: puppy_tricks.clone() }))
+        .unwrap();
+    println!("{registry:?}");
+    let config = "# species: name\ndog: Fido\ncat: Captain Floof\npuppy: Biscuit";
+    match PetRecord::parse_config(config).and_then(|records| registry.build_all(&records)) {
+        Ok(mut pets) => {
+            for pet in &pets {
+                println!("{pet:?}: {} ({})", pet.describe(), pet.sound());
+            }
+            if let Some(dog) = pets[0].downcast_mut::<Dog>() {
+                dog.tricks.push("roll over".to_string());
+            }
+            let dogs = pets.iter().filter(|pet| pet.is::<Dog>()).count();
+            let fido = pets.swap_remove(0).downcast::<Dog>().map(|dog| dog.tricks);
+            println!("{dogs} dogs, Fido knows {fido:?}");
+            println!("{:?}", pets[0].downcast_ref::<Dog>().map(|dog| &dog.tricks));
+        }
+        Err(e) => println!("{e}"),
+    }
+    if let Err(e) = registry.build(&PetRecord::new("dragon", "Smaug")) {
+        println!("{e}");
+    }
+
 
 
 
@@ -119,6 +133,35 @@ fn main() {
     let p2 = p1.clone();
     println!("Is {:?}]]nequal to {:?}?\nThe answer is {}", &p1, &p2, if p1 == p2 {"yes"} else {"no"});
 
+    // The derived PartialEq compares every field; compare.rs builds other orders without a derive.
+    let mut players = vec![
+        Player { name: "Cleo".into(), strength: 7, hit_points: 30 },
+        Player { name: "Abe".into(), strength: 9, hit_points: 20 },
+        Player { name: "Bea".into(), strength: 7, hit_points: 50 },
+    ];
+    let by_strength = compare::by_key(|p: &Player| p.strength);
+    println!("Cleo and Bea equally strong: {}", by_strength.equal(&players[0], &players[2]));
+    by_strength.reversed().then(compare::by_ref_key(|p: &Player| &p.name)).sort(&mut players);
+    println!("strongest first: {players:?}");
+    let toughest = compare::by_key(|p: &Player| p.hit_points).then_by_key(|p: &Player| std::cmp::Reverse(p.strength));
+    println!("toughest: {:?}", toughest.max(&players).map(|p| &p.name));
+
+    #[derive(Debug)]
+    struct Score {
+        player: String,
+        points: u32,
+        time_ms: u32,
+    }
+    // Most points first, the faster run breaking ties.
+    compare::impl_ord_by!(Score: |s| std::cmp::Reverse(s.points), |s| s.time_ms, |s| &s.player);
+    let mut scores = vec![
+        Score { player: "Abe".into(), points: 10, time_ms: 900 },
+        Score { player: "Bea".into(), points: 10, time_ms: 800 },
+        Score { player: "Cleo".into(), points: 12, time_ms: 1000 },
+    ];
+    scores.sort();
+    println!("{scores:?}, Bea beats Abe: {}", scores[1] < scores[2]);
+
 
 
 
@@ -157,6 +200,19 @@ fn main() {
     println!("{a:?} equals {b:?}: {}", a.equal(&b));
     println!("{a:?} not_equals {b:?}: {}", a.not_equal(&b));
 
+    // compare.rs lets `Equals` take another type on the right, so units mix.
+    let cm = compare::Centimeter(254);
+    let inches = compare::Inch(100);
+    println!("{cm:?} equals {inches:?}: {}, < {:?}: {}", cm.equal(&inches), compare::Meter(3), cm < compare::Meter(3));
+    println!("{cm:?} not_equals {:?}: {}", compare::Millimeter(2540), cm.not_equal(&compare::Millimeter(2540)));
+    let sum = 0.1 + 0.2;
+    println!("{sum} == 0.3: {}, within 1 ULP: {}, within 1e-9: {}, ULPs apart: {:?}",
+        sum == 0.3,
+        sum.approx_eq(0.3, Tolerance::Ulps(1)),
+        sum.approx_eq(0.3, Tolerance::Epsilon { absolute: 0.0, relative: 1e-9 }),
+        sum.ulps_between(0.3),
+    );
+
     trait Equals1 {
         fn equal(&self, other: &Self) -> bool;
     }
@@ -171,6 +227,18 @@ fn main() {
         }
     }
 
+    // Any type that implements `Equals1` now gets `not_equal` from the blanket impl.
+    #[derive(Debug)]
+    struct Foot(i16);
+
+    impl Equals1 for Foot {
+        fn equal(&self, other: &Foot) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    println!("{:?} not_equals {:?}: {}", Foot(4), Foot(4), Foot(4).not_equal(&Foot(4)));
+
 
 
 
@@ -195,6 +263,7 @@ fn main() {
         x.into() + 42_000_000
     }
 
+    #[allow(clippy::disallowed_names)]
     let foo = String::from("foo");
     let pair = duplicate(foo);
 
@@ -0,0 +1,304 @@
+// Pets built by species name.
+//
+// `PetRegistry` maps a species to a factory, so config records like
+// "dog: Fido" become `Box<dyn Pet>` without the caller naming a concrete
+// type. New species are added by registering another factory; nothing in
+// this module needs to change. When the concrete type does matter, a
+// `dyn Pet` can be downcast back to it, since every `Pet` is also `Any`.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Something with a name, as in 24.0, extended for the registry. `Any` is a
+/// supertrait so that trait objects can be downcast; it rules out pets that
+/// borrow data.
+pub trait Pet: Any {
+    fn name(&self) -> String;
+    fn species(&self) -> &'static str;
+    fn sound(&self) -> String;
+
+    fn describe(&self) -> String {
+        format!("{} the {} says {}", self.name(), self.species(), self.sound())
+    }
+}
+
+impl dyn Pet {
+    pub fn is<T: Pet>(&self) -> bool {
+        (self as &dyn Any).is::<T>()
+    }
+
+    pub fn downcast_ref<T: Pet>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref()
+    }
+
+    pub fn downcast_mut<T: Pet>(&mut self) -> Option<&mut T> {
+        (self as &mut dyn Any).downcast_mut()
+    }
+
+    /// Gives the box back unchanged if it holds some other type.
+    pub fn downcast<T: Pet>(self: Box<Self>) -> Result<Box<T>, Box<dyn Pet>> {
+        if self.is::<T>() {
+            let any: Box<dyn Any> = self;
+            Ok(any.downcast().expect("type checked above"))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Debug for dyn Pet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}({:?})", self.species(), self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dog {
+    pub name: String,
+    pub tricks: Vec<String>,
+}
+
+impl Pet for Dog {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn species(&self) -> &'static str {
+        "dog"
+    }
+
+    fn sound(&self) -> String {
+        String::from("Woof")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cat {
+    pub name: String,
+}
+
+impl Pet for Cat {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn species(&self) -> &'static str {
+        "cat"
+    }
+
+    fn sound(&self) -> String {
+        String::from("Meow")
+    }
+
+    fn describe(&self) -> String {
+        format!("{} the cat ignores you", self.name)
+    }
+}
+
+/// One pet from a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PetRecord {
+    pub species: String,
+    pub name: String,
+}
+
+impl PetRecord {
+    pub fn new(species: &str, name: &str) -> Self {
+        PetRecord { species: species.to_string(), name: name.to_string() }
+    }
+
+    /// Parses one `species: name` record per line. Blank lines and lines
+    /// starting with '#' are skipped.
+    pub fn parse_config(text: &str) -> Result<Vec<PetRecord>, PetError> {
+        let mut records = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |message| PetError::InvalidRecord { line: index + 1, message };
+            let (species, name) = line.split_once(':').ok_or(invalid("expected 'species: name'"))?;
+            let (species, name) = (species.trim(), name.trim());
+            if species.is_empty() {
+                return Err(invalid("missing species"));
+            }
+            if name.is_empty() {
+                return Err(invalid("missing name"));
+            }
+            records.push(PetRecord::new(species, name));
+        }
+        Ok(records)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PetError {
+    DuplicateSpecies(String),
+    UnknownSpecies { species: String, known: Vec<String> },
+    /// `line` counts from 1.
+    InvalidRecord { line: usize, message: &'static str },
+}
+
+impl fmt::Display for PetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PetError::DuplicateSpecies(species) => write!(f, "species {species:?} is already registered"),
+            PetError::UnknownSpecies { species, known } => {
+                write!(f, "unknown species {species:?}, known: {}", known.join(", "))
+            }
+            PetError::InvalidRecord { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for PetError {}
+
+/// Builds a pet from its name.
+pub type Factory = Box<dyn Fn(&str) -> Box<dyn Pet>>;
+
+#[derive(Default)]
+pub struct PetRegistry {
+    factories: BTreeMap<String, Factory>,
+}
+
+impl PetRegistry {
+    pub fn new() -> Self {
+        PetRegistry::default()
+    }
+
+    /// A registry that already knows dogs and cats.
+    pub fn with_builtins() -> Self {
+        let mut registry = PetRegistry::new();
+        registry
+            .register("dog", |name| Box::new(Dog { name: name.to_string(), tricks: Vec::new() }))
+            .expect("empty registry");
+        registry.register("cat", |name| Box::new(Cat { name: name.to_string() })).expect("new species");
+        registry
+    }
+
+    /// Species names are case-insensitive.
+    pub fn register(&mut self, species: &str, factory: impl Fn(&str) -> Box<dyn Pet> + 'static) -> Result<(), PetError> {
+        let species = species.to_lowercase();
+        if self.factories.contains_key(&species) {
+            return Err(PetError::DuplicateSpecies(species));
+        }
+        self.factories.insert(species, Box::new(factory));
+        Ok(())
+    }
+
+    /// Registered species, sorted.
+    pub fn species(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+
+    pub fn build(&self, record: &PetRecord) -> Result<Box<dyn Pet>, PetError> {
+        match self.factories.get(&record.species.to_lowercase()) {
+            Some(factory) => Ok(factory(&record.name)),
+            None => Err(PetError::UnknownSpecies {
+                species: record.species.clone(),
+                known: self.species().map(str::to_string).collect(),
+            }),
+        }
+    }
+
+    /// All or nothing: the first unknown species fails the whole list.
+    pub fn build_all<'r>(&self, records: impl IntoIterator<Item = &'r PetRecord>) -> Result<Vec<Box<dyn Pet>>, PetError> {
+        records.into_iter().map(|record| self.build(record)).collect()
+    }
+}
+
+impl fmt::Debug for PetRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PetRegistry").field("species", &self.species().collect::<Vec<_>>()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Parrot {
+        name: String,
+        phrase: &'static str,
+    }
+
+    impl Pet for Parrot {
+        fn name(&self) -> String {
+            self.name.clone()
+        }
+
+        fn species(&self) -> &'static str {
+            "parrot"
+        }
+
+        fn sound(&self) -> String {
+            format!("\"{}\"", self.phrase)
+        }
+    }
+
+    #[test]
+    fn test_build_from_config() {
+        let config = "# our pets\ndog: Fido\n\nCat: Captain Floof\n  dog :Rex  \n";
+        let records = PetRecord::parse_config(config).unwrap();
+        assert_eq!(records[1], PetRecord::new("Cat", "Captain Floof"));
+
+        let pets = PetRegistry::with_builtins().build_all(&records).unwrap();
+        let described: Vec<String> = pets.iter().map(|pet| pet.describe()).collect();
+        assert_eq!(described, ["Fido the dog says Woof", "Captain Floof the cat ignores you", "Rex the dog says Woof"]);
+        assert_eq!(format!("{pets:?}"), r#"[dog("Fido"), cat("Captain Floof"), dog("Rex")]"#);
+    }
+
+    #[test]
+    fn test_register_species() {
+        let mut registry = PetRegistry::with_builtins();
+        registry.register("Parrot", |name| Box::new(Parrot { name: name.to_string(), phrase: "Hello" })).unwrap();
+        assert_eq!(registry.species().collect::<Vec<_>>(), ["cat", "dog", "parrot"]);
+        assert_eq!(
+            registry.register("DOG", |name| Box::new(Cat { name: name.to_string() })).unwrap_err(),
+            PetError::DuplicateSpecies("dog".to_string())
+        );
+
+        let polly = registry.build(&PetRecord::new("parrot", "Polly")).unwrap();
+        assert_eq!(polly.describe(), "Polly the parrot says \"Hello\"");
+
+        let error = registry.build(&PetRecord::new("goldfish", "Bubbles")).unwrap_err();
+        assert_eq!(error.to_string(), "unknown species \"goldfish\", known: cat, dog, parrot");
+        let records = [PetRecord::new("dog", "Fido"), PetRecord::new("goldfish", "Bubbles")];
+        assert!(registry.build_all(&records).is_err());
+    }
+
+    #[test]
+    fn test_invalid_records() {
+        assert_eq!(
+            PetRecord::parse_config("dog: Fido\nFido"),
+            Err(PetError::InvalidRecord { line: 2, message: "expected 'species: name'" })
+        );
+        assert_eq!(
+            PetRecord::parse_config(": Fido"),
+            Err(PetError::InvalidRecord { line: 1, message: "missing species" })
+        );
+        assert_eq!(
+            PetRecord::parse_config("\n\ncat:  "),
+            Err(PetError::InvalidRecord { line: 3, message: "missing name" })
+        );
+    }
+
+    #[test]
+    fn test_downcast() {
+        let registry = PetRegistry::with_builtins();
+        let mut pet = registry.build(&PetRecord::new("dog", "Fido")).unwrap();
+        assert!(pet.is::<Dog>());
+        assert!(pet.downcast_ref::<Cat>().is_none());
+
+        pet.downcast_mut::<Dog>().unwrap().tricks.push("sit".to_string());
+        assert_eq!(pet.downcast_ref::<Dog>().unwrap().tricks, ["sit"]);
+
+        let pet = match pet.downcast::<Cat>() {
+            Ok(_) => panic!("a dog is not a cat"),
+            Err(pet) => pet,
+        };
+        let dog: Box<Dog> = pet.downcast().unwrap();
+        assert_eq!(*dog, Dog { name: "Fido".to_string(), tricks: vec!["sit".to_string()] });
+    }
+}